@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::llm;
+use crate::models::{Event, TitleMode};
+use crate::musicbrainz;
+use crate::scraping::base::fuzzy_name_key;
+use crate::utils;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("config io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("config parse error: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// What scrapers do when a listing has no show time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingShowTime {
+    /// Assume this local time, e.g. "7:00 PM".
+    At(String),
+    /// Keep the event date-only; posts say "Doors TBA".
+    Tba,
+}
+
+impl Default for MissingShowTime {
+    fn default() -> Self {
+        MissingShowTime::At("7:00 PM".to_string())
+    }
+}
+
+/// How many billed artists get a MusicBrainz lookup per event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrichScope {
+    #[default]
+    HeadlinerOnly,
+    /// The first N artists in billing order.
+    TopN(u8),
+    All,
+}
+
+/// Window when scheduled posts are held back, as 24-hour "HH:MM" wall-clock
+/// times in `timezone`. The window may span midnight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+    pub timezone: String,
+}
+
+/// Background scrape settings. Posting stays manual either way.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    pub enabled: bool,
+    pub interval_minutes: u32,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 6 * 60,
+        }
+    }
+}
+
+/// How aggressively one show listed by two venues' sources is folded into a
+/// single event: starts within `window_minutes` of each other and headliners
+/// at least `min_name_similarity` alike (0.0–1.0). A zero window disables it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CrossVenueDedup {
+    pub window_minutes: u32,
+    pub min_name_similarity: f64,
+}
+
+impl Default for CrossVenueDedup {
+    fn default() -> Self {
+        Self {
+            window_minutes: 30,
+            min_name_similarity: 0.9,
+        }
+    }
+}
+
+/// Second fetch of each event's own page for price, description and image.
+/// Off by default since it costs one request per event; `max_concurrent`
+/// bounds how many run at once per venue.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DetailPages {
+    pub enabled: bool,
+    pub max_concurrent: usize,
+}
+
+impl Default for DetailPages {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_concurrent: 4,
+        }
+    }
+}
+
+/// User settings persisted as JSON at `utils::config_path()`. Missing fields fall
+/// back to their defaults so older config files keep loading.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// Compose style per Facebook group target, e.g. "concise" or "hype".
+    pub group_styles: HashMap<String, String>,
+    /// When false, prompts ask for no emoji and composed posts are stripped of them.
+    pub use_emoji: bool,
+    /// How previews and fallback posts mention support acts in the title.
+    pub title_mode: TitleMode,
+    /// Fallback for listings without a show time, unless the venue overrides it.
+    pub default_show_time: MissingShowTime,
+    /// Per-venue overrides of `default_show_time`, keyed by venue id.
+    pub venue_show_times: HashMap<String, MissingShowTime>,
+    /// Venue ids included in batch scrapes, in run order. Empty enables all.
+    pub enabled_venues: Vec<String>,
+    /// Replaces the built-in persona for real posts. Previews keep the
+    /// factual prompt.
+    pub system_prompt_override: Option<String>,
+    /// Model requests allowed in flight at once across batch operations.
+    pub llm_max_concurrency: usize,
+    /// strftime pattern for show times in fallback posts. Times stay in the
+    /// venue's offset whatever zone this machine is in.
+    pub post_date_format: String,
+    /// Scheduled posts landing in this window are pushed to its end. Manual
+    /// posts ignore it.
+    pub quiet_hours: Option<QuietHours>,
+    /// Posts and scheduled posts are refused for events starting sooner than
+    /// this many hours after they would go out, unless forced.
+    pub min_lead_hours: Option<u32>,
+    /// Extra attempts for MusicBrainz lookups answered with 503 or 429.
+    pub musicbrainz_retries: u32,
+    /// Also look up an artist photo for matched headliners, stored as
+    /// `extra.musicbrainz.image`. Costs one extra rate-limited request each.
+    pub musicbrainz_images: bool,
+    /// Artists `reenrich_all` looks up at once. Requests still go through
+    /// the MusicBrainz rate limit; more only helps when lookups are cached.
+    pub reenrich_concurrency: usize,
+    /// Artists per event whose genres are merged into its tags.
+    pub enrich_scope: EnrichScope,
+    /// Periodic scrapes; read fresh every cycle, so changes apply without a
+    /// restart.
+    pub scheduler: SchedulerConfig,
+    /// Scheduled scrapes delete events that started more than this many days
+    /// ago. Zero keeps everything.
+    pub auto_purge_days: u32,
+    /// Where snapshots and cached responses go. Defaults to `cache/` under
+    /// the data root.
+    pub cache_dir: Option<PathBuf>,
+    /// Size cap for the cache directory; least recently used entries are
+    /// evicted past it.
+    pub cache_max_mb: u64,
+    pub cross_venue_dedup: CrossVenueDedup,
+    /// Query string, e.g. `utm_source=fb`, appended to ticket and event links
+    /// in composed posts. Stored events keep their original URLs.
+    pub ticket_link_suffix: Option<String>,
+    /// When a listing gives only a doors time, the show is assumed to start
+    /// this many minutes later.
+    pub doors_to_show_minutes: u32,
+    /// Event fields included in the JSON the model sees. Unknown names are
+    /// ignored.
+    pub llm_payload_fields: Vec<String>,
+    /// Acts never to promote. Matched against headliners ignoring case,
+    /// punctuation and asides like "(Live)".
+    pub artist_blocklist: Vec<String>,
+    pub detail_pages: DetailPages,
+    /// Keep "Artist feat. Guest" and "Artist with Guest" together as the
+    /// headliner's display name. Guests are still looked up separately.
+    pub keep_collaborations: bool,
+    /// Artists kept per scraped event, headliner first; guards against
+    /// broken markup splitting into dozens of fragments.
+    pub max_artists_per_event: usize,
+    /// Composed posts longer than this, or blank, are replaced by the
+    /// template draft.
+    pub max_composed_chars: usize,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            group_styles: HashMap::new(),
+            use_emoji: true,
+            title_mode: TitleMode::default(),
+            default_show_time: MissingShowTime::default(),
+            venue_show_times: HashMap::new(),
+            enabled_venues: Vec::new(),
+            system_prompt_override: None,
+            llm_max_concurrency: llm::DEFAULT_MAX_CONCURRENT_REQUESTS,
+            post_date_format: llm::DEFAULT_POST_DATE_FORMAT.to_string(),
+            quiet_hours: None,
+            min_lead_hours: None,
+            musicbrainz_retries: musicbrainz::DEFAULT_RETRIES,
+            musicbrainz_images: false,
+            reenrich_concurrency: 1,
+            enrich_scope: EnrichScope::default(),
+            scheduler: SchedulerConfig::default(),
+            auto_purge_days: 0,
+            cache_dir: None,
+            cache_max_mb: utils::cache::DEFAULT_MAX_MB,
+            cross_venue_dedup: CrossVenueDedup::default(),
+            ticket_link_suffix: None,
+            doors_to_show_minutes: crate::scraping::base::DEFAULT_DOORS_TO_SHOW_MINUTES,
+            llm_payload_fields: llm::DEFAULT_PAYLOAD_FIELDS
+                .iter()
+                .map(|field| field.to_string())
+                .collect(),
+            artist_blocklist: Vec::new(),
+            detail_pages: DetailPages::default(),
+            keep_collaborations: false,
+            max_artists_per_event: crate::scraping::base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+            max_composed_chars: llm::DEFAULT_MAX_COMPOSED_CHARS,
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_from(&utils::config_path())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path)?;
+        if raw.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&raw)?)
+    }
+
+    pub fn save(&self) -> Result<(), ConfigError> {
+        self.save_to(&utils::config_path())
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), ConfigError> {
+        utils::ensure_parent(path);
+        let raw = serde_json::to_string_pretty(self)?;
+        fs::write(path, raw)?;
+        Ok(())
+    }
+
+    pub fn style_for_group(&self, group_id: &str) -> Option<&str> {
+        self.group_styles
+            .get(group_id)
+            .map(String::as_str)
+            .filter(|style| !style.trim().is_empty())
+    }
+
+    /// The configured system prompt, ignoring blank values.
+    pub fn system_prompt(&self) -> Option<&str> {
+        self.system_prompt_override
+            .as_deref()
+            .filter(|prompt| !prompt.trim().is_empty())
+    }
+
+    pub fn show_time_for_venue(&self, venue_id: &str) -> &MissingShowTime {
+        self.venue_show_times
+            .get(venue_id)
+            .unwrap_or(&self.default_show_time)
+    }
+
+    /// Whether the event's headliner is on `artist_blocklist`.
+    pub fn blocks_headliner(&self, event: &Event) -> bool {
+        let headliner = fuzzy_name_key(&event.title());
+        !headliner.is_empty()
+            && self
+                .artist_blocklist
+                .iter()
+                .any(|blocked| fuzzy_name_key(blocked) == headliner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocklist_matches_normalized_headliners_only() {
+        let config = AppConfig {
+            artist_blocklist: vec!["Bad Act".to_string()],
+            ..AppConfig::default()
+        };
+        let mut event = crate::db::sample_event("venus", "Venus", chrono::Utc::now());
+        event.artists = vec!["BAD ACT (Live)".to_string()];
+        assert!(config.blocks_headliner(&event));
+
+        event.artists = vec!["Bad Actors".to_string()];
+        assert!(!config.blocks_headliner(&event));
+        event.artists = vec!["Opener".to_string(), "Bad Act".to_string()];
+        assert!(!config.blocks_headliner(&event));
+    }
+
+    #[test]
+    fn config_round_trips_through_disk() {
+        let path = std::env::temp_dir().join(format!(
+            "show-scrape-config-{}/config.json",
+            std::process::id()
+        ));
+        let mut config = AppConfig::default();
+        config
+            .group_styles
+            .insert("boise-ravers".to_string(), "hype".to_string());
+        config.save_to(&path).expect("save config");
+
+        let loaded = AppConfig::load_from(&path).expect("load config");
+        assert_eq!(loaded.style_for_group("boise-ravers"), Some("hype"));
+        assert_eq!(loaded.style_for_group("unknown"), None);
+        let _ = fs::remove_dir_all(path.parent().expect("parent"));
+    }
+
+    #[test]
+    fn missing_config_loads_defaults() {
+        let path = std::env::temp_dir().join("show-scrape-missing/config.json");
+        let config = AppConfig::load_from(&path).expect("defaults");
+        assert!(config.group_styles.is_empty());
+        assert!(config.use_emoji);
+        assert_eq!(
+            config.show_time_for_venue("treefort"),
+            &MissingShowTime::At("7:00 PM".to_string())
+        );
+    }
+
+    #[test]
+    fn venue_show_time_overrides_global_default() {
+        let mut config = AppConfig {
+            default_show_time: MissingShowTime::At("8:00 PM".to_string()),
+            ..AppConfig::default()
+        };
+        config
+            .venue_show_times
+            .insert("knitboise".to_string(), MissingShowTime::Tba);
+
+        assert_eq!(
+            config.show_time_for_venue("knitboise"),
+            &MissingShowTime::Tba
+        );
+        assert_eq!(
+            config.show_time_for_venue("treefort"),
+            &MissingShowTime::At("8:00 PM".to_string())
+        );
+    }
+}
@@ -1,11 +1,24 @@
-use chrono::{DateTime, Duration, Local, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Local, SecondsFormat, Utc};
 use rusqlite::{params, Connection};
 use serde_json::json;
+use sha2::{Digest, Sha256};
 
-use crate::models::Event;
-use crate::musicbrainz::ArtistProfile;
+use crate::models::{AgeRestriction, Event};
+use crate::musicbrainz::{canonical_genre_key, ArtistProfile};
+use crate::scraping::base::{event_id, fuzzy_name_key};
 use crate::utils;
 
+/// Events whose enrichment found nothing wait this long before being retried,
+/// so unmatched artists aren't looked up on every pass.
+const ENRICH_RETRY_AFTER_HOURS: i64 = 24;
+
+/// `PRAGMA user_version` once event ids use the normalized headliner from
+/// `base::event_id` instead of the raw first artist.
+const NORMALIZED_IDS_VERSION: i64 = 1;
+
 pub struct Store {
     conn: Connection,
 }
@@ -14,17 +27,60 @@ pub struct PendingEvent {
     pub event: Event,
 }
 
+#[derive(Debug, Clone)]
+pub struct ScrapeRun {
+    pub venue_id: String,
+    pub ran_at_utc: String,
+    pub event_count: i64,
+    pub error: Option<String>,
+    pub selector_warning: Option<String>,
+}
+
+/// Outcome of an auto-purge, for the settings page.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PurgeRun {
+    pub ran_at_utc: String,
+    pub removed: i64,
+}
+
+/// One row of the post log. An event posted to several groups has a row per
+/// destination.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PostRecord {
+    pub post_id: String,
+    pub event_id: String,
+    pub group_id: Option<String>,
+    pub fb_object_id: Option<String>,
+    pub created_at_utc: Option<String>,
+    pub status: Option<String>,
+    pub response_json: Option<String>,
+}
+
 impl Store {
     pub fn open_default() -> rusqlite::Result<Self> {
         let path = utils::database_path();
         utils::ensure_parent(&path);
+        Self::open_at(&path)
+    }
+
+    pub fn open_at(path: &Path) -> rusqlite::Result<Self> {
         let conn = Connection::open(path)?;
+        // Commands each open their own connection, so writers can overlap.
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
         let store = Self { conn };
         store.init_schema()?;
         store.seed_if_empty()?;
         Ok(store)
     }
 
+    /// Throwaway store for tests and the mock self-test; nothing is seeded.
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        let conn = Connection::open_in_memory()?;
+        let store = Self { conn };
+        store.init_schema()?;
+        Ok(store)
+    }
+
     fn init_schema(&self) -> rusqlite::Result<()> {
         self.conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS events(
@@ -42,12 +98,150 @@ impl Store {
                 status TEXT,
                 response_json TEXT
             );
+            CREATE TABLE IF NOT EXISTS scrape_runs(
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                venue_id TEXT NOT NULL,
+                ran_at_utc TEXT NOT NULL,
+                event_count INTEGER NOT NULL,
+                error TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_scrape_runs_venue ON scrape_runs(venue_id);
             CREATE TABLE IF NOT EXISTS musicbrainz_cache(
                 artist_key TEXT PRIMARY KEY,
                 profile_json TEXT NOT NULL,
                 fetched_at_utc TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS event_aliases(
+                alias_id TEXT PRIMARY KEY,
+                event_id TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS purge_runs(
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                ran_at_utc TEXT NOT NULL,
+                removed INTEGER NOT NULL
             );",
         )?;
+        self.ensure_column("scrape_runs", "selector_warning", "TEXT")?;
+        self.ensure_column("posts", "group_id", "TEXT")?;
+        self.ensure_column("events", "enrich_attempted_utc", "TEXT")?;
+        // Copies of payload fields for indexed lookups; the payload stays
+        // authoritative and these are rewritten with it.
+        self.ensure_column("events", "headliner", "TEXT")?;
+        self.ensure_column("events", "venue_id", "TEXT")?;
+        self.ensure_column("events", "start_utc", "TEXT")?;
+        self.conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_events_headliner ON events(headliner);
+             CREATE INDEX IF NOT EXISTS idx_events_venue ON events(venue_id);
+             CREATE INDEX IF NOT EXISTS idx_events_start ON events(start_utc);",
+        )?;
+        self.backfill_event_columns()?;
+        self.migrate_event_ids()
+    }
+
+    /// Re-keys rows stored under the old raw-headliner id so a rescrape finds
+    /// them instead of inserting the show again as pending. Rows that now share
+    /// an id are merged. Runs once per database.
+    fn migrate_event_ids(&self) -> rusqlite::Result<()> {
+        let version: i64 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version >= NORMALIZED_IDS_VERSION {
+            return Ok(());
+        }
+        let rows = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT id, payload FROM events ORDER BY first_seen_utc, id")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        for (id, payload) in rows {
+            let Ok(event) = serde_json::from_str::<Event>(&payload) else {
+                continue;
+            };
+            let Ok(start) = DateTime::parse_from_rfc3339(&event.start_utc) else {
+                continue;
+            };
+            // Seeded, merged and already-normalized rows keep their ids.
+            if id != legacy_event_id(&event) {
+                continue;
+            }
+            let new_id = event_id(&event.venue_id, &start.with_timezone(&Utc), &event.artists);
+            if new_id != id {
+                self.rekey_event(&id, event, &new_id)?;
+            }
+        }
+        self.conn
+            .execute_batch(&format!("PRAGMA user_version = {NORMALIZED_IDS_VERSION}"))
+    }
+
+    /// Moves an event and its post log to `new_id`, folding it into the row
+    /// already stored there if there is one.
+    fn rekey_event(&self, old_id: &str, mut event: Event, new_id: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "UPDATE posts SET event_id = ?2 WHERE event_id = ?1",
+            params![old_id, new_id],
+        )?;
+        match self.get_event(new_id) {
+            Ok(mut kept) => {
+                kept.absorb(&event);
+                self.merge_into(&kept, old_id)
+            }
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                event.id = new_id.to_string();
+                let payload = serde_json::to_string(&event).expect("event serialization");
+                self.conn.execute(
+                    "UPDATE events SET id = ?2, payload = ?3 WHERE id = ?1",
+                    params![old_id, new_id, payload],
+                )?;
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Fills `headliner`, `venue_id` and `start_utc` for rows written before
+    /// those columns existed. Unreadable payloads are left for
+    /// `list_corrupt_event_ids`.
+    fn backfill_event_columns(&self) -> rusqlite::Result<()> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, payload FROM events
+             WHERE headliner IS NULL OR venue_id IS NULL OR start_utc IS NULL",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut missing = Vec::new();
+        for row in rows {
+            let (id, payload) = row?;
+            if let Ok(event) = serde_json::from_str::<Event>(&payload) {
+                missing.push((id, headliner_key(&event), start_key(&event), event.venue_id));
+            }
+        }
+        for (id, headliner, start, venue_id) in missing {
+            self.conn.execute(
+                "UPDATE events SET headliner = ?2, venue_id = ?3, start_utc = ?4 WHERE id = ?1",
+                params![id, headliner, venue_id, start],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Adds a column to a table created by an older build.
+    fn ensure_column(&self, table: &str, column: &str, decl: &str) -> rusqlite::Result<()> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({table})"))?;
+        let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+        for name in names {
+            if name? == column {
+                return Ok(());
+            }
+        }
+        self.conn.execute(
+            &format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"),
+            [],
+        )?;
         Ok(())
     }
 
@@ -73,43 +267,293 @@ impl Store {
         Ok(())
     }
 
+    /// Inserts or replaces an event. Scraped events carry no note and usually
+    /// no genres, so the stored note and enriched tags are kept unless `event`
+    /// brings its own. An id merged away by `merge_into` only refreshes the
+    /// surviving row's last-seen time, so the duplicate doesn't come back.
     pub fn upsert_event(&self, event: &Event) -> rusqlite::Result<()> {
         let now = Utc::now().to_rfc3339();
+        if let Some(kept_id) = self.alias_target(&event.id)? {
+            self.conn.execute(
+                "UPDATE events SET last_seen_utc = ?2 WHERE id = ?1",
+                params![kept_id, now],
+            )?;
+            return Ok(());
+        }
+        let merged;
+        let event = match self.stored_event(&event.id)? {
+            Some(stored)
+                if (event.note.is_none() && stored.note.is_some())
+                    || (event.tags.is_empty() && !stored.tags.is_empty()) =>
+            {
+                merged = Event {
+                    note: event.note.clone().or(stored.note),
+                    tags: if event.tags.is_empty() {
+                        stored.tags
+                    } else {
+                        event.tags.clone()
+                    },
+                    ..event.clone()
+                };
+                &merged
+            }
+            _ => event,
+        };
         let payload = serde_json::to_string(event).expect("event serialization");
         self.conn.execute(
-            "INSERT INTO events (id, payload, first_seen_utc, last_seen_utc, posted_at_utc)
-             VALUES (?1, ?2, ?3, ?3, NULL)
+            "INSERT INTO events
+               (id, payload, first_seen_utc, last_seen_utc, posted_at_utc,
+                headliner, venue_id, start_utc)
+             VALUES (?1, ?2, ?3, ?3, NULL, ?4, ?5, ?6)
              ON CONFLICT(id) DO UPDATE SET
                payload = excluded.payload,
-               last_seen_utc = excluded.last_seen_utc",
-            params![event.id, payload, now],
+               last_seen_utc = excluded.last_seen_utc,
+               headliner = excluded.headliner,
+               venue_id = excluded.venue_id,
+               start_utc = excluded.start_utc",
+            params![
+                event.id,
+                payload,
+                now,
+                headliner_key(event),
+                event.venue_id,
+                start_key(event)
+            ],
         )?;
         Ok(())
     }
 
+    /// Unposted events. Rows whose payload no longer deserializes are logged
+    /// and skipped so one bad row can't hide the rest; see
+    /// `list_corrupt_event_ids`.
     pub fn list_pending_events(&self) -> rusqlite::Result<Vec<PendingEvent>> {
         let mut stmt = self
             .conn
-            .prepare("SELECT payload FROM events WHERE posted_at_utc IS NULL")?;
+            .prepare("SELECT id, payload FROM events WHERE posted_at_utc IS NULL")?;
         let rows = stmt.query_map([], |row| {
-            let payload: String = row.get(0)?;
-            let event: Event = serde_json::from_str(&payload).map_err(|err| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    payload.len(),
-                    rusqlite::types::Type::Text,
-                    Box::new(err),
-                )
-            })?;
-            Ok(PendingEvent { event })
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, payload) = row?;
+            match serde_json::from_str::<Event>(&payload) {
+                Ok(event) => out.push(PendingEvent { event }),
+                Err(err) => eprintln!("skipping unreadable event {id}: {err}"),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Ids of events whose stored payload no longer deserializes, for cleanup.
+    pub fn list_corrupt_event_ids(&self) -> rusqlite::Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, payload FROM events ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, payload) = row?;
+            if serde_json::from_str::<Event>(&payload).is_err() {
+                out.push(id);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Unposted upcoming events with no tags yet, soonest first, skipping any
+    /// whose enrichment was attempted within the retry window.
+    pub fn list_events_missing_genres(
+        &self,
+        limit: usize,
+        now: DateTime<Utc>,
+    ) -> rusqlite::Result<Vec<Event>> {
+        let retry_cutoff = (now - Duration::hours(ENRICH_RETRY_AFTER_HOURS)).to_rfc3339();
+        let mut stmt = self.conn.prepare(
+            "SELECT payload FROM events
+             WHERE posted_at_utc IS NULL
+               AND (enrich_attempted_utc IS NULL OR enrich_attempted_utc < ?1)",
+        )?;
+        let rows = stmt.query_map(params![retry_cutoff], |row| row.get::<_, String>(0))?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let Ok(event) = serde_json::from_str::<Event>(&row?) else {
+                continue;
+            };
+            let upcoming =
+                DateTime::parse_from_rfc3339(&event.start_utc).is_ok_and(|start| start >= now);
+            if upcoming && event.tags.is_empty() {
+                out.push(event);
+            }
+        }
+        out.sort_by(|a, b| a.start_utc.cmp(&b.start_utc));
+        out.truncate(limit);
+        Ok(out)
+    }
+
+    /// Upcoming events, posted or not, tagged with `tag` after case and
+    /// synonym folding, soonest first.
+    pub fn list_events_by_tag(
+        &self,
+        tag: &str,
+        now: DateTime<Utc>,
+    ) -> rusqlite::Result<Vec<Event>> {
+        let wanted = canonical_genre_key(tag);
+        Ok(self
+            .list_upcoming_events(now, None, false)?
+            .into_iter()
+            .filter(|event| event.tags.iter().any(|t| canonical_genre_key(t) == wanted))
+            .collect())
+    }
+
+    /// Events still ahead at `now` (see `Event::is_upcoming`) starting no
+    /// later than `until`, soonest first. `pending_only` leaves out posted
+    /// ones. Filters on the indexed `start_utc` column, so rows whose start
+    /// doesn't parse never show up.
+    pub fn list_upcoming_events(
+        &self,
+        now: DateTime<Utc>,
+        until: Option<DateTime<Utc>>,
+        pending_only: bool,
+    ) -> rusqlite::Result<Vec<Event>> {
+        Ok(self
+            .upcoming_rows(now, until, pending_only)?
+            .into_iter()
+            .map(|row| row.event)
+            .collect())
+    }
+
+    /// Pending upcoming events up to `until`, keyed by the `venue_id`
+    /// column, each list soonest first.
+    pub fn list_upcoming_by_venue(
+        &self,
+        now: DateTime<Utc>,
+        until: Option<DateTime<Utc>>,
+    ) -> rusqlite::Result<HashMap<String, Vec<Event>>> {
+        let mut grouped: HashMap<String, Vec<Event>> = HashMap::new();
+        for row in self.upcoming_rows(now, until, true)? {
+            grouped.entry(row.venue_id).or_default().push(row.event);
+        }
+        Ok(grouped)
+    }
+
+    /// Pending upcoming events grouped by the normalized `headliner` column,
+    /// groups ordered by their soonest show, so each artist is handled once.
+    pub fn list_upcoming_by_headliner(
+        &self,
+        now: DateTime<Utc>,
+    ) -> rusqlite::Result<Vec<(String, Vec<Event>)>> {
+        let mut groups: Vec<(String, Vec<Event>)> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for row in self.upcoming_rows(now, None, true)? {
+            match index.get(&row.headliner) {
+                Some(&at) => groups[at].1.push(row.event),
+                None => {
+                    index.insert(row.headliner.clone(), groups.len());
+                    groups.push((row.headliner, vec![row.event]));
+                }
+            }
+        }
+        Ok(groups)
+    }
+
+    fn upcoming_rows(
+        &self,
+        now: DateTime<Utc>,
+        until: Option<DateTime<Utc>>,
+        pending_only: bool,
+    ) -> rusqlite::Result<Vec<UpcomingRow>> {
+        // Date-only shows sit at their day's midnight, so look back a day and
+        // let `is_upcoming` decide.
+        let from = time_key(now - Duration::days(1));
+        let until = until.map(time_key);
+        let mut stmt = self.conn.prepare(
+            "SELECT venue_id, headliner, payload FROM events
+             WHERE start_utc >= ?1
+               AND (?2 IS NULL OR start_utc <= ?2)
+               AND (?3 = 0 OR posted_at_utc IS NULL)
+             ORDER BY start_utc, id",
+        )?;
+        let rows = stmt.query_map(params![from, until, pending_only], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, String>(2)?,
+            ))
         })?;
 
         let mut out = Vec::new();
         for row in rows {
-            out.push(row?);
+            let (venue_id, headliner, payload) = row?;
+            let Ok(event) = serde_json::from_str::<Event>(&payload) else {
+                continue;
+            };
+            if event.is_upcoming(now) {
+                out.push(UpcomingRow {
+                    venue_id: venue_id.unwrap_or_else(|| event.venue_id.clone()),
+                    headliner: headliner.unwrap_or_else(|| headliner_key(&event)),
+                    event,
+                });
+            }
         }
         Ok(out)
     }
 
+    pub fn mark_enrich_attempted(&self, event_id: &str, at: DateTime<Utc>) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "UPDATE events SET enrich_attempted_utc = ?2 WHERE id = ?1",
+            params![event_id, at.to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    pub fn count_events(&self) -> rusqlite::Result<i64> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))
+    }
+
+    /// The surviving id an event was merged into, if `id` was merged away.
+    fn alias_target(&self, id: &str) -> rusqlite::Result<Option<String>> {
+        match self.conn.query_row(
+            "SELECT event_id FROM event_aliases WHERE alias_id = ?1",
+            params![id],
+            |row| row.get(0),
+        ) {
+            Ok(kept_id) => Ok(Some(kept_id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn stored_event(&self, id: &str) -> rusqlite::Result<Option<Event>> {
+        let payload: Option<String> = match self.conn.query_row(
+            "SELECT payload FROM events WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        ) {
+            Ok(payload) => Some(payload),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(err) => return Err(err),
+        };
+        Ok(payload.and_then(|payload| serde_json::from_str::<Event>(&payload).ok()))
+    }
+
+    /// Replaces an event's organizer note; `None` clears it.
+    pub fn set_event_note(&self, id: &str, note: Option<&str>) -> rusqlite::Result<()> {
+        let mut event = self.get_event(id)?;
+        event.note = note.map(str::to_string);
+        let payload = serde_json::to_string(&event).expect("event serialization");
+        self.conn.execute(
+            "UPDATE events SET payload = ?2 WHERE id = ?1",
+            params![id, payload],
+        )?;
+        Ok(())
+    }
+
     pub fn get_event(&self, id: &str) -> rusqlite::Result<Event> {
         let payload: String = self.conn.query_row(
             "SELECT payload FROM events WHERE id = ?1",
@@ -126,6 +570,84 @@ impl Store {
         Ok(event)
     }
 
+    /// When the event was marked posted, or `None` while it is pending.
+    pub fn posted_at(&self, event_id: &str) -> rusqlite::Result<Option<String>> {
+        self.conn.query_row(
+            "SELECT posted_at_utc FROM events WHERE id = ?1",
+            params![event_id],
+            |row| row.get(0),
+        )
+    }
+
+    /// Stores the merged payload under the surviving id and deletes the dropped
+    /// event with its post history. The survivor inherits the dropped event's
+    /// posted state and earliest first-seen time, and the dropped id is kept
+    /// as an alias so rescrapes of it land on the survivor.
+    pub fn merge_into(&self, merged: &Event, drop_id: &str) -> rusqlite::Result<()> {
+        let payload = serde_json::to_string(merged).expect("event serialization");
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE events SET
+               payload = ?2,
+               headliner = ?4,
+               venue_id = ?5,
+               start_utc = ?6,
+               posted_at_utc = COALESCE(posted_at_utc,
+                 (SELECT posted_at_utc FROM events WHERE id = ?3)),
+               first_seen_utc = MIN(first_seen_utc,
+                 COALESCE((SELECT first_seen_utc FROM events WHERE id = ?3), first_seen_utc))
+             WHERE id = ?1",
+            params![
+                merged.id,
+                payload,
+                drop_id,
+                headliner_key(merged),
+                merged.venue_id,
+                start_key(merged)
+            ],
+        )?;
+        tx.execute("DELETE FROM posts WHERE event_id = ?1", params![drop_id])?;
+        tx.execute("DELETE FROM events WHERE id = ?1", params![drop_id])?;
+        tx.execute(
+            "UPDATE event_aliases SET event_id = ?1 WHERE event_id = ?2",
+            params![merged.id, drop_id],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO event_aliases (alias_id, event_id) VALUES (?1, ?2)",
+            params![drop_id, merged.id],
+        )?;
+        tx.commit()
+    }
+
+    /// Deletes events that started before `cutoff`. Rows without a readable
+    /// start are kept. Their post log rows stay: the log is the posting
+    /// history that exports read. Returns how many events were removed.
+    pub fn purge_events_before(&self, cutoff: DateTime<Utc>) -> rusqlite::Result<usize> {
+        let mut stmt = self.conn.prepare("SELECT id, payload FROM events")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut expired = Vec::new();
+        for row in rows {
+            let (id, payload) = row?;
+            let start = serde_json::from_str::<serde_json::Value>(&payload)
+                .ok()
+                .and_then(|value| value["start_utc"].as_str().map(str::to_string))
+                .and_then(|start| DateTime::parse_from_rfc3339(&start).ok());
+            if start.is_some_and(|start| start < cutoff) {
+                expired.push(id);
+            }
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        for id in &expired {
+            tx.execute("DELETE FROM events WHERE id = ?1", params![id])?;
+            tx.execute("DELETE FROM event_aliases WHERE event_id = ?1", params![id])?;
+        }
+        tx.commit()?;
+        Ok(expired.len())
+    }
+
     pub fn mark_posted(&self, event_id: &str) -> rusqlite::Result<()> {
         let now = Utc::now().to_rfc3339();
         self.conn.execute(
@@ -135,6 +657,177 @@ impl Store {
         Ok(())
     }
 
+    /// Marks the event posted only if it is still pending. Returns false when
+    /// another caller already claimed it, so a double-submit can't post twice.
+    pub fn claim_for_posting(&self, event_id: &str) -> rusqlite::Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let changed = self.conn.execute(
+            "UPDATE events SET posted_at_utc = ?2, last_seen_utc = ?2
+             WHERE id = ?1 AND posted_at_utc IS NULL",
+            params![event_id, now],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Clears `posted_at_utc`, returning the event to pending. Returns false when
+    /// the event was not marked posted.
+    pub fn unmark_posted(&self, event_id: &str) -> rusqlite::Result<bool> {
+        let changed = self.conn.execute(
+            "UPDATE events SET posted_at_utc = NULL WHERE id = ?1 AND posted_at_utc IS NOT NULL",
+            params![event_id],
+        )?;
+        Ok(changed > 0)
+    }
+
+    pub fn record_post(
+        &self,
+        event_id: &str,
+        group_id: Option<&str>,
+        fb_object_id: Option<&str>,
+        status: &str,
+        response: &serde_json::Value,
+    ) -> rusqlite::Result<String> {
+        let now = Utc::now();
+        let post_id = match group_id {
+            Some(group) => format!("{event_id}:{group}:{}", now.timestamp_millis()),
+            None => format!("{event_id}:{}", now.timestamp_millis()),
+        };
+        let response_json = serde_json::to_string(response)
+            .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+        self.conn.execute(
+            "INSERT INTO posts (post_id, event_id, group_id, fb_object_id, created_at_utc, status, response_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                post_id,
+                event_id,
+                group_id,
+                fb_object_id,
+                now.to_rfc3339(),
+                status,
+                response_json
+            ],
+        )?;
+        Ok(post_id)
+    }
+
+    #[cfg(test)]
+    pub fn get_post(&self, post_id: &str) -> rusqlite::Result<PostRecord> {
+        self.conn.query_row(
+            "SELECT post_id, event_id, group_id, fb_object_id, created_at_utc, status, response_json
+             FROM posts WHERE post_id = ?1",
+            params![post_id],
+            post_record,
+        )
+    }
+
+    /// Every post log row for an event, oldest first.
+    pub fn posts_for_event(&self, event_id: &str) -> rusqlite::Result<Vec<PostRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT post_id, event_id, group_id, fb_object_id, created_at_utc, status, response_json
+             FROM posts WHERE event_id = ?1
+             ORDER BY created_at_utc, post_id",
+        )?;
+        let rows = stmt.query_map(params![event_id], post_record)?;
+        rows.collect()
+    }
+
+    /// Every post row, oldest first, joined with its event for the export.
+    pub fn list_post_log(&self) -> rusqlite::Result<Vec<PostLogEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT p.created_at_utc, p.event_id, p.group_id, p.fb_object_id, p.status, e.payload
+             FROM posts p LEFT JOIN events e ON e.id = p.event_id
+             ORDER BY p.created_at_utc, p.post_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let event = row
+                .get::<_, Option<String>>(5)?
+                .and_then(|payload| serde_json::from_str::<Event>(&payload).ok());
+            Ok(PostLogEntry {
+                posted_at: row.get(0)?,
+                event_id: row.get(1)?,
+                venue: event
+                    .as_ref()
+                    .map(|e| e.venue_name.clone().unwrap_or_else(|| e.venue_id.clone())),
+                headliner: event.as_ref().map(Event::title),
+                group_id: row.get(2)?,
+                fb_object_id: row.get(3)?,
+                status: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    #[cfg(test)]
+    pub fn count_posts(&self, event_id: &str, status: &str) -> rusqlite::Result<i64> {
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM posts WHERE event_id = ?1 AND status = ?2",
+            params![event_id, status],
+            |row| row.get(0),
+        )
+    }
+
+    pub fn record_scrape_run(
+        &self,
+        venue_id: &str,
+        event_count: usize,
+        error: Option<&str>,
+        selector_warning: Option<&str>,
+    ) -> rusqlite::Result<()> {
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO scrape_runs (venue_id, ran_at_utc, event_count, error, selector_warning)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![venue_id, now, event_count as i64, error, selector_warning],
+        )?;
+        Ok(())
+    }
+
+    pub fn latest_scrape_runs(&self) -> rusqlite::Result<HashMap<String, ScrapeRun>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT r.venue_id, r.ran_at_utc, r.event_count, r.error, r.selector_warning
+             FROM scrape_runs r
+             WHERE r.id = (SELECT MAX(id) FROM scrape_runs WHERE venue_id = r.venue_id)",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ScrapeRun {
+                venue_id: row.get(0)?,
+                ran_at_utc: row.get(1)?,
+                event_count: row.get(2)?,
+                error: row.get(3)?,
+                selector_warning: row.get(4)?,
+            })
+        })?;
+
+        let mut out = HashMap::new();
+        for row in rows {
+            let run = row?;
+            out.insert(run.venue_id.clone(), run);
+        }
+        Ok(out)
+    }
+
+    pub fn record_purge_run(&self, removed: usize) -> rusqlite::Result<()> {
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO purge_runs (ran_at_utc, removed) VALUES (?1, ?2)",
+            params![now, removed as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn last_purge_run(&self) -> rusqlite::Result<Option<PurgeRun>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT ran_at_utc, removed FROM purge_runs ORDER BY id DESC LIMIT 1")?;
+        let mut rows = stmt.query_map([], |row| {
+            Ok(PurgeRun {
+                ran_at_utc: row.get(0)?,
+                removed: row.get(1)?,
+            })
+        })?;
+        rows.next().transpose()
+    }
+
     pub fn get_musicbrainz_profile(
         &self,
         artist_key: &str,
@@ -162,6 +855,27 @@ impl Store {
         }
     }
 
+    /// Artist keys whose MusicBrainz lookup found no match, alphabetically.
+    pub fn list_unmatched_artists(&self) -> rusqlite::Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT artist_key FROM musicbrainz_cache
+             WHERE profile_json = 'null'
+             ORDER BY artist_key",
+        )?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Forgets one artist's lookup so the next enrichment asks MusicBrainz
+    /// again. Returns whether a row was removed.
+    pub fn delete_musicbrainz_profile(&self, artist_key: &str) -> rusqlite::Result<bool> {
+        let removed = self.conn.execute(
+            "DELETE FROM musicbrainz_cache WHERE artist_key = ?1",
+            params![artist_key],
+        )?;
+        Ok(removed > 0)
+    }
+
     pub fn put_musicbrainz_profile(
         &self,
         artist_key: &str,
@@ -182,7 +896,73 @@ impl Store {
     }
 }
 
-fn sample_event(venue_id: &str, venue_name: &str, start: DateTime<Utc>) -> Event {
+/// An event with its indexed `venue_id` and `headliner` columns.
+struct UpcomingRow {
+    venue_id: String,
+    headliner: String,
+    event: Event,
+}
+
+/// A post log row with the event's venue and headliner, for exports. Venue
+/// and headliner are empty when the event row is gone or unreadable.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PostLogEntry {
+    pub posted_at: Option<String>,
+    pub event_id: String,
+    pub venue: Option<String>,
+    pub headliner: Option<String>,
+    pub group_id: Option<String>,
+    pub fb_object_id: Option<String>,
+    pub status: Option<String>,
+}
+
+fn post_record(row: &rusqlite::Row<'_>) -> rusqlite::Result<PostRecord> {
+    Ok(PostRecord {
+        post_id: row.get(0)?,
+        event_id: row.get(1)?,
+        group_id: row.get(2)?,
+        fb_object_id: row.get(3)?,
+        created_at_utc: row.get(4)?,
+        status: row.get(5)?,
+        response_json: row.get(6)?,
+    })
+}
+
+/// Id the scrapers assigned before headliners were normalized: the stored
+/// start string and the first artist exactly as scraped.
+fn legacy_event_id(event: &Event) -> String {
+    let headliner = event
+        .artists
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "unknown".to_string());
+    let mut hasher = Sha256::new();
+    hasher.update(event.venue_id.as_bytes());
+    hasher.update(b"|");
+    hasher.update(event.start_utc.as_bytes());
+    hasher.update(b"|");
+    hasher.update(headliner.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Start stored in the `start_utc` column: UTC, whole seconds, so the
+/// strings sort and compare in time order.
+fn start_key(event: &Event) -> Option<String> {
+    DateTime::parse_from_rfc3339(&event.start_utc)
+        .ok()
+        .map(|start| time_key(start.with_timezone(&Utc)))
+}
+
+fn time_key(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Normalized headliner stored in the `headliner` column.
+fn headliner_key(event: &Event) -> String {
+    fuzzy_name_key(&event.title())
+}
+
+pub(crate) fn sample_event(venue_id: &str, venue_name: &str, start: DateTime<Utc>) -> Event {
     let start_iso = start.to_rfc3339();
     Event {
         id: format!("{venue_id}|{start_iso}|headliner"),
@@ -192,9 +972,13 @@ fn sample_event(venue_id: &str, venue_name: &str, start: DateTime<Utc>) -> Event
         venue_url: None,
         start_local: Some(start.with_timezone(&Local).to_rfc3339()),
         start_utc: start_iso.clone(),
+        time_tba: false,
+        duration_minutes: None,
         doors_local: None,
         artists: vec!["Sample Artist".to_string()],
-        is_all_ages: Some(true),
+        billed_as: None,
+        lineup: Vec::new(),
+        age_restriction: AgeRestriction::AllAges,
         ticket_url: Some("https://tickets.example.com".to_string()),
         event_url: Some("https://events.example.com".to_string()),
         price_min_cents: Some(1500),
@@ -203,5 +987,450 @@ fn sample_event(venue_id: &str, venue_name: &str, start: DateTime<Utc>) -> Event
         tags: vec!["Rock".to_string()],
         scraped_at_utc: Utc::now().to_rfc3339(),
         extra: json!({}),
+        note: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_missing_genres_are_listed_until_attempted() {
+        let store = Store::open_in_memory().expect("store");
+        let now = Utc::now();
+        let tagged = sample_event("venus", "Venus", now + Duration::days(3));
+        let mut later = sample_event("fox", "Fox", now + Duration::days(5));
+        later.tags.clear();
+        let mut sooner = sample_event("pine_box", "Pine Box", now + Duration::days(2));
+        sooner.tags.clear();
+        let mut past = sample_event("treefort", "Treefort", now - Duration::days(1));
+        past.tags.clear();
+        for event in [&tagged, &later, &sooner, &past] {
+            store.upsert_event(event).expect("insert");
+        }
+
+        let ids = |events: Vec<Event>| events.into_iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(store.list_events_missing_genres(10, now).expect("list")),
+            vec![sooner.id.clone(), later.id.clone()]
+        );
+        assert_eq!(
+            ids(store.list_events_missing_genres(1, now).expect("list")),
+            vec![sooner.id.clone()]
+        );
+
+        store.mark_enrich_attempted(&sooner.id, now).expect("mark");
+        assert_eq!(
+            ids(store.list_events_missing_genres(10, now).expect("list")),
+            vec![later.id.clone()]
+        );
+        let next_day = now + Duration::hours(ENRICH_RETRY_AFTER_HOURS + 1);
+        assert_eq!(
+            ids(store
+                .list_events_missing_genres(10, next_day)
+                .expect("list")),
+            vec![sooner.id.clone(), later.id.clone()]
+        );
+    }
+
+    #[test]
+    fn post_log_joins_event_details() {
+        let store = Store::open_in_memory().expect("store");
+        let event = sample_event("venus", "Venus Lounge", Utc::now() + Duration::days(2));
+        store.upsert_event(&event).expect("insert");
+        store
+            .record_post(&event.id, Some("boise-ravers"), None, "manual", &json!({}))
+            .expect("post");
+        store
+            .record_post("gone", None, Some("fb-1"), "manual", &json!({}))
+            .expect("orphan post");
+
+        let log = store.list_post_log().expect("log");
+        assert_eq!(log.len(), 2);
+        let posted = log.iter().find(|e| e.event_id == event.id).expect("entry");
+        assert_eq!(posted.venue.as_deref(), Some("Venus Lounge"));
+        assert_eq!(posted.headliner.as_deref(), Some("Sample Artist"));
+        assert_eq!(posted.group_id.as_deref(), Some("boise-ravers"));
+        let orphan = log.iter().find(|e| e.event_id == "gone").expect("orphan");
+        assert_eq!(orphan.venue, None);
+        assert_eq!(orphan.fb_object_id.as_deref(), Some("fb-1"));
+    }
+
+    #[test]
+    fn one_event_can_have_a_post_per_group() {
+        let store = Store::open_in_memory().expect("store");
+        let event = sample_event("venus", "Venus", Utc::now() + Duration::days(2));
+        store.upsert_event(&event).expect("insert");
+        store
+            .record_post(&event.id, Some("boise-ravers"), None, "manual", &json!({}))
+            .expect("first group");
+        store
+            .record_post(
+                &event.id,
+                Some("treasure-valley"),
+                None,
+                "manual",
+                &json!({}),
+            )
+            .expect("second group");
+
+        let posts = store.posts_for_event(&event.id).expect("posts");
+        let groups: Vec<Option<&str>> = posts.iter().map(|p| p.group_id.as_deref()).collect();
+        assert_eq!(groups, vec![Some("boise-ravers"), Some("treasure-valley")]);
+        assert!(store.posts_for_event("other").expect("none").is_empty());
+    }
+
+    #[test]
+    fn deleting_a_musicbrainz_profile_only_removes_that_artist() {
+        let store = Store::open_in_memory().expect("store");
+        store
+            .put_musicbrainz_profile("pup", &None)
+            .expect("put pup");
+        store
+            .put_musicbrainz_profile("built to spill", &None)
+            .expect("put bts");
+
+        assert!(store.delete_musicbrainz_profile("pup").expect("delete"));
+        assert!(!store
+            .delete_musicbrainz_profile("pup")
+            .expect("delete again"));
+        assert!(store.get_musicbrainz_profile("pup").expect("get").is_none());
+        assert!(store
+            .get_musicbrainz_profile("built to spill")
+            .expect("get")
+            .is_some());
+    }
+
+    #[test]
+    fn rescrapes_keep_the_organizer_note() {
+        let store = Store::open_in_memory().expect("store");
+        let event = sample_event("treefort", "Treefort", Utc::now() + Duration::days(2));
+        store.upsert_event(&event).expect("insert");
+        store
+            .set_event_note(&event.id, Some("waiting on promo code"))
+            .expect("note");
+
+        let mut rescraped = event.clone();
+        rescraped.ticket_url = Some("https://tickets.example.com/new".to_string());
+        store.upsert_event(&rescraped).expect("rescrape");
+        let stored = store.get_event(&event.id).expect("stored");
+        assert_eq!(stored.note.as_deref(), Some("waiting on promo code"));
+        assert_eq!(stored.ticket_url, rescraped.ticket_url);
+
+        store.set_event_note(&event.id, None).expect("clear");
+        store.upsert_event(&rescraped).expect("rescrape again");
+        assert_eq!(store.get_event(&event.id).expect("stored").note, None);
+    }
+
+    #[test]
+    fn rescrapes_keep_enriched_tags() {
+        let store = Store::open_in_memory().expect("store");
+        let now = Utc::now();
+        let event = sample_event("treefort", "Treefort", now + Duration::days(2));
+        store.upsert_event(&event).expect("insert");
+        store.mark_enrich_attempted(&event.id, now).expect("mark");
+
+        let mut rescraped = event.clone();
+        rescraped.tags.clear();
+        store.upsert_event(&rescraped).expect("rescrape");
+        assert_eq!(
+            store.get_event(&event.id).expect("stored").tags,
+            vec!["Rock"]
+        );
+        assert_eq!(
+            store.list_events_by_tag("rock", now).expect("by tag").len(),
+            1
+        );
+
+        rescraped.tags = vec!["Punk".to_string()];
+        store.upsert_event(&rescraped).expect("tagged rescrape");
+        assert_eq!(
+            store.get_event(&event.id).expect("stored").tags,
+            vec!["Punk"]
+        );
+    }
+
+    #[test]
+    fn events_by_tag_match_case_insensitively_and_skip_past_shows() {
+        let store = Store::open_in_memory().expect("store");
+        let now = Utc::now();
+        let tagged = |id: &str, days: i64, tags: &[&str]| {
+            let mut event = sample_event("treefort", "Treefort", now + Duration::days(days));
+            event.id = id.to_string();
+            event.tags = tags.iter().map(|tag| tag.to_string()).collect();
+            store.upsert_event(&event).expect("upsert");
+        };
+        tagged("later-metal", 9, &["Doom", "METAL"]);
+        tagged("soon-metal", 2, &["metal"]);
+        tagged("past-metal", -2, &["Metal"]);
+        tagged("folk", 3, &["Folk"]);
+        tagged("alt", 4, &["Alt-Rock"]);
+
+        let ids = |tag: &str| -> Vec<String> {
+            store
+                .list_events_by_tag(tag, now)
+                .expect("by tag")
+                .into_iter()
+                .map(|event| event.id)
+                .collect()
+        };
+        assert_eq!(ids(" Metal "), vec!["soon-metal", "later-metal"]);
+        assert_eq!(ids("alternative rock"), vec!["alt"]);
+        assert!(ids("jazz").is_empty());
+    }
+
+    #[test]
+    fn upcoming_window_keeps_todays_date_only_shows_and_its_end() {
+        let store = Store::open_in_memory().expect("store");
+        let now = Utc::now();
+        let show = |id: &str, start: DateTime<Utc>, tba: bool| {
+            let mut event = sample_event("treefort", "Treefort", start);
+            event.id = id.to_string();
+            event.time_tba = tba;
+            store.upsert_event(&event).expect("upsert");
+        };
+        show("tba-today", now - Duration::hours(2), true);
+        show("started", now - Duration::hours(2), false);
+        show("edge", now + Duration::days(7), false);
+        show("later", now + Duration::days(8), false);
+        show("soon", now + Duration::days(1), false);
+        store.mark_posted("soon").expect("posted");
+
+        let ids = |until: Option<DateTime<Utc>>, pending_only: bool| -> Vec<String> {
+            store
+                .list_upcoming_events(now, until, pending_only)
+                .expect("upcoming")
+                .into_iter()
+                .map(|event| event.id)
+                .collect()
+        };
+        let week = Some(now + Duration::days(7) + Duration::seconds(1));
+        assert_eq!(ids(week, false), vec!["tba-today", "soon", "edge"]);
+        assert_eq!(ids(week, true), vec!["tba-today", "edge"]);
+        assert_eq!(ids(None, true), vec!["tba-today", "edge", "later"]);
+    }
+
+    #[test]
+    fn events_group_by_venue_in_start_order() {
+        let now = Utc::now();
+        let seed = [
+            sample_event("pine_box", "Pine Box", now + Duration::days(9)),
+            sample_event("venus", "Venus", now + Duration::days(2)),
+            sample_event("pine_box", "Pine Box", now + Duration::days(1)),
+            sample_event("venus", "Venus", now - Duration::days(1)),
+            sample_event("fox", "Fox", now + Duration::days(40)),
+        ];
+
+        let store = Store::open_in_memory().expect("store");
+        for event in &seed {
+            store.upsert_event(event).expect("upsert");
+        }
+        let mut posted = sample_event("fox", "Fox", now + Duration::days(3));
+        posted.artists = vec!["Posted Artist".to_string()];
+        posted.id = "posted".to_string();
+        store.upsert_event(&posted).expect("upsert");
+        store.mark_posted(&posted.id).expect("posted");
+
+        let all = store.list_upcoming_by_venue(now, None).expect("grouped");
+        assert_eq!(all.len(), 3);
+        let pine_box: Vec<&str> = all["pine_box"].iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(pine_box, vec![seed[2].id.as_str(), seed[0].id.as_str()]);
+        assert_eq!(all["venus"].len(), 1, "past shows are dropped");
+        assert_eq!(all["fox"].len(), 1, "posted shows are left out");
+
+        let soon = store
+            .list_upcoming_by_venue(now, Some(now + Duration::days(7)))
+            .expect("grouped");
+        assert!(!soon.contains_key("fox"));
+        assert_eq!(soon["pine_box"].len(), 1);
+    }
+
+    #[test]
+    fn upcoming_pending_events_group_by_normalized_headliner() {
+        let store = Store::open_in_memory().expect("store");
+        let now = Utc::now();
+        let show = |id: &str, headliner: &str, days: i64| {
+            let mut event = sample_event("venus", "Venus", now + Duration::days(days));
+            event.id = id.to_string();
+            event.artists = vec![headliner.to_string()];
+            store.upsert_event(&event).expect("upsert");
+        };
+        show("pup-1", "PUP", 1);
+        show("bts", "Built to Spill", 2);
+        show("pup-2", "pup", 3);
+        show("past", "Desert Dwellers", -1);
+
+        let groups: Vec<(String, Vec<String>)> = store
+            .list_upcoming_by_headliner(now)
+            .expect("by headliner")
+            .into_iter()
+            .map(|(key, events)| (key, events.into_iter().map(|event| event.id).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (
+                    "pup".to_string(),
+                    vec!["pup-1".to_string(), "pup-2".to_string()]
+                ),
+                ("built to spill".to_string(), vec!["bts".to_string()]),
+            ]
+        );
+    }
+
+    fn indexed_columns(store: &Store, id: &str) -> (Option<String>, Option<String>) {
+        store
+            .conn
+            .query_row(
+                "SELECT headliner, venue_id FROM events WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("indexed columns")
+    }
+
+    #[test]
+    fn headliner_and_venue_columns_follow_the_payload() {
+        let store = Store::open_in_memory().expect("store");
+        let mut event = sample_event("treefort", "Treefort", Utc::now() + Duration::days(2));
+        event.artists = vec!["PUP (Live)".to_string(), "Chase Petra".to_string()];
+        store.upsert_event(&event).expect("insert");
+        assert_eq!(
+            indexed_columns(&store, &event.id),
+            (Some("pup".to_string()), Some("treefort".to_string()))
+        );
+
+        event.artists = vec!["Built to Spill".to_string()];
+        event.venue_id = "revolution".to_string();
+        store.upsert_event(&event).expect("update");
+        assert_eq!(
+            indexed_columns(&store, &event.id),
+            (
+                Some("built to spill".to_string()),
+                Some("revolution".to_string())
+            )
+        );
+
+        let mut other = sample_event("knitboise", "Knitting Factory", Utc::now());
+        other.id = "other".to_string();
+        store.upsert_event(&other).expect("insert other");
+        event.artists = vec!["Sample Artist".to_string()];
+        store.merge_into(&event, &other.id).expect("merge");
+        assert_eq!(
+            indexed_columns(&store, &event.id).0.as_deref(),
+            Some("sample artist")
+        );
+    }
+
+    #[test]
+    fn existing_rows_are_backfilled() {
+        let store = Store::open_in_memory().expect("store");
+        let event = sample_event("treefort", "Treefort", Utc::now() + Duration::days(2));
+        let payload = serde_json::to_string(&event).expect("payload");
+        store
+            .conn
+            .execute(
+                "INSERT INTO events (id, payload, first_seen_utc, last_seen_utc)
+                 VALUES (?1, ?2, '2025-01-01', '2025-01-01')",
+                params![event.id, payload],
+            )
+            .expect("legacy insert");
+        assert_eq!(indexed_columns(&store, &event.id), (None, None));
+
+        store.backfill_event_columns().expect("backfill");
+        assert_eq!(
+            indexed_columns(&store, &event.id),
+            (
+                Some("sample artist".to_string()),
+                Some("treefort".to_string())
+            )
+        );
+        assert_eq!(
+            store
+                .list_upcoming_events(Utc::now(), None, true)
+                .expect("upcoming")
+                .len(),
+            1,
+            "the start column is filled too"
+        );
+    }
+
+    #[test]
+    fn upgraded_stores_keep_posted_events_posted_after_a_rescrape() {
+        let store = Store::open_in_memory().expect("store");
+        let start = Utc::now() + Duration::days(3);
+        let legacy = |artist: &str| {
+            let mut event = sample_event("treefort", "Treefort", start);
+            event.artists = vec![artist.to_string(), "Chase Petra".to_string()];
+            event.id = legacy_event_id(&event);
+            let payload = serde_json::to_string(&event).expect("payload");
+            store
+                .conn
+                .execute(
+                    "INSERT INTO events (id, payload, first_seen_utc, last_seen_utc)
+                     VALUES (?1, ?2, '2025-01-01', '2025-01-01')",
+                    params![event.id, payload],
+                )
+                .expect("legacy insert");
+            event
+        };
+        let posted = legacy("PUP");
+        let duplicate = legacy("pup ");
+        store.mark_posted(&posted.id).expect("posted");
+        store
+            .record_post(&posted.id, None, None, "manual", &json!({}))
+            .expect("post log");
+        store
+            .set_event_note(&duplicate.id, Some("ask about guest list"))
+            .expect("note");
+        store
+            .conn
+            .execute_batch("PRAGMA user_version = 0")
+            .expect("downgrade");
+
+        store.migrate_event_ids().expect("migrate");
+        let mut rescraped = posted.clone();
+        rescraped.id = event_id("treefort", &start, &rescraped.artists);
+        store.upsert_event(&rescraped).expect("rescrape");
+
+        assert_eq!(store.count_events().expect("count"), 1);
+        assert!(store.list_pending_events().expect("pending").is_empty());
+        assert!(store.posted_at(&rescraped.id).expect("posted").is_some());
+        assert_eq!(
+            store.posts_for_event(&rescraped.id).expect("posts").len(),
+            1
+        );
+        assert_eq!(
+            store
+                .get_event(&rescraped.id)
+                .expect("stored")
+                .note
+                .as_deref(),
+            Some("ask about guest list")
+        );
+    }
+
+    #[test]
+    fn malformed_payload_rows_are_skipped_and_reported() {
+        let store = Store::open_in_memory().expect("store");
+        let good = sample_event("treefort", "Treefort", Utc::now() + Duration::days(2));
+        store.upsert_event(&good).expect("insert good");
+        store
+            .conn
+            .execute(
+                "INSERT INTO events (id, payload, first_seen_utc, last_seen_utc)
+                 VALUES ('legacy-1', '{\"id\": 7}', '2025-01-01', '2025-01-01')",
+                [],
+            )
+            .expect("insert malformed");
+
+        let pending = store.list_pending_events().expect("pending");
+        let ids: Vec<&str> = pending.iter().map(|p| p.event.id.as_str()).collect();
+        assert_eq!(ids, vec![good.id.as_str()]);
+        assert_eq!(
+            store.list_corrupt_event_ids().expect("corrupt ids"),
+            vec!["legacy-1".to_string()]
+        );
     }
 }
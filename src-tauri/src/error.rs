@@ -0,0 +1,88 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+/// Error returned by commands that the frontend needs to tell apart. It
+/// serializes as `{ "code": ..., "message": ... }`; codes are stable API.
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("event not found: {0}")]
+    EventNotFound(String),
+    #[error("database error: {0}")]
+    Database(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::EventNotFound(_) => "event_not_found",
+            AppError::Database(_) => "database",
+            AppError::Internal(_) => "internal",
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(err: rusqlite::Error) -> Self {
+        AppError::Database(err.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(err: tauri::Error) -> Self {
+        AppError::Internal(err.to_string())
+    }
+}
+
+/// Lets helpers that still return `String` errors propagate an `AppError`
+/// with `?` while commands migrate.
+impl From<AppError> for String {
+    fn from(err: AppError) -> Self {
+        err.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn errors_serialize_with_stable_codes() {
+        let cases = [
+            (
+                AppError::EventNotFound("abc".to_string()),
+                json!({ "code": "event_not_found", "message": "event not found: abc" }),
+            ),
+            (
+                AppError::Database("disk I/O error".to_string()),
+                json!({ "code": "database", "message": "database error: disk I/O error" }),
+            ),
+            (
+                AppError::Internal("task panicked".to_string()),
+                json!({ "code": "internal", "message": "task panicked" }),
+            ),
+        ];
+        for (err, expected) in cases {
+            assert_eq!(serde_json::to_value(&err).expect("serialize"), expected);
+        }
+    }
+
+    #[test]
+    fn missing_rows_map_to_database_errors() {
+        let err = AppError::from(rusqlite::Error::QueryReturnedNoRows);
+        assert_eq!(err.code(), "database");
+        assert_eq!(String::from(err), "database error: Query returned no rows");
+    }
+}
@@ -0,0 +1,115 @@
+use serde::Deserialize;
+
+use crate::db::PostLogEntry;
+
+const POST_LOG_COLUMNS: [&str; 7] = [
+    "posted_at",
+    "event_id",
+    "venue",
+    "headliner",
+    "group_id",
+    "fb_object_id",
+    "status",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+pub fn render_post_log(entries: &[PostLogEntry], format: ExportFormat) -> Result<String, String> {
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(entries).map_err(|e| e.to_string()),
+        ExportFormat::Csv => {
+            let mut out = csv_row(POST_LOG_COLUMNS.iter().copied());
+            for entry in entries {
+                out.push_str(&csv_row([
+                    entry.posted_at.as_deref().unwrap_or(""),
+                    entry.event_id.as_str(),
+                    entry.venue.as_deref().unwrap_or(""),
+                    entry.headliner.as_deref().unwrap_or(""),
+                    entry.group_id.as_deref().unwrap_or(""),
+                    entry.fb_object_id.as_deref().unwrap_or(""),
+                    entry.status.as_deref().unwrap_or(""),
+                ]));
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// One RFC 4180 line, CRLF-terminated.
+pub fn csv_row<'a>(fields: impl IntoIterator<Item = &'a str>) -> String {
+    let mut line = fields
+        .into_iter()
+        .map(csv_field)
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// Quotes a field when it holds a comma, quote or line break, doubling any
+/// embedded quotes.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(headliner: &str, group_id: Option<&str>) -> PostLogEntry {
+        PostLogEntry {
+            posted_at: Some("2025-10-01T18:00:00+00:00".to_string()),
+            event_id: "evt-1".to_string(),
+            venue: Some("Treefort Music Hall".to_string()),
+            headliner: Some(headliner.to_string()),
+            group_id: group_id.map(str::to_string),
+            fb_object_id: None,
+            status: Some("manual".to_string()),
+        }
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("Earth, Wind & Fire"), "\"Earth, Wind & Fire\"");
+        assert_eq!(csv_field("The \"Band\""), "\"The \"\"Band\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn post_log_renders_as_csv() {
+        let csv = render_post_log(
+            &[entry("Earth, Wind & Fire", Some("boise-ravers"))],
+            ExportFormat::Csv,
+        )
+        .expect("csv");
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(
+            lines[0],
+            "posted_at,event_id,venue,headliner,group_id,fb_object_id,status"
+        );
+        assert_eq!(
+            lines[1],
+            "2025-10-01T18:00:00+00:00,evt-1,Treefort Music Hall,\"Earth, Wind & Fire\",boise-ravers,,manual"
+        );
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn post_log_renders_as_json() {
+        let json = render_post_log(&[entry("PUP", None)], ExportFormat::Json).expect("json");
+        let parsed: serde_json::Value = serde_json::from_str(&json).expect("parse");
+        assert_eq!(parsed[0]["headliner"], "PUP");
+        assert_eq!(parsed[0]["group_id"], serde_json::Value::Null);
+        assert_eq!(parsed[0]["status"], "manual");
+    }
+}
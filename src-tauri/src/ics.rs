@@ -0,0 +1,160 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::{Event, TitleMode};
+
+const PRODID: &str = "-//show-scraper//Boise Shows//EN";
+/// RFC 5545 caps content lines at 75 octets before folding.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Wraps events in a VCALENDAR document.
+pub fn render_calendar(events: &[Event]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{PRODID}"),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for event in events {
+        lines.extend(vevent_lines(event));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&fold_line(&line));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Content lines for one VEVENT. DTEND is only emitted when the event has a
+/// known duration; date-only events become all-day entries.
+fn vevent_lines(event: &Event) -> Vec<String> {
+    let start = DateTime::parse_from_rfc3339(&event.start_utc)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc));
+    let stamp = DateTime::parse_from_rfc3339(&event.scraped_at_utc)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@show-scraper", event.id),
+        format!("DTSTAMP:{}", format_utc(&stamp)),
+    ];
+
+    if event.time_tba {
+        let date = event
+            .start_local
+            .as_deref()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.date_naive())
+            .or_else(|| start.map(|dt| dt.date_naive()));
+        if let Some(date) = date {
+            lines.push(format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+        }
+    } else if let Some(start) = start {
+        lines.push(format!("DTSTART:{}", format_utc(&start)));
+        if let Some(minutes) = event.duration_minutes {
+            let end = start + Duration::minutes(i64::from(minutes));
+            lines.push(format!("DTEND:{}", format_utc(&end)));
+        }
+    }
+
+    lines.push(format!(
+        "SUMMARY:{}",
+        escape_text(&event.title_with_support(TitleMode::Count))
+    ));
+    if let Some(venue) = &event.venue_name {
+        lines.push(format!("LOCATION:{}", escape_text(venue)));
+    }
+    if let Some(url) = event.event_url.as_ref().or(event.ticket_url.as_ref()) {
+        lines.push(format!("URL:{url}"));
+    }
+    let description = event
+        .ticket_url
+        .as_ref()
+        .map(|ticket| format!("Tickets: {ticket}"));
+    if let Some(description) = description {
+        lines.push(format!("DESCRIPTION:{}", escape_text(&description)));
+    }
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+fn format_utc(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn fold_line(line: &str) -> String {
+    if line.len() <= MAX_LINE_OCTETS {
+        return line.to_string();
+    }
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    let mut width = 0;
+    for ch in line.chars() {
+        // Continuation lines start with a space, which counts toward the limit.
+        if width + ch.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(ch);
+        width += ch.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use chrono::TimeZone;
+
+    fn event_at_eight() -> Event {
+        let start = Utc.with_ymd_and_hms(2025, 10, 9, 2, 0, 0).unwrap();
+        db::sample_event("treefort", "Treefort Music Hall", start)
+    }
+
+    #[test]
+    fn dtend_follows_duration() {
+        let mut event = event_at_eight();
+        event.duration_minutes = Some(150);
+
+        let calendar = render_calendar(&[event]);
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.contains("\r\nDTSTART:20251009T020000Z\r\n"));
+        assert!(calendar.contains("\r\nDTEND:20251009T043000Z\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn dtend_is_omitted_without_duration() {
+        let mut event = event_at_eight();
+        event.duration_minutes = None;
+
+        let calendar = render_calendar(&[event]);
+        assert!(calendar.contains("DTSTART:20251009T020000Z"));
+        assert!(!calendar.contains("DTEND"));
+    }
+
+    #[test]
+    fn long_lines_are_folded_and_text_escaped() {
+        let mut event = event_at_eight();
+        event.venue_name = Some(format!("Hall; Boise, ID {}", "x".repeat(80)));
+
+        let calendar = render_calendar(&[event]);
+        assert!(calendar.contains("LOCATION:Hall\\; Boise\\, ID"));
+        assert!(calendar
+            .split("\r\n")
+            .all(|line| line.len() <= MAX_LINE_OCTETS));
+    }
+}
@@ -1,19 +1,46 @@
+mod config;
 mod db;
+mod error;
+mod export;
+mod ics;
 mod llm;
 mod models;
 mod musicbrainz;
 mod scheduler;
 pub mod scraping;
+#[cfg(test)]
+mod test_support;
 mod utils;
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use serde_json::json;
+use tauri::Emitter;
 
+use config::{AppConfig, SchedulerConfig};
 use db::{PendingEvent, Store};
-use llm::{fallback, fallback_preview, LLMComposer};
+use error::AppError;
+use llm::{fallback, fallback_preview, fallback_roundup, LLMComposer, OutputFormat, RenderOptions};
 use models::Event;
+use scraping::ScrapeReport;
+
+/// Facebook only accepts scheduled publish times 10 minutes to 75 days out.
+const MIN_SCHEDULE_LEAD_MINUTES: i64 = 10;
+const MAX_SCHEDULE_LEAD_DAYS: i64 = 75;
+
+/// `self_test` only runs when this is set to "1".
+const MOCK_ENV: &str = "SHOW_SCRAPER_MOCK";
+
+/// Network checks in `healthcheck` give up after this long.
+const HEALTHCHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Events looked up per `enrich_next_batch` call, keeping each call short under
+/// MusicBrainz's one-request-per-second limit.
+const ENRICH_BATCH_SIZE: usize = 5;
 
 const BUCKET_KEYS: [&str; 6] = ["DAY_OF", "LT_1W", "LT_2W", "LT_1M", "LT_2M", "GTE_2M"];
 
@@ -21,6 +48,81 @@ const BUCKET_KEYS: [&str; 6] = ["DAY_OF", "LT_1W", "LT_2W", "LT_1M", "LT_2M", "G
 struct BucketItem {
     days_until: i64,
     event: models::Event,
+    /// Headliner is on the artist blocklist; posting will be refused.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    blocklisted: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct VenueStatus {
+    id: String,
+    name: String,
+    url: String,
+    last_scraped_at: Option<String>,
+    last_event_count: Option<i64>,
+    last_error: Option<String>,
+    selector_warning: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SubsystemHealth {
+    ok: bool,
+    detail: String,
+}
+
+impl SubsystemHealth {
+    fn ok(detail: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            detail: detail.into(),
+        }
+    }
+
+    fn failed(detail: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct HealthReport {
+    database: SubsystemHealth,
+    config: SubsystemHealth,
+    facebook: SubsystemHealth,
+    llm: SubsystemHealth,
+    musicbrainz: SubsystemHealth,
+}
+
+#[derive(Debug, Serialize)]
+struct SelfTestStage {
+    stage: &'static str,
+    ok: bool,
+    detail: String,
+}
+
+impl SelfTestStage {
+    fn new(stage: &'static str, ok: bool, detail: impl Into<String>) -> Self {
+        Self {
+            stage,
+            ok,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct EventExtra {
+    extra: serde_json::Value,
+    /// One `path: value` line per leaf of `extra`.
+    summary: String,
+}
+
+#[derive(Debug, Serialize)]
+struct PreviewItem {
+    event_id: String,
+    preview: String,
 }
 
 #[tauri::command]
@@ -28,80 +130,377 @@ async fn list_venues() -> Result<Vec<scraping::ScraperInfo>, String> {
     Ok(scraping::list_scrapers())
 }
 
+#[tauri::command]
+async fn venue_status() -> Result<Vec<VenueStatus>, String> {
+    tauri::async_runtime::spawn_blocking(|| -> Result<Vec<VenueStatus>, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        venue_statuses(&store, scraping::list_scrapers())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// How a scrape went, so "nothing on" is distinguishable from a partial
+/// failure. Every variant carries the persisted count and the scrapers'
+/// skipped-card and selector warnings as "venue: warning" lines.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum ScrapeOutcome {
+    Succeeded {
+        count: usize,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        warnings: Vec<String>,
+    },
+    NoEvents {
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        warnings: Vec<String>,
+    },
+    VenuesFailed {
+        count: usize,
+        failed: Vec<String>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        warnings: Vec<String>,
+    },
+}
+
+impl ScrapeOutcome {
+    fn new(count: usize, failed: Vec<String>, warnings: Vec<String>) -> Self {
+        match (count, failed.is_empty()) {
+            (_, false) => ScrapeOutcome::VenuesFailed {
+                count,
+                failed,
+                warnings,
+            },
+            (0, true) => ScrapeOutcome::NoEvents { warnings },
+            (count, true) => ScrapeOutcome::Succeeded { count, warnings },
+        }
+    }
+
+    fn count(&self) -> usize {
+        match self {
+            ScrapeOutcome::Succeeded { count, .. } | ScrapeOutcome::VenuesFailed { count, .. } => {
+                *count
+            }
+            ScrapeOutcome::NoEvents { .. } => 0,
+        }
+    }
+}
+
+/// A venue's warnings and selector warning as "venue: warning" lines.
+fn venue_warnings<'a>(
+    venue_id: &str,
+    warnings: impl IntoIterator<Item = &'a String>,
+) -> Vec<String> {
+    warnings
+        .into_iter()
+        .map(|warning| format!("{venue_id}: {warning}"))
+        .collect()
+}
+
+/// Scrapes every enabled venue and returns how many events were saved.
 #[tauri::command]
 async fn scrape_all() -> Result<usize, String> {
-    let events = tauri::async_runtime::spawn_blocking(scraping::run_all)
+    run_scrape_all().await.map(|outcome| outcome.count())
+}
+
+/// `scrape_all` with the outcome spelled out: succeeded, nothing listed, or
+/// some venues failed.
+#[tauri::command]
+async fn scrape_all_outcome() -> Result<ScrapeOutcome, String> {
+    run_scrape_all().await
+}
+
+async fn run_scrape_all() -> Result<ScrapeOutcome, String> {
+    tauri::async_runtime::spawn_blocking(|| -> Result<ScrapeOutcome, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        let outcomes = scraping::run_each();
+        let errors: Vec<String> = outcomes
+            .iter()
+            .filter_map(|outcome| {
+                let err = outcome.error.as_ref()?;
+                Some(format!("{}: {err}", outcome.venue_id))
+            })
+            .collect();
+        let outcomes = save_batch(&store, outcomes, &scraping::cross_venue_dedup(), false)?;
+        let warnings = outcomes
+            .iter()
+            .flat_map(|outcome| {
+                venue_warnings(
+                    &outcome.venue_id,
+                    outcome.warnings.iter().chain(&outcome.selector_warning),
+                )
+            })
+            .collect();
+        let count = outcomes.iter().map(|outcome| outcome.events.len()).sum();
+        if count == 0 && !errors.is_empty() {
+            return Err(format!("scrapers failed: {}", errors.join("; ")));
+        }
+        let failed = outcomes
+            .into_iter()
+            .filter(|outcome| outcome.error.is_some())
+            .map(|outcome| outcome.venue_id)
+            .collect();
+        Ok(ScrapeOutcome::new(count, failed, warnings))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Runs every enabled venue through the same dedupe as `scrape_all` and
+/// reports per-venue counts and sample titles without saving events or
+/// recording scrape runs, for checking parsers after a markup change.
+#[tauri::command]
+async fn scrape_all_dry_run() -> Result<Vec<scraping::DryRunVenue>, String> {
+    tauri::async_runtime::spawn_blocking(|| -> Result<Vec<scraping::DryRunVenue>, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        let outcomes = save_batch(
+            &store,
+            scraping::run_each(),
+            &scraping::cross_venue_dedup(),
+            true,
+        )?;
+        Ok(outcomes
+            .into_iter()
+            .map(scraping::DryRunVenue::from)
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The tail of a batch scrape: cross-venue dedupe, then, unless `dry_run`,
+/// recording each venue's run and saving the surviving events to `store`.
+/// Returns the deduplicated outcomes.
+fn save_batch(
+    store: &Store,
+    outcomes: Vec<scraping::VenueOutcome>,
+    rules: &config::CrossVenueDedup,
+    dry_run: bool,
+) -> Result<Vec<scraping::VenueOutcome>, String> {
+    if !dry_run {
+        for outcome in &outcomes {
+            if let Err(err) = store.record_scrape_run(
+                &outcome.venue_id,
+                outcome.events.len(),
+                outcome.error.as_deref(),
+                outcome.selector_warning.as_deref(),
+            ) {
+                eprintln!(
+                    "failed to record scrape run for {}: {err}",
+                    outcome.venue_id
+                );
+            }
+        }
+    }
+    let outcomes = scraping::dedupe_outcomes(outcomes, rules);
+    if !dry_run {
+        for event in outcomes.iter().flat_map(|outcome| &outcome.events) {
+            store
+                .upsert_event(event)
+                .map_err(|e| format!("failed to persist event {}: {e}", event.id))?;
+        }
+    }
+    Ok(outcomes)
+}
+
+/// Scrapes one venue and returns how many events were saved.
+#[tauri::command]
+async fn scrape_venue(venue_id: String) -> Result<usize, String> {
+    run_scrape_venue(venue_id)
+        .await
+        .map(|outcome| outcome.count())
+}
+
+/// `scrape_venue` with the outcome and the scraper's warnings spelled out.
+#[tauri::command]
+async fn scrape_venue_outcome(venue_id: String) -> Result<ScrapeOutcome, String> {
+    run_scrape_venue(venue_id).await
+}
+
+async fn run_scrape_venue(venue_id: String) -> Result<ScrapeOutcome, String> {
+    let (events, warnings) = tauri::async_runtime::spawn_blocking(move || {
+        let result = scraping::run_single(&venue_id);
+        let count = result.as_ref().map(|r| r.events.len()).unwrap_or(0);
+        let error = result.as_ref().err().map(|err| err.to_string());
+        let selector_warning = result
+            .as_ref()
+            .ok()
+            .and_then(|r| r.selector_warning.as_deref());
+        record_scrape_runs([(venue_id.as_str(), count, error.as_deref(), selector_warning)]);
+        result.map(|report| {
+            let warnings = venue_warnings(
+                &venue_id,
+                report.warnings.iter().chain(&report.selector_warning),
+            );
+            (report.events, warnings)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+    let count = persist_events(events).await?;
+    Ok(ScrapeOutcome::new(count, Vec::new(), warnings))
+}
+
+/// Runs one venue's scraper and returns what it parsed, with any skipped-card
+/// warnings, without persisting or recording a scrape run.
+#[tauri::command]
+async fn scrape_venue_preview(venue_id: String) -> Result<ScrapeReport, String> {
+    tauri::async_runtime::spawn_blocking(move || scraping::run_single(&venue_id))
         .await
         .map_err(|e| e.to_string())?
-        .map_err(|e| e.to_string())?;
-    persist_events(events).await
+        .map_err(|e| e.to_string())
 }
 
+/// Maintenance check: fetches a venue's live page and reports how many matches
+/// each of its card, date, artist and ticket selectors found.
 #[tauri::command]
-async fn scrape_venue(venue_id: String) -> Result<usize, String> {
-    let events = tauri::async_runtime::spawn_blocking(move || scraping::run_single(&venue_id))
+async fn validate_venue_selectors(venue_id: String) -> Result<scraping::SelectorReport, String> {
+    tauri::async_runtime::spawn_blocking(move || scraping::validate_selectors(&venue_id))
         .await
         .map_err(|e| e.to_string())?
-        .map_err(|e| e.to_string())?;
-    persist_events(events).await
+        .map_err(|e| e.to_string())
 }
 
+/// Pre-posting check: fetches every venue's live page and reports, per venue,
+/// cards found, events parsed and which key selectors still match.
 #[tauri::command]
-async fn list_pending_buckets() -> Result<HashMap<&'static str, Vec<BucketItem>>, String> {
-    let pending = tauri::async_runtime::spawn_blocking(|| -> Result<Vec<PendingEvent>, String> {
-        let store = Store::open_default().map_err(|e| e.to_string())?;
-        store.list_pending_events().map_err(|e| e.to_string())
+async fn diagnose_scrapers() -> Result<Vec<scraping::ScraperHealth>, String> {
+    tauri::async_runtime::spawn_blocking(scraping::diagnose_scrapers)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Dev helper: parses a saved listing page for `venue_id` and returns what the
+/// scraper would find, without storing anything. Without a `path`, the page
+/// the last selector check fetched for that venue is used.
+#[tauri::command]
+async fn reparse_snapshot(venue_id: String, path: Option<String>) -> Result<ScrapeReport, String> {
+    tauri::async_runtime::spawn_blocking(move || match path {
+        Some(path) => scraping::reparse_snapshot(&venue_id, std::path::Path::new(&path)),
+        None => scraping::reparse_cached_snapshot(&venue_id),
     })
     .await
-    .map_err(|e| e.to_string())??;
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
 
-    let mut enriched_events: Vec<Event> = Vec::with_capacity(pending.len());
-    for item in pending {
-        let event = item.event;
-        match musicbrainz::enrich_event(event.clone()).await {
-            Ok(enriched) => enriched_events.push(enriched),
-            Err(err) => {
-                eprintln!("musicbrainz enrich failed: {err}");
-                enriched_events.push(event);
-            }
-        }
+#[tauri::command]
+async fn list_pending_buckets(
+    fast: Option<bool>,
+) -> Result<HashMap<&'static str, Vec<BucketItem>>, String> {
+    load_pending_buckets(fast.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn preview_bucket(bucket_key: String) -> Result<Vec<PreviewItem>, String> {
+    let mut buckets = load_buckets().await?;
+    let items = buckets
+        .remove(bucket_key.as_str())
+        .ok_or_else(|| format!("unknown bucket: {bucket_key}"))?;
+    let events = items.into_iter().map(|item| item.event).collect();
+
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    let composer = Arc::new(LLMComposer::from_config(&config));
+    compose_previews(composer, events).await
+}
+
+/// Composes a single digest post for every event in a bucket, split into as
+/// many messages as the post length limit requires.
+#[tauri::command]
+async fn compose_roundup(bucket_key: String) -> Result<Vec<String>, String> {
+    let mut buckets = load_buckets().await?;
+    let items = buckets
+        .remove(bucket_key.as_str())
+        .ok_or_else(|| format!("unknown bucket: {bucket_key}"))?;
+    if items.is_empty() {
+        return Err(format!("no pending events in bucket: {bucket_key}"));
     }
+    let events: Vec<Event> = items.into_iter().map(|item| item.event).collect();
 
-    let now = Utc::now();
-    let mut buckets: HashMap<&'static str, Vec<BucketItem>> =
-        BUCKET_KEYS.iter().map(|key| (*key, Vec::new())).collect();
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    let composer = LLMComposer::from_config(&config);
+    let roundup = match composer.compose_roundup(&events).await {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("roundup compose failed for {bucket_key}: {err}");
+            fallback_roundup(&events, composer.render_options())
+        }
+    };
+    Ok(llm::split_post(&roundup, llm::MAX_POST_CHARS))
+}
 
-    for event in enriched_events {
-        let start = match parse_start(&event) {
-            Some(dt) => dt,
-            None => continue,
-        };
-        let duration = start.signed_duration_since(now);
-        if duration.num_seconds() < 0 {
-            continue;
+/// Scraper debugging context (raw date text, doors text, MusicBrainz block)
+/// for an event.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn get_event_extra(eventId: String) -> Result<EventExtra, AppError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<EventExtra, AppError> {
+        let store = Store::open_default()?;
+        event_extra(&store, &eventId)
+    })
+    .await?
+}
+
+fn event_extra(store: &Store, event_id: &str) -> Result<EventExtra, AppError> {
+    let extra = load_event(store, event_id)?.extra;
+    let mut lines = Vec::new();
+    flatten_extra("", &extra, &mut lines);
+    Ok(EventExtra {
+        extra,
+        summary: lines.join("\n"),
+    })
+}
+
+fn flatten_extra(path: &str, value: &serde_json::Value, lines: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                flatten_extra(&child_path, child, lines);
+            }
         }
-        let days_until = duration.num_seconds() / 86_400;
-        let bucket = bucket_for(days_until);
-        if let Some(b) = buckets.get_mut(bucket) {
-            b.push(BucketItem { days_until, event });
+        serde_json::Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                flatten_extra(&format!("{path}[{index}]"), child, lines);
+            }
         }
+        serde_json::Value::String(text) => lines.push(format!("{path}: {text}")),
+        other => lines.push(format!("{path}: {other}")),
     }
+}
 
-    for bucket in buckets.values_mut() {
-        bucket.sort_by_key(|item| parse_start(&item.event).unwrap_or(now));
-    }
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn get_event_details(eventId: String, enrich: Option<bool>) -> Result<Event, AppError> {
+    let event = tauri::async_runtime::spawn_blocking(move || -> Result<models::Event, AppError> {
+        let store = Store::open_default()?;
+        load_event(&store, &eventId)
+    })
+    .await??;
 
-    Ok(buckets)
+    if !enrich.unwrap_or(false) {
+        return Ok(event);
+    }
+    match musicbrainz::enrich_event(event.clone()).await {
+        Ok(enriched) => Ok(enriched),
+        Err(err) => {
+            eprintln!("musicbrainz enrich failed: {err}");
+            Ok(event)
+        }
+    }
 }
 
 #[allow(non_snake_case)]
 #[tauri::command]
-async fn preview_post(eventId: String) -> Result<String, String> {
+async fn preview_post(eventId: String, format: Option<OutputFormat>) -> Result<String, String> {
     let event = tauri::async_runtime::spawn_blocking(move || -> Result<models::Event, String> {
         let store = Store::open_default().map_err(|e| e.to_string())?;
-        store
-            .get_event(&eventId)
-            .map_err(|e| format!("event lookup failed: {e}"))
+        Ok(load_event(&store, &eventId)?)
     })
     .await
     .map_err(|e| e.to_string())??;
@@ -114,13 +513,79 @@ async fn preview_post(eventId: String) -> Result<String, String> {
         }
     };
 
-    let composer = LLMComposer::from_env();
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    let render = RenderOptions::from_config(&config).with_format(format.unwrap_or_default());
+    let composer = LLMComposer::from_config(&config).with_render_options(render);
     match composer.compose_preview(&event_for_prompt).await {
         Ok(s) => Ok(s),
-        Err(_) => Ok(fallback_preview(&event_for_prompt)),
+        Err(_) => Ok(fallback_preview(
+            &event_for_prompt,
+            composer.render_options(),
+        )),
     }
 }
 
+/// Composes the real post (not the factual preview) in a caller-chosen
+/// style, so tones can be compared before posting. Nothing is saved.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn preview_post_styled(eventId: String, style: String) -> Result<String, String> {
+    let event = tauri::async_runtime::spawn_blocking(move || -> Result<models::Event, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        Ok(load_event(&store, &eventId)?)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let event_for_prompt = match musicbrainz::enrich_event(event.clone()).await {
+        Ok(enriched) => enriched,
+        Err(err) => {
+            eprintln!("musicbrainz enrich failed: {err}");
+            event
+        }
+    };
+
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    let composer = LLMComposer::from_config(&config);
+    let style = style.trim();
+    let style = (!style.is_empty()).then_some(style);
+    Ok(compose_in_style(&composer, &config, style, &event_for_prompt).await)
+}
+
+/// Composes the post for `eventId` (default style) and reports its length,
+/// links and emoji so settings can be tuned before posting.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn analyze_post(eventId: String) -> Result<llm::PostAnalysis, String> {
+    let text = compose_post(eventId, None).await?;
+    Ok(llm::analyze_post(&text))
+}
+
+/// Returns the system and user messages the composer would send for an
+/// event, after enrichment, without calling the model.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn debug_prompt(eventId: String, preview: bool) -> Result<llm::Prompt, String> {
+    let event = tauri::async_runtime::spawn_blocking(move || -> Result<models::Event, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        Ok(load_event(&store, &eventId)?)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let event_for_prompt = match musicbrainz::enrich_event(event.clone()).await {
+        Ok(enriched) => enriched,
+        Err(err) => {
+            eprintln!("musicbrainz enrich failed: {err}");
+            event
+        }
+    };
+
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    let composer = LLMComposer::from_config(&config);
+    Ok(composer.prompt(&event_for_prompt, preview))
+}
+
 #[allow(non_snake_case)]
 #[tauri::command]
 async fn mark_events_posted(eventIds: Vec<String>) -> Result<(), String> {
@@ -139,57 +604,2840 @@ async fn mark_events_posted(eventIds: Vec<String>) -> Result<(), String> {
     Ok(())
 }
 
-async fn persist_events(events: Vec<Event>) -> Result<usize, String> {
-    if events.is_empty() {
-        return Ok(0);
-    }
-
-    tauri::async_runtime::spawn_blocking(move || -> Result<usize, String> {
+/// Composes the post draft an operator copies into a Facebook group, using the
+/// style configured for `groupId` when one is set.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn compose_post(eventId: String, groupId: Option<String>) -> Result<String, String> {
+    let event = tauri::async_runtime::spawn_blocking(move || -> Result<models::Event, String> {
         let store = Store::open_default().map_err(|e| e.to_string())?;
-        for event in &events {
-            store
-                .upsert_event(event)
-                .map_err(|e| format!("failed to persist event {}: {e}", event.id))?;
-        }
-        Ok(events.len())
+        Ok(load_event(&store, &eventId)?)
     })
     .await
-    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())??;
+
+    let event_for_prompt = match musicbrainz::enrich_event(event.clone()).await {
+        Ok(enriched) => enriched,
+        Err(err) => {
+            eprintln!("musicbrainz enrich failed: {err}");
+            event
+        }
+    };
+
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    let composer = LLMComposer::from_config(&config);
+    Ok(compose_for_group(&composer, &config, groupId.as_deref(), &event_for_prompt).await)
 }
 
-fn parse_start(event: &models::Event) -> Option<DateTime<Utc>> {
-    chrono::DateTime::parse_from_rfc3339(&event.start_utc)
-        .ok()
-        .map(|dt| dt.with_timezone(&Utc))
+/// What the operator is about to publish for an event: the composed draft,
+/// the flyer to attach and the groups it is meant for.
+#[derive(Debug, Clone, Serialize)]
+struct PostPreview {
+    message: String,
+    image_url: Option<String>,
+    target_groups: Vec<String>,
 }
 
-fn bucket_for(days_until: i64) -> &'static str {
-    match days_until {
-        d if d <= 0 => "DAY_OF",
-        d if d < 7 => "LT_1W",
-        d if d < 14 => "LT_2W",
-        d if d < 30 => "LT_1M",
-        d if d < 60 => "LT_2M",
-        _ => "GTE_2M",
+/// Shows the real draft (not the internal preview), the event's image and
+/// the configured groups before anything is recorded. Nothing is posted.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn preview_facebook_post(eventId: String) -> Result<PostPreview, String> {
+    let event = tauri::async_runtime::spawn_blocking(move || -> Result<models::Event, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        Ok(load_event(&store, &eventId)?)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let event_for_prompt = match musicbrainz::enrich_event(event.clone()).await {
+        Ok(enriched) => enriched,
+        Err(err) => {
+            eprintln!("musicbrainz enrich failed: {err}");
+            event
+        }
+    };
+
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    let composer = LLMComposer::from_config(&config);
+    Ok(post_preview(&composer, &config, &event_for_prompt).await)
+}
+
+async fn post_preview(composer: &LLMComposer, config: &AppConfig, event: &Event) -> PostPreview {
+    let mut target_groups: Vec<String> = config.group_styles.keys().cloned().collect();
+    target_groups.sort();
+    PostPreview {
+        message: compose_for_group(composer, config, None, event).await,
+        image_url: event_image(event),
+        target_groups,
     }
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    scheduler::init();
-    tauri::Builder::default()
-        .invoke_handler(tauri::generate_handler![
+/// The flyer from the event's detail page, else the headliner's MusicBrainz
+/// image.
+fn event_image(event: &Event) -> Option<String> {
+    event
+        .extra
+        .get("image_url")
+        .or_else(|| event.extra.pointer("/musicbrainz/image"))
+        .and_then(|image| image.as_str())
+        .map(str::to_string)
+}
+
+/// Variations asked for when `compose_variations` isn't given a count.
+const DEFAULT_VARIATIONS: usize = 3;
+
+/// Drafts returned by `compose_variations`, with why there are fewer than
+/// asked for when that's the case.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ComposedVariations {
+    variations: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+/// Several alternative drafts for one event to choose from, at most
+/// `llm::MAX_VARIATIONS`. Falls back to the template draft alone when the
+/// model can't be reached; drafts already composed are kept if a later
+/// request fails.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn compose_variations(
+    eventId: String,
+    n: Option<usize>,
+) -> Result<ComposedVariations, String> {
+    let event = tauri::async_runtime::spawn_blocking(move || -> Result<models::Event, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        Ok(load_event(&store, &eventId)?)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let event_for_prompt = match musicbrainz::enrich_event(event.clone()).await {
+        Ok(enriched) => enriched,
+        Err(err) => {
+            eprintln!("musicbrainz enrich failed: {err}");
+            event
+        }
+    };
+
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    let composer = LLMComposer::from_config(&config);
+    let n = n.unwrap_or(DEFAULT_VARIATIONS);
+    let mut warnings = Vec::new();
+    if n > llm::MAX_VARIATIONS {
+        warnings.push(format!(
+            "asked for {n} variations; composed at most {}",
+            llm::MAX_VARIATIONS
+        ));
+    }
+    let drafts = match composer.compose_variations(&event_for_prompt, n).await {
+        Ok(variations) => {
+            if let Some(err) = variations.stopped {
+                warnings.push(format!(
+                    "model stopped after {} variations: {err}",
+                    variations.drafts.len()
+                ));
+            }
+            variations.drafts
+        }
+        Err(err) => {
+            warnings.push(format!(
+                "model unavailable, using the template draft: {err}"
+            ));
+            Vec::new()
+        }
+    };
+    let variations = if drafts.is_empty() {
+        vec![fallback(&event_for_prompt, composer.render_options())]
+    } else {
+        drafts
+    };
+    Ok(ComposedVariations {
+        variations,
+        warnings,
+    })
+}
+
+/// Composes the draft and records it as scheduled for `publishAt` (RFC 3339),
+/// pushed past the configured quiet hours if it lands inside them. The
+/// minimum lead is measured from the publish time; `force` skips it.
+/// Publishing is manual, so this is the operator's queue for setting up a
+/// scheduled post in the group; the event stays pending until marked posted.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn schedule_post(
+    eventId: String,
+    publishAt: String,
+    groupId: Option<String>,
+    force: Option<bool>,
+) -> Result<String, String> {
+    let mut publish_at = validate_publish_at(&publishAt, Utc::now())?;
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    if let Some(quiet) = &config.quiet_hours {
+        let deferred = scheduler::defer_past_quiet_hours(publish_at, quiet)?;
+        if deferred != publish_at {
+            eprintln!("publish time {publish_at} falls in quiet hours; deferred to {deferred}");
+            publish_at = deferred;
+        }
+    }
+    let min_lead_hours = config.min_lead_hours.filter(|_| !force.unwrap_or(false));
+    let event_id = eventId.clone();
+    tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        let event = load_event(&store, &event_id)?;
+        ensure_not_blocklisted(&event)?;
+        match min_lead_hours {
+            Some(hours) => ensure_lead_time(&event, publish_at, hours),
+            None => Ok(()),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let message = compose_post(eventId.clone(), groupId.clone()).await?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<String, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        record_scheduled_post(&store, &eventId, groupId.as_deref(), &message, publish_at)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn validate_publish_at(publish_at: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let publish_at = DateTime::parse_from_rfc3339(publish_at.trim())
+        .map_err(|e| format!("invalid publish time {publish_at:?}: {e}"))?
+        .with_timezone(&Utc);
+    let earliest = now + chrono::Duration::minutes(MIN_SCHEDULE_LEAD_MINUTES);
+    let latest = now + chrono::Duration::days(MAX_SCHEDULE_LEAD_DAYS);
+    if publish_at < earliest {
+        return Err(format!(
+            "publish time must be at least {MIN_SCHEDULE_LEAD_MINUTES} minutes from now"
+        ));
+    }
+    if publish_at > latest {
+        return Err(format!(
+            "publish time must be within {MAX_SCHEDULE_LEAD_DAYS} days from now"
+        ));
+    }
+    Ok(publish_at)
+}
+
+fn record_scheduled_post(
+    store: &Store,
+    event_id: &str,
+    group_id: Option<&str>,
+    message: &str,
+    publish_at: DateTime<Utc>,
+) -> Result<String, String> {
+    llm::validate_post_message(message)?;
+    load_event(store, event_id)?;
+    let payload = json!({
+        "message": message,
+        "group_id": group_id,
+        "published": false,
+        "scheduled_publish_time": publish_at.timestamp(),
+        "publish_at": publish_at.to_rfc3339(),
+    });
+    store
+        .record_post(event_id, group_id, None, "scheduled", &payload)
+        .map_err(|e| format!("record post failed: {e}"))
+}
+
+/// One scheduler tick: scrape everything, then apply `auto_purge_days`.
+async fn scheduled_scrape() {
+    if let Err(err) = scrape_all().await {
+        eprintln!("scheduled scrape failed: {err}");
+    }
+    let days = AppConfig::load()
+        .map(|config| config.auto_purge_days)
+        .unwrap_or(0);
+    let purged = tauri::async_runtime::spawn_blocking(move || -> Result<usize, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        purge_expired(&store, days, Utc::now())
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+    if let Err(err) = purged {
+        eprintln!("scheduled purge failed: {err}");
+    }
+}
+
+/// Removes events that started more than `days` days before `now` and
+/// records the run for `last_purge`; zero disables purging. Upcoming events
+/// are never old enough to qualify.
+fn purge_expired(store: &Store, days: u32, now: DateTime<Utc>) -> Result<usize, String> {
+    if days == 0 {
+        return Ok(0);
+    }
+    let cutoff = now - chrono::Duration::days(i64::from(days));
+    let removed = store
+        .purge_events_before(cutoff)
+        .map_err(|e| format!("purge failed: {e}"))?;
+    if let Err(err) = store.record_purge_run(removed) {
+        eprintln!("failed to record purge run: {err}");
+    }
+    Ok(removed)
+}
+
+/// Sets how long past events are kept; zero turns auto-purge off.
+#[tauri::command]
+async fn set_auto_purge_days(days: u32) -> Result<(), String> {
+    let mut config = AppConfig::load().map_err(|e| e.to_string())?;
+    config.auto_purge_days = days;
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Sets the query string appended to ticket and event links in composed posts;
+/// `None` or blank removes it.
+#[tauri::command]
+async fn set_ticket_link_suffix(suffix: Option<String>) -> Result<(), String> {
+    let mut config = AppConfig::load().map_err(|e| e.to_string())?;
+    config.ticket_link_suffix = suffix.filter(|suffix| !suffix.trim().is_empty());
+    config.save().map_err(|e| e.to_string())
+}
+
+/// The most recent auto-purge, or `None` if none has run.
+#[tauri::command]
+async fn last_purge() -> Result<Option<db::PurgeRun>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        store.last_purge_run().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn get_scheduler_config() -> Result<SchedulerConfig, String> {
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    Ok(config.scheduler)
+}
+
+/// Saves the background scrape settings; the running loop picks them up on
+/// its next cycle.
+#[tauri::command]
+async fn set_scheduler_config(scheduler: SchedulerConfig) -> Result<(), String> {
+    scheduler::validate(&scheduler)?;
+    let mut config = AppConfig::load().map_err(|e| e.to_string())?;
+    config.scheduler = scheduler;
+    config.save().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_group_styles() -> Result<HashMap<String, String>, String> {
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    Ok(config.group_styles)
+}
+
+/// Sets the compose style for a group; an empty style clears the override.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn set_group_style(groupId: String, style: String) -> Result<(), String> {
+    let mut config = AppConfig::load().map_err(|e| e.to_string())?;
+    let style = style.trim();
+    if style.is_empty() {
+        config.group_styles.remove(&groupId);
+    } else {
+        config.group_styles.insert(groupId, style.to_string());
+    }
+    config.save().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_genre_overrides() -> Result<HashMap<String, Vec<String>>, String> {
+    let overrides = musicbrainz::GenreOverrides::load().map_err(|e| e.to_string())?;
+    Ok(overrides.0)
+}
+
+/// Sets the genres used for `artist` instead of MusicBrainz; an empty list
+/// clears the override.
+#[tauri::command]
+async fn set_genre_override(artist: String, genres: Vec<String>) -> Result<(), String> {
+    let mut overrides = musicbrainz::GenreOverrides::load().map_err(|e| e.to_string())?;
+    overrides.set(&artist, genres);
+    overrides.save().map_err(|e| e.to_string())
+}
+
+/// Re-looks-up one artist after a wrong match, discarding only its cached
+/// profile. Returns the new profile, or `None` when nothing matched.
+#[tauri::command]
+async fn refresh_artist_genres(name: String) -> Result<Option<musicbrainz::ArtistProfile>, String> {
+    if name.trim().is_empty() {
+        return Err("artist name is empty".to_string());
+    }
+    musicbrainz::refresh_artist(&name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Upcoming events carrying `tag`, soonest first, for themed posts.
+#[tauri::command]
+async fn list_events_by_tag(tag: String) -> Result<Vec<Event>, String> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<Vec<Event>, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        store
+            .list_events_by_tag(&tag, Utc::now())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Plain-text list of every venue's shows in the next `within_days` days,
+/// soonest first, for pasting into chats. Built without the model.
+#[tauri::command]
+async fn text_digest(within_days: i64) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<String, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        let now = Utc::now();
+        let events = store
+            .list_upcoming_events(
+                now,
+                Some(now + chrono::Duration::days(within_days.max(0))),
+                false,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(render_text_digest(&events, within_days))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// One "Fri Oct 10 — PUP @ Treefort — tickets: …" line per event, dated in
+/// the venue's own offset.
+fn render_text_digest(events: &[Event], within_days: i64) -> String {
+    if events.is_empty() {
+        return format!("No upcoming shows in the next {within_days} days.");
+    }
+    events
+        .iter()
+        .map(|event| {
+            let date = parse_local_start(event)
+                .map(|start| start.format("%a %b %-d").to_string())
+                .unwrap_or_else(|| event.start_utc.clone());
+            let venue = event.venue_name.as_deref().unwrap_or(&event.venue_id);
+            let tickets = match event.ticket_url.as_deref() {
+                Some(url) => url,
+                None if event.is_free() => "free",
+                None => "TBA",
+            };
+            format!(
+                "{date} — {title} @ {venue} — tickets: {tickets}",
+                title = event.title_with_support(models::TitleMode::Count),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Artists MusicBrainz couldn't match and that have no genre override yet.
+#[tauri::command]
+async fn list_unmatched_artists() -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(|| -> Result<Vec<String>, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        let overrides = musicbrainz::GenreOverrides::load().map_err(|e| e.to_string())?;
+        unmatched_artists(&store, &overrides)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn unmatched_artists(
+    store: &Store,
+    overrides: &musicbrainz::GenreOverrides,
+) -> Result<Vec<String>, String> {
+    let artists = store
+        .list_unmatched_artists()
+        .map_err(|e| format!("musicbrainz cache lookup failed: {e}"))?;
+    Ok(artists
+        .into_iter()
+        .filter(|artist| overrides.genres_for(artist).is_none())
+        .collect())
+}
+
+/// Pending upcoming events keyed by venue id, each list sorted by start.
+/// `withinDays` drops shows further out than that. Skips enrichment.
+#[tauri::command]
+#[allow(non_snake_case)]
+async fn list_events_by_venue(
+    withinDays: Option<i64>,
+) -> Result<HashMap<String, Vec<Event>>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        let now = Utc::now();
+        let horizon = withinDays.map(|days| now + chrono::Duration::days(days));
+        store
+            .list_upcoming_by_venue(now, horizon)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Ids of stored events whose payload no longer loads, e.g. rows written by
+/// an older schema. They're left out of every listing until removed.
+#[tauri::command]
+async fn list_corrupt_events() -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        store.list_corrupt_event_ids().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Renders every pending event as an iCalendar document.
+#[tauri::command]
+async fn export_ics() -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(|| -> Result<String, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        let mut events: Vec<Event> = store
+            .list_pending_events()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|pending| pending.event)
+            .collect();
+        events.sort_by(|a, b| a.start_utc.cmp(&b.start_utc));
+        Ok(ics::render_calendar(&events))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// One event as its own VCALENDAR, for "add to calendar" on the detail screen.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn event_ics(eventId: String) -> Result<String, AppError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<String, AppError> {
+        let store = Store::open_default()?;
+        event_calendar(&store, &eventId)
+    })
+    .await?
+}
+
+fn event_calendar(store: &Store, event_id: &str) -> Result<String, AppError> {
+    let event = load_event(store, event_id)?;
+    Ok(ics::render_calendar(&[event]))
+}
+
+/// Sets the private organizer note on an event; blank clears it. Notes never
+/// reach composed posts.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn set_event_note(eventId: String, note: Option<String>) -> Result<(), AppError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<(), AppError> {
+        let store = Store::open_default()?;
+        load_event(&store, &eventId)?;
+        let note = note
+            .as_deref()
+            .map(str::trim)
+            .filter(|note| !note.is_empty());
+        Ok(store.set_event_note(&eventId, note)?)
+    })
+    .await?
+}
+
+/// How far off an event is, measured in the venue's own offset so every
+/// screen agrees on "today".
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct EventTiming {
+    /// Calendar days from today to the show date; 0 on the day, negative
+    /// once past.
+    days_until: i64,
+    hours_until: i64,
+    local_time_str: String,
+    is_past: bool,
+}
+
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn event_timing(eventId: String) -> Result<EventTiming, AppError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<EventTiming, AppError> {
+        let store = Store::open_default()?;
+        timing_for(&load_event(&store, &eventId)?, Utc::now())
+    })
+    .await?
+}
+
+fn timing_for(event: &Event, now: DateTime<Utc>) -> Result<EventTiming, AppError> {
+    let start = parse_local_start(event).ok_or_else(|| {
+        AppError::Internal(format!("event {} has an unreadable start time", event.id))
+    })?;
+    Ok(EventTiming {
+        days_until: local_days_until(&start, now),
+        hours_until: start.signed_duration_since(now).num_hours(),
+        local_time_str: start.format("%a, %b %-d · %-I:%M %p").to_string(),
+        is_past: start < now,
+    })
+}
+
+/// Everything recorded in the post log, with venue and headliner, as CSV or
+/// JSON for download.
+#[tauri::command]
+async fn export_post_log(format: export::ExportFormat) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<String, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        let entries = store.list_post_log().map_err(|e| e.to_string())?;
+        export::render_post_log(&entries, format)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Reports the state of each subsystem for the status page. The LLM and
+/// MusicBrainz checks run in parallel with short timeouts.
+#[tauri::command]
+async fn healthcheck() -> Result<HealthReport, String> {
+    let config = AppConfig::load();
+    let composer = LLMComposer::from_config(&config.as_ref().cloned().unwrap_or_default());
+    let llm_check = tauri::async_runtime::spawn(async move {
+        match composer.ping(HEALTHCHECK_TIMEOUT).await {
+            Ok(endpoint) => SubsystemHealth::ok(format!("reachable at {endpoint}")),
+            Err(err) => SubsystemHealth::failed(err.to_string()),
+        }
+    });
+    let musicbrainz_check = tauri::async_runtime::spawn(async {
+        match musicbrainz::ping(HEALTHCHECK_TIMEOUT).await {
+            Ok(()) => SubsystemHealth::ok("reachable"),
+            Err(err) => SubsystemHealth::failed(err.to_string()),
+        }
+    });
+    let database = tauri::async_runtime::spawn_blocking(|| match Store::open_default() {
+        Ok(store) => database_health(&store),
+        Err(err) => SubsystemHealth::failed(format!("cannot open database: {err}")),
+    });
+
+    Ok(HealthReport {
+        database: database.await.map_err(|e| e.to_string())?,
+        config: config_health(&config),
+        facebook: SubsystemHealth::ok(
+            "Graph API integration removed; drafts are published manually",
+        ),
+        llm: llm_check.await.map_err(|e| e.to_string())?,
+        musicbrainz: musicbrainz_check.await.map_err(|e| e.to_string())?,
+    })
+}
+
+/// Smoke-tests the whole pipeline without network access or the real
+/// database: sample scrape, in-memory persist, stubbed enrichment, fallback
+/// compose and a dry-run post. Requires `SHOW_SCRAPER_MOCK=1`.
+#[tauri::command]
+async fn self_test() -> Result<Vec<SelfTestStage>, String> {
+    self_test_in_mode(std::env::var(MOCK_ENV).as_deref() == Ok("1")).await
+}
+
+/// `self_test` with the mock-mode check resolved by the caller.
+async fn self_test_in_mode(mock: bool) -> Result<Vec<SelfTestStage>, String> {
+    if !mock {
+        return Err(format!("self_test only runs with {MOCK_ENV}=1"));
+    }
+    tauri::async_runtime::spawn_blocking(run_self_test)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn run_self_test() -> Vec<SelfTestStage> {
+    let mut stages = Vec::new();
+
+    // Empty pages make every parser fall back to its sample event.
+    let mut events = Vec::new();
+    let mut failures = Vec::new();
+    for venue_id in scraping::known_venue_ids() {
+        match scraping::parse_html(venue_id, "") {
+            Ok(report) => events.extend(report.events),
+            Err(err) => failures.push(format!("{venue_id}: {err}")),
+        }
+    }
+    let scraped = failures.is_empty() && !events.is_empty();
+    stages.push(SelfTestStage::new(
+        "scrape",
+        scraped,
+        if scraped {
+            format!("{} sample events", events.len())
+        } else {
+            format!("parsers failed: {}", failures.join("; "))
+        },
+    ));
+
+    let store = match Store::open_in_memory() {
+        Ok(store) => store,
+        Err(err) => {
+            stages.push(SelfTestStage::new("persist", false, err.to_string()));
+            return stages;
+        }
+    };
+    let persisted = events
+        .iter()
+        .try_for_each(|event| store.upsert_event(event))
+        .and_then(|()| store.count_events());
+    stages.push(match persisted {
+        Ok(count) => SelfTestStage::new(
+            "persist",
+            count as usize == events.len(),
+            format!("{count} events stored in memory"),
+        ),
+        Err(err) => SelfTestStage::new("persist", false, err.to_string()),
+    });
+
+    let Some(event) = events.into_iter().next() else {
+        stages.push(SelfTestStage::new("enrich", false, "no event to enrich"));
+        return stages;
+    };
+    let mut stub = musicbrainz::GenreOverrides::default();
+    stub.set(&event.title(), vec!["self-test".to_string()]);
+    let enriched = tauri::async_runtime::block_on(musicbrainz::enrich_event_with(
+        event.clone(),
+        &stub,
+        config::EnrichScope::HeadlinerOnly,
+    ));
+    let event = match enriched {
+        Ok(enriched) if enriched.tags.iter().any(|tag| tag == "self-test") => {
+            stages.push(SelfTestStage::new("enrich", true, "stubbed genres applied"));
+            enriched
+        }
+        Ok(_) => {
+            stages.push(SelfTestStage::new(
+                "enrich",
+                false,
+                "stubbed genres missing",
+            ));
+            event
+        }
+        Err(err) => {
+            stages.push(SelfTestStage::new("enrich", false, err.to_string()));
+            event
+        }
+    };
+
+    let draft = fallback(&event, &RenderOptions::default());
+    stages.push(match llm::validate_post_message(&draft) {
+        Ok(()) => SelfTestStage::new(
+            "compose",
+            true,
+            format!("{} character fallback draft", draft.chars().count()),
+        ),
+        Err(err) => SelfTestStage::new("compose", false, err),
+    });
+
+    stages.push(
+        match record_custom_message(&store, &event.id, None, &draft) {
+            Ok(post_id) => SelfTestStage::new("post", true, format!("dry run recorded {post_id}")),
+            Err(err) => SelfTestStage::new("post", false, err),
+        },
+    );
+    stages
+}
+
+fn database_health(store: &Store) -> SubsystemHealth {
+    match store.count_events() {
+        Ok(count) => SubsystemHealth::ok(format!("{count} events stored")),
+        Err(err) => SubsystemHealth::failed(format!("query failed: {err}")),
+    }
+}
+
+fn config_health(config: &Result<AppConfig, config::ConfigError>) -> SubsystemHealth {
+    match config {
+        Ok(_) => SubsystemHealth::ok(format!("loaded from {}", utils::config_path().display())),
+        Err(err) => SubsystemHealth::failed(err.to_string()),
+    }
+}
+
+#[tauri::command]
+async fn get_enabled_venues() -> Result<Vec<String>, String> {
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    Ok(config.enabled_venues)
+}
+
+/// Replaces the venues included in batch scrapes, in run order; an empty
+/// list enables every venue.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn set_enabled_venues(venueIds: Vec<String>) -> Result<(), String> {
+    let known = scraping::known_venue_ids();
+    if let Some(unknown) = venueIds.iter().find(|id| !known.contains(&id.as_str())) {
+        return Err(format!("unknown venue id: {unknown}"));
+    }
+    let mut config = AppConfig::load().map_err(|e| e.to_string())?;
+    config.enabled_venues = venueIds;
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Returns a posted event to the pending list and logs the undo as an
+/// `unposted` row in the post history. This only changes local state: anything
+/// already published to Facebook stays up and must be removed there by hand.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn unpost_event(eventId: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<String, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        undo_post(&store, &eventId)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn undo_post(store: &Store, event_id: &str) -> Result<String, String> {
+    load_event(store, event_id)?;
+    let was_posted = store
+        .unmark_posted(event_id)
+        .map_err(|e| format!("unmark posted failed: {e}"))?;
+    if !was_posted {
+        return Err(format!("event is not posted: {event_id}"));
+    }
+    store
+        .record_post(event_id, None, None, "unposted", &json!({}))
+        .map_err(|e| format!("record post failed: {e}"))
+}
+
+/// One member of a likely-duplicate group, with the fields needed to decide
+/// which to keep.
+#[derive(Debug, Clone, Serialize)]
+struct DuplicateCandidate {
+    id: String,
+    venue_id: String,
+    venue_name: Option<String>,
+    headliner: String,
+    start_utc: String,
+}
+
+/// Groups pending events that look like the same show, by headliner
+/// similarity and start window from `cross_venue_dedup`. Read-only; merge with
+/// `merge_events` after review.
+#[tauri::command]
+async fn find_duplicate_events() -> Result<Vec<Vec<DuplicateCandidate>>, String> {
+    let rules = AppConfig::load()
+        .map(|config| config.cross_venue_dedup)
+        .unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || -> Result<_, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        let events = store
+            .list_pending_events()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|pending| pending.event)
+            .collect();
+        Ok(duplicate_groups(events, &rules))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn duplicate_groups(
+    mut events: Vec<Event>,
+    rules: &config::CrossVenueDedup,
+) -> Vec<Vec<DuplicateCandidate>> {
+    events.sort_by(|a, b| a.start_utc.cmp(&b.start_utc));
+    let window = chrono::Duration::minutes(i64::from(rules.window_minutes));
+    let mut groups: Vec<Vec<Event>> = Vec::new();
+    for event in events {
+        let Some(start) = parse_start(&event) else {
+            continue;
+        };
+        let alike = |other: &Event| {
+            parse_start(other).is_some_and(|other_start| (other_start - start).abs() <= window)
+                && scraping::base::name_similarity(&other.title(), &event.title())
+                    >= rules.min_name_similarity
+        };
+        match groups.iter_mut().find(|group| group.iter().any(alike)) {
+            Some(group) => group.push(event),
+            None => groups.push(vec![event]),
+        }
+    }
+    groups
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|group| {
+            group
+                .into_iter()
+                .map(|event| DuplicateCandidate {
+                    headliner: event.title(),
+                    id: event.id,
+                    venue_id: event.venue_id,
+                    venue_name: event.venue_name,
+                    start_utc: event.start_utc,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Folds `dropId` into `keepId` when dedup missed a duplicate. Merging a posted
+/// event into a pending one marks the survivor posted, so it needs `confirm`.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn merge_events(
+    keepId: String,
+    dropId: String,
+    confirm: Option<bool>,
+) -> Result<Event, String> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<Event, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        merge_event_rows(&store, &keepId, &dropId, confirm.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn merge_event_rows(
+    store: &Store,
+    keep_id: &str,
+    drop_id: &str,
+    confirm: bool,
+) -> Result<Event, String> {
+    if keep_id == drop_id {
+        return Err(format!("cannot merge an event into itself: {keep_id}"));
+    }
+    let mut keep = load_event(store, keep_id)?;
+    let drop = load_event(store, drop_id)?;
+
+    let lookup = |id: &str| {
+        store
+            .posted_at(id)
+            .map_err(|e| format!("event lookup failed: {e}"))
+    };
+    if !confirm && lookup(drop_id)?.is_some() && lookup(keep_id)?.is_none() {
+        return Err(format!(
+            "event {drop_id} is already posted; confirm to merge it into pending event {keep_id}"
+        ));
+    }
+
+    keep.absorb(&drop);
+    store
+        .merge_into(&keep, drop_id)
+        .map_err(|e| format!("merge failed: {e}"))?;
+    Ok(keep)
+}
+
+/// Records operator-supplied copy for an event and marks it posted. The Graph API
+/// integration was removed, so the message is stored for the manual workflow rather
+/// than published; it returns the id of the new `posts` row. Passing `groupId` lets
+/// an already-posted event be recorded against another group.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn post_custom_message(
+    eventId: String,
+    message: String,
+    groupId: Option<String>,
+    force: Option<bool>,
+) -> Result<String, String> {
+    let min_lead_hours = lead_requirement(force)?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<String, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        ensure_postable(&store, &eventId, min_lead_hours)?;
+        record_custom_message(&store, &eventId, groupId.as_deref(), &message)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Outcome of recording a message against one group.
+#[derive(Debug, Clone, Serialize)]
+struct GroupPostResult {
+    group_id: String,
+    post_id: Option<String>,
+    error: Option<String>,
+}
+
+/// `post_custom_message` for several groups at once. Each group is recorded
+/// as it succeeds and a failure doesn't stop the rest; the event counts as
+/// posted once any group succeeds. Retry failures with `retry_post_to_group`.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn post_custom_message_to_groups(
+    eventId: String,
+    message: String,
+    groupIds: Vec<String>,
+    force: Option<bool>,
+) -> Result<Vec<GroupPostResult>, String> {
+    let min_lead_hours = lead_requirement(force)?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<_, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        ensure_postable(&store, &eventId, min_lead_hours)?;
+        Ok(record_to_groups(&store, &eventId, &groupIds, &message))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Records `message` for one group that failed in a multi-group post. The
+/// blocklist and minimum lead apply as they did to the first attempt.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn retry_post_to_group(
+    eventId: String,
+    groupId: String,
+    message: String,
+    force: Option<bool>,
+) -> Result<GroupPostResult, String> {
+    let min_lead_hours = lead_requirement(force)?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<_, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        ensure_postable(&store, &eventId, min_lead_hours)?;
+        Ok(record_to_group(&store, &eventId, &groupId, &message))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn record_to_groups(
+    store: &Store,
+    event_id: &str,
+    group_ids: &[String],
+    message: &str,
+) -> Vec<GroupPostResult> {
+    group_ids
+        .iter()
+        .map(|group_id| record_to_group(store, event_id, group_id, message))
+        .collect()
+}
+
+fn record_to_group(
+    store: &Store,
+    event_id: &str,
+    group_id: &str,
+    message: &str,
+) -> GroupPostResult {
+    let group_id = group_id.trim();
+    let result = if group_id.is_empty() {
+        Err("group id is empty".to_string())
+    } else {
+        record_custom_message(store, event_id, Some(group_id), message)
+    };
+    GroupPostResult {
+        group_id: group_id.to_string(),
+        post_id: result.as_ref().ok().cloned(),
+        error: result.err(),
+    }
+}
+
+/// The checks every manual post goes through: the blocklist, then the
+/// minimum lead unless `lead_requirement` skipped it.
+fn ensure_postable(
+    store: &Store,
+    event_id: &str,
+    min_lead_hours: Option<u32>,
+) -> Result<(), String> {
+    let event = load_event(store, event_id)?;
+    ensure_not_blocklisted(&event)?;
+    match min_lead_hours {
+        Some(hours) => ensure_lead_time(&event, Utc::now(), hours),
+        None => Ok(()),
+    }
+}
+
+/// Refuses events whose headliner is on the artist blocklist.
+fn ensure_not_blocklisted(event: &Event) -> Result<(), String> {
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    if config.blocks_headliner(event) {
+        return Err(format!(
+            "{} is on the artist blocklist; not posting {}",
+            event.title(),
+            event.id
+        ));
+    }
+    Ok(())
+}
+
+/// The configured minimum lead, or `None` when `force` skips the check.
+fn lead_requirement(force: Option<bool>) -> Result<Option<u32>, String> {
+    if force.unwrap_or(false) {
+        return Ok(None);
+    }
+    let config = AppConfig::load().map_err(|e| e.to_string())?;
+    Ok(config.min_lead_hours)
+}
+
+/// Refuses to post about a show starting less than `min_lead_hours` after
+/// `at`, when nobody would have time to act on it.
+fn ensure_lead_time(event: &Event, at: DateTime<Utc>, min_lead_hours: u32) -> Result<(), String> {
+    let Some(start) = parse_start(event) else {
+        return Ok(());
+    };
+    let lead = start.signed_duration_since(at);
+    if lead < chrono::Duration::hours(i64::from(min_lead_hours)) {
+        return Err(format!(
+            "event {} starts in {} hours, less than the {min_lead_hours} hour minimum lead; \
+             force the post to override",
+            event.id,
+            lead.num_hours().max(0)
+        ));
+    }
+    Ok(())
+}
+
+fn record_custom_message(
+    store: &Store,
+    event_id: &str,
+    group_id: Option<&str>,
+    message: &str,
+) -> Result<String, String> {
+    llm::validate_post_message(message)?;
+    load_event(store, event_id)?;
+    let claimed = store
+        .claim_for_posting(event_id)
+        .map_err(|e| format!("mark posted failed: {e}"))?;
+    // A posted event may still go out to another group, once per group.
+    if !claimed && !new_destination(store, event_id, group_id)? {
+        return Err(match group_id {
+            Some(group) => format!("event already posted to {group}: {event_id}"),
+            None => format!("event already posted: {event_id}"),
+        });
+    }
+    match store.record_post(
+        event_id,
+        group_id,
+        None,
+        "manual",
+        &json!({ "message": message }),
+    ) {
+        Ok(post_id) => Ok(post_id),
+        Err(err) => {
+            if claimed {
+                if let Err(undo) = store.unmark_posted(event_id) {
+                    eprintln!("failed to release {event_id} after post error: {undo}");
+                }
+            }
+            Err(format!("record post failed: {err}"))
+        }
+    }
+}
+
+/// Whether `group_id` names a group the event hasn't been posted or
+/// scheduled to yet.
+fn new_destination(store: &Store, event_id: &str, group_id: Option<&str>) -> Result<bool, String> {
+    let Some(group_id) = group_id else {
+        return Ok(false);
+    };
+    let posts = store
+        .posts_for_event(event_id)
+        .map_err(|e| format!("post history failed: {e}"))?;
+    Ok(!posts.iter().any(|post| {
+        matches!(post.status.as_deref(), Some("manual" | "scheduled"))
+            && post.group_id.as_deref() == Some(group_id)
+    }))
+}
+
+/// Every post log row for an event, one per destination group.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn posted_history(eventId: String) -> Result<Vec<db::PostRecord>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        store
+            .posts_for_event(&eventId)
+            .map_err(|e| format!("post history failed: {e}"))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+async fn compose_for_group(
+    composer: &LLMComposer,
+    config: &AppConfig,
+    group_id: Option<&str>,
+    event: &Event,
+) -> String {
+    let style = group_id.and_then(|id| config.style_for_group(id));
+    compose_in_style(composer, config, style, event).await
+}
+
+/// Composes with `style`, or the composer's default when `None`, falling back
+/// to the template draft if the model call fails.
+async fn compose_in_style(
+    composer: &LLMComposer,
+    config: &AppConfig,
+    style: Option<&str>,
+    event: &Event,
+) -> String {
+    let composed = match style {
+        Some(style) => composer.compose_with_style(event, style).await,
+        None => composer.compose(event).await,
+    };
+    match composed {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("compose failed for {}: {err}", event.id);
+            fallback(event, &RenderOptions::from_config(config))
+        }
+    }
+}
+
+fn load_event(store: &Store, event_id: &str) -> Result<Event, AppError> {
+    match store.get_event(event_id) {
+        Ok(event) => Ok(event),
+        Err(rusqlite::Error::QueryReturnedNoRows) => {
+            Err(AppError::EventNotFound(event_id.to_string()))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+async fn load_buckets() -> Result<HashMap<&'static str, Vec<BucketItem>>, String> {
+    load_pending_buckets(false).await
+}
+
+/// Pending events sorted into time buckets. `fast` skips MusicBrainz and
+/// keeps the tags already stored on each event.
+async fn load_pending_buckets(
+    fast: bool,
+) -> Result<HashMap<&'static str, Vec<BucketItem>>, String> {
+    let pending = tauri::async_runtime::spawn_blocking(|| -> Result<Vec<PendingEvent>, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        store.list_pending_events().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let events = pending.into_iter().map(|item| item.event).collect();
+    let mut buckets = bucket_pending(events, fast, musicbrainz::enrich_event, Utc::now()).await;
+    let config = AppConfig::load().unwrap_or_default();
+    for item in buckets.values_mut().flatten() {
+        item.blocklisted = config.blocks_headliner(&item.event);
+    }
+    Ok(buckets)
+}
+
+async fn bucket_pending<F, Fut>(
+    events: Vec<Event>,
+    fast: bool,
+    enrich: F,
+    now: DateTime<Utc>,
+) -> HashMap<&'static str, Vec<BucketItem>>
+where
+    F: Fn(Event) -> Fut,
+    Fut: std::future::Future<Output = Result<Event, musicbrainz::MusicBrainzError>>,
+{
+    let mut enriched_events: Vec<Event> = Vec::with_capacity(events.len());
+    for event in events {
+        if fast {
+            enriched_events.push(event);
+            continue;
+        }
+        match enrich(event.clone()).await {
+            Ok(enriched) => enriched_events.push(enriched),
+            Err(err) => {
+                eprintln!("musicbrainz enrich failed: {err}");
+                enriched_events.push(event);
+            }
+        }
+    }
+
+    let mut buckets: HashMap<&'static str, Vec<BucketItem>> =
+        BUCKET_KEYS.iter().map(|key| (*key, Vec::new())).collect();
+
+    for event in enriched_events {
+        let start = match parse_local_start(&event) {
+            Some(dt) => dt,
+            None => continue,
+        };
+        if !event.is_upcoming(now) {
+            continue;
+        }
+        let days_until = local_days_until(&start, now);
+        let bucket = bucket_for(days_until);
+        if let Some(b) = buckets.get_mut(bucket) {
+            b.push(BucketItem {
+                days_until,
+                event,
+                blocklisted: false,
+            });
+        }
+    }
+
+    for bucket in buckets.values_mut() {
+        bucket.sort_by_key(|item| parse_start(&item.event).unwrap_or(now));
+    }
+
+    buckets
+}
+
+async fn compose_previews(
+    composer: Arc<LLMComposer>,
+    events: Vec<Event>,
+) -> Result<Vec<PreviewItem>, String> {
+    let mut handles = Vec::with_capacity(events.len());
+    for event in events {
+        let composer = Arc::clone(&composer);
+        handles.push(tauri::async_runtime::spawn(async move {
+            let preview = match composer.compose_preview(&event).await {
+                Ok(text) => text,
+                Err(err) => {
+                    eprintln!("preview compose failed for {}: {err}", event.id);
+                    fallback_preview(&event, composer.render_options())
+                }
+            };
+            PreviewItem {
+                event_id: event.id,
+                preview,
+            }
+        }));
+    }
+
+    let mut previews = Vec::with_capacity(handles.len());
+    for handle in handles {
+        previews.push(handle.await.map_err(|e| e.to_string())?);
+    }
+    Ok(previews)
+}
+
+fn record_scrape_runs<'a>(
+    runs: impl IntoIterator<Item = (&'a str, usize, Option<&'a str>, Option<&'a str>)>,
+) {
+    let store = match Store::open_default() {
+        Ok(store) => store,
+        Err(err) => {
+            eprintln!("failed to open store for scrape history: {err}");
+            return;
+        }
+    };
+    for (venue_id, event_count, error, selector_warning) in runs {
+        if let Err(err) = store.record_scrape_run(venue_id, event_count, error, selector_warning) {
+            eprintln!("failed to record scrape run for {venue_id}: {err}");
+        }
+    }
+}
+
+fn venue_statuses(
+    store: &Store,
+    scrapers: Vec<scraping::ScraperInfo>,
+) -> Result<Vec<VenueStatus>, String> {
+    let mut runs = store.latest_scrape_runs().map_err(|e| e.to_string())?;
+    Ok(scrapers
+        .into_iter()
+        .map(|info| {
+            let run = runs.remove(&info.id);
+            VenueStatus {
+                last_scraped_at: run.as_ref().map(|r| r.ran_at_utc.clone()),
+                last_event_count: run.as_ref().map(|r| r.event_count),
+                selector_warning: run.as_ref().and_then(|r| r.selector_warning.clone()),
+                last_error: run.and_then(|r| r.error),
+                id: info.id,
+                name: info.name,
+                url: info.url,
+            }
+        })
+        .collect())
+}
+
+/// Enriches the next few upcoming events that still have no genres and saves
+/// any that gained some, so the pending view never waits on MusicBrainz.
+/// Returns how many events were updated.
+#[tauri::command]
+async fn enrich_next_batch() -> Result<usize, String> {
+    let now = Utc::now();
+    let batch = tauri::async_runtime::spawn_blocking(move || -> Result<Vec<Event>, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        let events = store
+            .list_events_missing_genres(ENRICH_BATCH_SIZE, now)
+            .map_err(|e| e.to_string())?;
+        for event in &events {
+            store
+                .mark_enrich_attempted(&event.id, now)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(events)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    persist_events(enrich_missing_genres(batch, musicbrainz::enrich_event).await).await
+}
+
+/// Cancel flag of the running `reenrich_all`, `None` when none is running.
+/// Each run gets its own flag so a cancel can't leak into the next run.
+static REENRICH_RUN: std::sync::Mutex<Option<Arc<AtomicBool>>> = std::sync::Mutex::new(None);
+
+/// Clears `REENRICH_RUN` when a run ends, however it ends.
+struct ReenrichRun(Arc<AtomicBool>);
+
+impl ReenrichRun {
+    fn start() -> Result<Self, String> {
+        let mut running = REENRICH_RUN.lock().map_err(|e| e.to_string())?;
+        if running.is_some() {
+            return Err("re-enrichment is already running".to_string());
+        }
+        let cancelled = Arc::new(AtomicBool::new(false));
+        *running = Some(Arc::clone(&cancelled));
+        Ok(Self(cancelled))
+    }
+}
+
+impl Drop for ReenrichRun {
+    fn drop(&mut self) {
+        if let Ok(mut running) = REENRICH_RUN.lock() {
+            *running = None;
+        }
+    }
+}
+
+/// Emitted as `reenrich-progress` after each artist `reenrich_all` finishes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ReenrichProgress {
+    done: usize,
+    total: usize,
+    artist: String,
+}
+
+/// Re-runs genre enrichment over every pending upcoming event, grouped by
+/// headliner so each artist is looked up once however many shows it has,
+/// `reenrich_concurrency` artists at a time. Progress goes out as
+/// `reenrich-progress` events and `cancel_reenrich` stops it before the next
+/// artists; what finished is still saved. Only one run at a time. Returns
+/// how many events were saved.
+#[tauri::command]
+async fn reenrich_all(app: tauri::AppHandle) -> Result<usize, String> {
+    let run = ReenrichRun::start()?;
+    let concurrency = AppConfig::load()
+        .map(|config| config.reenrich_concurrency)
+        .unwrap_or(1);
+    let groups = tauri::async_runtime::spawn_blocking(|| -> Result<Vec<Vec<Event>>, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        Ok(store
+            .list_upcoming_by_headliner(Utc::now())
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|(_, events)| events)
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let enriched = reenrich_by_artist(
+        groups,
+        musicbrainz::enrich_event,
+        concurrency,
+        &run.0,
+        |progress| {
+            if let Err(err) = app.emit("reenrich-progress", progress) {
+                eprintln!("reenrich progress not delivered: {err}");
+            }
+        },
+    )
+    .await;
+    persist_events(enriched).await
+}
+
+/// Stops the running `reenrich_all` before its next artists. Returns false
+/// when no run was active, so there was nothing to cancel.
+#[tauri::command]
+async fn cancel_reenrich() -> Result<bool, String> {
+    let running = REENRICH_RUN.lock().map_err(|e| e.to_string())?;
+    Ok(match running.as_ref() {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Enriches per-headliner groups of events in order, up to `concurrency`
+/// groups at once, checking `cancelled` before each batch of groups.
+/// Progress is reported per group in order. Returns the events that were
+/// enriched; failures keep the stored version.
+async fn reenrich_by_artist<F, Fut>(
+    groups: Vec<Vec<Event>>,
+    enrich: F,
+    concurrency: usize,
+    cancelled: &AtomicBool,
+    mut progress: impl FnMut(&ReenrichProgress),
+) -> Vec<Event>
+where
+    F: Fn(Event) -> Fut + Clone + Send + 'static,
+    Fut:
+        std::future::Future<Output = Result<Event, musicbrainz::MusicBrainzError>> + Send + 'static,
+{
+    let total = groups.len();
+    let mut enriched = Vec::new();
+    let mut done = 0;
+    let mut groups = groups.into_iter().peekable();
+    while groups.peek().is_some() {
+        if cancelled.load(Ordering::SeqCst) {
+            eprintln!("reenrich cancelled after {done} of {total} artists");
+            break;
+        }
+        let handles: Vec<_> = groups
+            .by_ref()
+            .take(concurrency.max(1))
+            .map(|group| {
+                let enrich = enrich.clone();
+                let artist = group.first().map(Event::title).unwrap_or_default();
+                let handle = tauri::async_runtime::spawn(async move {
+                    let mut enriched = Vec::new();
+                    for event in group {
+                        match enrich(event).await {
+                            Ok(event) => enriched.push(event),
+                            Err(err) => eprintln!("musicbrainz enrich failed: {err}"),
+                        }
+                    }
+                    enriched
+                });
+                (artist, handle)
+            })
+            .collect();
+        for (artist, handle) in handles {
+            match handle.await {
+                Ok(group) => enriched.extend(group),
+                Err(err) => eprintln!("reenrich task for {artist} failed: {err}"),
+            }
+            done += 1;
+            progress(&ReenrichProgress {
+                done,
+                total,
+                artist,
+            });
+        }
+    }
+    enriched
+}
+
+/// Runs `enrich` over each event and keeps the ones that came back tagged.
+async fn enrich_missing_genres<F, Fut>(events: Vec<Event>, enrich: F) -> Vec<Event>
+where
+    F: Fn(Event) -> Fut,
+    Fut: std::future::Future<Output = Result<Event, musicbrainz::MusicBrainzError>>,
+{
+    let mut tagged = Vec::new();
+    for event in events {
+        match enrich(event).await {
+            Ok(enriched) if !enriched.tags.is_empty() => tagged.push(enriched),
+            Ok(_) => {}
+            Err(err) => eprintln!("musicbrainz enrich failed: {err}"),
+        }
+    }
+    tagged
+}
+
+async fn persist_events(events: Vec<Event>) -> Result<usize, String> {
+    if events.is_empty() {
+        return Ok(0);
+    }
+
+    tauri::async_runtime::spawn_blocking(move || -> Result<usize, String> {
+        let store = Store::open_default().map_err(|e| e.to_string())?;
+        for event in &events {
+            store
+                .upsert_event(event)
+                .map_err(|e| format!("failed to persist event {}: {e}", event.id))?;
+        }
+        Ok(events.len())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn parse_start(event: &models::Event) -> Option<DateTime<Utc>> {
+    chrono::DateTime::parse_from_rfc3339(&event.start_utc)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// The start in the venue's own offset, from `start_local`, else `start_utc`.
+fn parse_local_start(event: &models::Event) -> Option<DateTime<chrono::FixedOffset>> {
+    event
+        .start_local
+        .as_deref()
+        .and_then(|local| DateTime::parse_from_rfc3339(local).ok())
+        .or_else(|| DateTime::parse_from_rfc3339(&event.start_utc).ok())
+}
+
+/// Calendar days from today to the start's date, both taken in the start's
+/// offset, so a late show counts toward the venue's day rather than UTC's.
+fn local_days_until(start: &DateTime<chrono::FixedOffset>, now: DateTime<Utc>) -> i64 {
+    let local_now = now.with_timezone(start.offset());
+    start
+        .date_naive()
+        .signed_duration_since(local_now.date_naive())
+        .num_days()
+}
+
+fn bucket_for(days_until: i64) -> &'static str {
+    match days_until {
+        d if d <= 0 => "DAY_OF",
+        d if d < 7 => "LT_1W",
+        d if d < 14 => "LT_2W",
+        d if d < 30 => "LT_1M",
+        d if d < 60 => "LT_2M",
+        _ => "GTE_2M",
+    }
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    scheduler::init(scheduled_scrape);
+    tauri::Builder::default()
+        .invoke_handler(tauri::generate_handler![
             list_venues,
+            venue_status,
             scrape_all,
+            scrape_all_outcome,
             scrape_venue,
+            scrape_venue_outcome,
+            scrape_venue_preview,
+            reparse_snapshot,
+            validate_venue_selectors,
             list_pending_buckets,
+            preview_bucket,
+            compose_roundup,
+            get_event_details,
+            get_event_extra,
+            compose_post,
+            schedule_post,
+            get_scheduler_config,
+            set_scheduler_config,
+            set_auto_purge_days,
+            last_purge,
+            get_group_styles,
+            set_group_style,
+            get_genre_overrides,
+            set_genre_override,
+            list_unmatched_artists,
+            get_enabled_venues,
+            export_ics,
+            list_corrupt_events,
+            list_events_by_venue,
+            healthcheck,
+            self_test,
+            set_enabled_venues,
             preview_post,
-            mark_events_posted
+            preview_post_styled,
+            analyze_post,
+            debug_prompt,
+            mark_events_posted,
+            unpost_event,
+            merge_events,
+            post_custom_message,
+            posted_history,
+            enrich_next_batch,
+            export_post_log,
+            event_ics,
+            set_ticket_link_suffix,
+            find_duplicate_events,
+            post_custom_message_to_groups,
+            retry_post_to_group,
+            event_timing,
+            refresh_artist_genres,
+            compose_variations,
+            preview_facebook_post,
+            list_events_by_tag,
+            scrape_all_dry_run,
+            set_event_note,
+            reenrich_all,
+            cancel_reenrich,
+            diagnose_scrapers,
+            text_digest
         ])
         .setup(|_| {
+            utils::ensure_writable_data_root()
+                .map_err(|e| -> Box<dyn std::error::Error> { e.into() })?;
             Store::open_default().map_err(|e| -> Box<dyn std::error::Error> { Box::new(e) })?;
+            if let Err(err) = utils::cache::cache_prune() {
+                eprintln!("cache prune failed: {err}");
+            }
             Ok(())
         })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{chat_completion, MockResponse, MockServer};
+
+    fn pending_event(venue_id: &str, headliner: &str) -> Event {
+        let mut event = db::sample_event(venue_id, headliner, Utc::now());
+        event.id = format!("{venue_id}-{headliner}");
+        event.artists = vec![headliner.to_string()];
+        event
+    }
+
+    #[test]
+    fn dry_run_batches_dedupe_like_a_real_scrape_but_write_nothing() {
+        let outcome = |venue_id: &str, headliners: &[&str]| scraping::VenueOutcome {
+            venue_id: venue_id.to_string(),
+            events: headliners
+                .iter()
+                .map(|headliner| pending_event(venue_id, headliner))
+                .collect(),
+            warnings: Vec::new(),
+            error: None,
+            selector_warning: None,
+        };
+        let batch = || {
+            vec![
+                outcome("treefort", &["Alpha Band"]),
+                outcome("knitboise", &["Alpha Band", "Beta Band"]),
+            ]
+        };
+        let rules = config::CrossVenueDedup::default();
+        let store = Store::open_in_memory().expect("store");
+
+        let dry = save_batch(&store, batch(), &rules, true).expect("dry run");
+        let counts: Vec<usize> = dry.iter().map(|outcome| outcome.events.len()).collect();
+        assert_eq!(counts, vec![1, 1]);
+        assert_eq!(store.count_events().expect("count"), 0);
+        assert!(store.latest_scrape_runs().expect("runs").is_empty());
+
+        let saved = save_batch(&store, batch(), &rules, false).expect("real run");
+        let counts: Vec<usize> = saved.iter().map(|outcome| outcome.events.len()).collect();
+        assert_eq!(counts, vec![1, 1]);
+        assert_eq!(store.count_events().expect("count"), 2);
+        assert_eq!(store.latest_scrape_runs().expect("runs").len(), 2);
+    }
+
+    #[test]
+    fn compose_previews_falls_back_per_event() {
+        let server = MockServer::start(|request| {
+            if request.body.contains("Alpha Band") {
+                MockResponse::ok(chat_completion("Alpha Band preview"))
+            } else {
+                MockResponse::status(500, "model crashed")
+            }
+        });
+        let composer = Arc::new(LLMComposer::from_env().with_base_url(server.url()));
+        let alpha = pending_event("treefort", "Alpha Band");
+        let beta = pending_event("revolution", "Beta Band");
+        let expected_fallback = fallback_preview(&beta, composer.render_options());
+
+        let previews =
+            tauri::async_runtime::block_on(compose_previews(composer, vec![alpha, beta]))
+                .expect("compose previews");
+
+        assert_eq!(previews.len(), 2);
+        assert_eq!(previews[0].event_id, "treefort-Alpha Band");
+        assert_eq!(previews[0].preview, "Alpha Band preview");
+        assert_eq!(previews[1].event_id, "revolution-Beta Band");
+        assert_eq!(previews[1].preview, expected_fallback);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn styled_preview_sends_the_requested_style_and_falls_back() {
+        let server = MockServer::start(|_| MockResponse::ok(chat_completion("wry draft")));
+        let composer = LLMComposer::from_env().with_base_url(server.url());
+        let config = AppConfig::default();
+        let event = pending_event("treefort", "Alpha Band");
+
+        let text = tauri::async_runtime::block_on(compose_in_style(
+            &composer,
+            &config,
+            Some("dry and wry"),
+            &event,
+        ));
+        assert_eq!(text, "wry draft");
+        let requests = server.requests();
+        assert!(requests[0].body.contains("Style: dry and wry."));
+        assert!(requests[0]
+            .body
+            .contains("Format a short Facebook group post"));
+
+        let failing = MockServer::start(|_| MockResponse::status(500, "down"));
+        let composer = LLMComposer::from_env().with_base_url(failing.url());
+        let text = tauri::async_runtime::block_on(compose_in_style(
+            &composer,
+            &config,
+            Some("dry and wry"),
+            &event,
+        ));
+        assert_eq!(text, fallback(&event, &RenderOptions::from_config(&config)));
+    }
+
+    #[test]
+    fn blank_or_overlong_model_output_falls_back_to_the_template() {
+        let config = AppConfig::default();
+        let event = pending_event("treefort", "Alpha Band");
+        let expected = fallback(&event, &RenderOptions::from_config(&config));
+
+        // Emoji-only content is non-empty on the wire but blank once finished.
+        let blank = MockServer::start(|_| MockResponse::ok(chat_completion("🎸🎸")));
+        let composer = LLMComposer::from_env()
+            .with_base_url(blank.url())
+            .with_render_options(RenderOptions {
+                use_emoji: false,
+                ..RenderOptions::default()
+            });
+        let text =
+            tauri::async_runtime::block_on(compose_in_style(&composer, &config, None, &event));
+        assert_eq!(text, expected);
+
+        let huge = MockServer::start(|_| MockResponse::ok(chat_completion(&"la ".repeat(4000))));
+        let composer = LLMComposer::from_env().with_base_url(huge.url());
+        let text =
+            tauri::async_runtime::block_on(compose_in_style(&composer, &config, None, &event));
+        assert_eq!(text, expected);
+
+        let composer = LLMComposer::from_env()
+            .with_base_url(huge.url())
+            .with_max_composed_chars(20_000);
+        let text =
+            tauri::async_runtime::block_on(compose_in_style(&composer, &config, None, &event));
+        assert_eq!(text.chars().count(), 12_000 - 1);
+    }
+
+    #[test]
+    fn post_preview_uses_the_real_compose_path_and_event_image() {
+        let server = MockServer::start(|_| MockResponse::ok(chat_completion("real draft")));
+        let composer = LLMComposer::from_env().with_base_url(server.url());
+        let mut config = AppConfig::default();
+        config
+            .group_styles
+            .insert("rave-group".to_string(), "hype".to_string());
+        config
+            .group_styles
+            .insert("boise-page".to_string(), "concise".to_string());
+        let mut event = pending_event("treefort", "Alpha Band");
+        event.extra = json!({
+            "image_url": "https://treefort.example/flyers/alpha.jpg",
+            "musicbrainz": { "image": "https://commons.example/alpha.jpg" },
+        });
+
+        let preview = tauri::async_runtime::block_on(post_preview(&composer, &config, &event));
+        assert_eq!(preview.message, "real draft");
+        assert_eq!(
+            preview.image_url.as_deref(),
+            Some("https://treefort.example/flyers/alpha.jpg")
+        );
+        assert_eq!(preview.target_groups, vec!["boise-page", "rave-group"]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].body.contains("Facebook group"));
+
+        event.extra = json!({ "musicbrainz": { "image": "https://commons.example/alpha.jpg" } });
+        assert_eq!(
+            event_image(&event).as_deref(),
+            Some("https://commons.example/alpha.jpg")
+        );
+    }
+
+    #[test]
+    fn compose_for_group_uses_each_groups_style() {
+        let server = MockServer::start(|_| MockResponse::ok(chat_completion("draft")));
+        let composer = LLMComposer::from_env().with_base_url(server.url());
+        let mut config = AppConfig::default();
+        config
+            .group_styles
+            .insert("boise-page".to_string(), "concise".to_string());
+        config
+            .group_styles
+            .insert("rave-group".to_string(), "hype".to_string());
+        let event = pending_event("treefort", "Alpha Band");
+
+        for group in ["boise-page", "rave-group"] {
+            let text = tauri::async_runtime::block_on(compose_for_group(
+                &composer,
+                &config,
+                Some(group),
+                &event,
+            ));
+            assert_eq!(text, "draft");
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/chat/completions");
+        assert!(requests[0].body.contains("Style: concise."));
+        assert!(requests[1].body.contains("Style: hype."));
+    }
+
+    #[test]
+    fn venue_statuses_use_latest_run_per_venue() {
+        let store = Store::open_in_memory().expect("store");
+        store
+            .record_scrape_run("treefort", 12, None, None)
+            .expect("first treefort run");
+        store
+            .record_scrape_run("treefort", 0, Some("request failed"), None)
+            .expect("second treefort run");
+        store
+            .record_scrape_run("revolution", 7, None, None)
+            .expect("revolution run");
+
+        let scrapers = vec![
+            scraping::ScraperInfo {
+                id: "treefort".to_string(),
+                name: "Treefort Music Hall".to_string(),
+                url: "https://treefortmusichall.com/shows/".to_string(),
+                enabled: true,
+            },
+            scraping::ScraperInfo {
+                id: "revolution".to_string(),
+                name: "Revolution Concert House".to_string(),
+                url: "https://example.com/revolution".to_string(),
+                enabled: true,
+            },
+            scraping::ScraperInfo {
+                id: "knitboise".to_string(),
+                name: "Knitting Factory Boise".to_string(),
+                url: "https://bo.knittingfactory.com/".to_string(),
+                enabled: true,
+            },
+        ];
+
+        let statuses = venue_statuses(&store, scrapers).expect("statuses");
+        assert_eq!(statuses.len(), 3);
+
+        let treefort = &statuses[0];
+        assert_eq!(treefort.last_event_count, Some(0));
+        assert_eq!(treefort.last_error.as_deref(), Some("request failed"));
+        assert!(treefort.last_scraped_at.is_some());
+
+        let revolution = &statuses[1];
+        assert_eq!(revolution.last_event_count, Some(7));
+        assert_eq!(revolution.last_error, None);
+
+        let knitting = &statuses[2];
+        assert_eq!(knitting.last_scraped_at, None);
+        assert_eq!(knitting.last_event_count, None);
+    }
+
+    #[test]
+    fn venue_statuses_expose_selector_warnings() {
+        let store = Store::open_in_memory().expect("store");
+        store
+            .record_scrape_run(
+                "treefort",
+                1,
+                None,
+                Some("possible selector breakage: found 0 events, expected at least 10"),
+            )
+            .expect("treefort run");
+
+        let scrapers = vec![scraping::ScraperInfo {
+            id: "treefort".to_string(),
+            name: "Treefort Music Hall".to_string(),
+            url: "https://treefortmusichall.com/shows/".to_string(),
+            enabled: true,
+        }];
+        let statuses = venue_statuses(&store, scrapers).expect("statuses");
+        assert!(statuses[0]
+            .selector_warning
+            .as_deref()
+            .is_some_and(|warning| warning.starts_with("possible selector breakage")));
+        assert_eq!(statuses[0].last_error, None);
+    }
+
+    #[test]
+    fn load_event_returns_stored_event() {
+        let store = Store::open_in_memory().expect("store");
+        let event = pending_event("treefort", "Alpha Band");
+        store.upsert_event(&event).expect("upsert");
+
+        let loaded = load_event(&store, &event.id).expect("event found");
+        assert_eq!(loaded.id, event.id);
+        assert_eq!(loaded.artists, event.artists);
+        assert_eq!(loaded.venue_name, event.venue_name);
+    }
+
+    #[test]
+    fn event_extra_round_trips_with_a_flat_summary() {
+        let store = Store::open_in_memory().expect("store");
+        let mut event = pending_event("treefort", "Alpha Band");
+        event.extra = json!({
+            "raw_date": "Thu Oct 9",
+            "doors_text": "Doors 7pm",
+            "musicbrainz": { "genres": ["indie", "rock"], "score": 98 }
+        });
+        store.upsert_event(&event).expect("upsert");
+
+        let extra = event_extra(&store, &event.id).expect("extra");
+        assert_eq!(extra.extra, event.extra);
+        let lines: Vec<&str> = extra.summary.lines().collect();
+        assert!(lines.contains(&"raw_date: Thu Oct 9"));
+        assert!(lines.contains(&"musicbrainz.genres[1]: rock"));
+        assert!(lines.contains(&"musicbrainz.score: 98"));
+    }
+
+    #[test]
+    fn unmatched_artists_lists_negative_lookups_without_overrides() {
+        let store = Store::open_in_memory().expect("store");
+        let matched = musicbrainz::ArtistProfile {
+            id: "mbid-1".to_string(),
+            name: "PUP".to_string(),
+            disambiguation: None,
+            genres: vec!["punk".to_string()],
+        };
+        store
+            .put_musicbrainz_profile("pup", &Some(matched))
+            .expect("matched");
+        for key in ["zeta local", "alpha local", "overridden local"] {
+            store
+                .put_musicbrainz_profile(key, &None)
+                .expect("unmatched");
+        }
+        let mut overrides = musicbrainz::GenreOverrides::default();
+        overrides.set("Overridden Local", vec!["folk".to_string()]);
+
+        assert_eq!(
+            store.list_unmatched_artists().expect("list"),
+            vec!["alpha local", "overridden local", "zeta local"]
+        );
+        assert_eq!(
+            unmatched_artists(&store, &overrides).expect("worklist"),
+            vec!["alpha local", "zeta local"]
+        );
+    }
+
+    #[test]
+    fn lead_time_rejects_events_starting_too_soon() {
+        let now = Utc::now();
+        let soon = db::sample_event("treefort", "Treefort", now + chrono::Duration::hours(3));
+        let err = ensure_lead_time(&soon, now, 6).expect_err("inside the lead window");
+        assert!(err.contains("less than the 6 hour minimum lead"), "{err}");
+
+        let later = db::sample_event("treefort", "Treefort", now + chrono::Duration::hours(30));
+        assert!(ensure_lead_time(&later, now, 6).is_ok());
+        // A scheduled post is measured from its publish time, not from now.
+        assert!(ensure_lead_time(&later, now + chrono::Duration::hours(26), 6).is_err());
+    }
+
+    #[test]
+    fn retries_go_through_the_same_lead_check() {
+        let store = Store::open_in_memory().expect("store");
+        let soon = db::sample_event(
+            "treefort",
+            "Treefort",
+            Utc::now() + chrono::Duration::hours(3),
+        );
+        store.upsert_event(&soon).expect("upsert");
+
+        let err = ensure_postable(&store, &soon.id, Some(6)).expect_err("too soon");
+        assert!(err.contains("minimum lead"), "{err}");
+        assert!(ensure_postable(&store, &soon.id, None).is_ok());
+        assert!(ensure_postable(&store, "missing", None).is_err());
+    }
+
+    #[test]
+    fn buckets_follow_the_venues_calendar_day() {
+        let enrich = |event: Event| std::future::ready(Ok(event));
+        let bucket = |start_local: &str, start_utc: &str, now: DateTime<Utc>| {
+            let mut event = pending_event("treefort", "Late Show");
+            event.start_local = Some(start_local.to_string());
+            event.start_utc = start_utc.to_string();
+            let buckets =
+                tauri::async_runtime::block_on(bucket_pending(vec![event], true, enrich, now));
+            buckets
+                .into_iter()
+                .find_map(|(key, items)| items.first().map(|item| (key, item.days_until)))
+                .expect("bucketed")
+        };
+
+        // 11pm on the 9th in Boise is the 10th in UTC; at 10am local on the
+        // 9th it is still tonight's show.
+        let morning = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 10, 9, 16, 0, 0).unwrap();
+        assert_eq!(
+            bucket(
+                "2025-10-09T23:00:00-06:00",
+                "2025-10-10T05:00:00+00:00",
+                morning
+            ),
+            ("DAY_OF", 0)
+        );
+
+        // Late on the 8th locally, a show 22 hours away is still tomorrow.
+        let late_night = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 10, 9, 4, 0, 0).unwrap();
+        assert_eq!(
+            bucket(
+                "2025-10-09T20:00:00-06:00",
+                "2025-10-10T02:00:00+00:00",
+                late_night
+            ),
+            ("LT_1W", 1)
+        );
+    }
+
+    #[test]
+    fn date_only_shows_stay_pending_through_their_day() {
+        let enrich = |event: Event| std::future::ready(Ok(event));
+        let mut event = pending_event("treefort", "Open Decks");
+        event.start_local = Some("2025-10-09T00:00:00-06:00".to_string());
+        event.start_utc = "2025-10-09T06:00:00+00:00".to_string();
+        event.time_tba = true;
+        let bucketed = |now: DateTime<Utc>| {
+            tauri::async_runtime::block_on(bucket_pending(vec![event.clone()], true, enrich, now))
+                ["DAY_OF"]
+                .len()
+        };
+
+        let afternoon = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 10, 9, 21, 0, 0).unwrap();
+        assert_eq!(bucketed(afternoon), 1);
+        let next_day = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 10, 10, 7, 0, 0).unwrap();
+        assert_eq!(bucketed(next_day), 0);
+    }
+
+    #[test]
+    fn fast_buckets_skip_enrichment() {
+        let now = Utc::now();
+        let mut event = db::sample_event("venus", "Venus", now + chrono::Duration::days(3));
+        event.tags = vec!["stored".to_string()];
+        let lookups = std::sync::atomic::AtomicUsize::new(0);
+        let enrich = |mut event: Event| {
+            lookups.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            event.tags.push("looked up".to_string());
+            std::future::ready(Ok(event))
+        };
+
+        let fast =
+            tauri::async_runtime::block_on(bucket_pending(vec![event.clone()], true, enrich, now));
+        assert_eq!(lookups.load(std::sync::atomic::Ordering::SeqCst), 0);
+        assert_eq!(fast["LT_1W"][0].event.tags, vec!["stored"]);
+
+        let full = tauri::async_runtime::block_on(bucket_pending(vec![event], false, enrich, now));
+        assert_eq!(lookups.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(full["LT_1W"][0].event.tags, vec!["stored", "looked up"]);
+    }
+
+    #[test]
+    fn text_digest_lists_every_venue_by_local_date() {
+        let store = Store::open_in_memory().expect("store");
+        let now = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 10, 8, 18, 0, 0).unwrap();
+        let show = |venue_id: &str, venue: &str, headliner: &str, start_local: &str| {
+            let start = DateTime::parse_from_rfc3339(start_local).unwrap();
+            let mut event = db::sample_event(venue_id, venue, start.with_timezone(&Utc));
+            event.start_local = Some(start_local.to_string());
+            event.artists = vec![headliner.to_string()];
+            event.id = format!("{venue_id}|{headliner}");
+            event
+        };
+        let mut free = show(
+            "pine_box",
+            "Pine Box",
+            "Open Mic",
+            "2025-10-09T19:00:00-06:00",
+        );
+        free.ticket_url = None;
+        free.price_min_cents = Some(0);
+        free.price_max_cents = None;
+        let mut pup = show("treefort", "Treefort", "PUP", "2025-10-10T22:30:00-06:00");
+        pup.ticket_url = Some("https://tix.example.com/pup".to_string());
+        let mut tba = show(
+            "knitboise",
+            "Knitting Factory",
+            "Built to Spill",
+            "2025-10-12T20:00:00-06:00",
+        );
+        tba.ticket_url = None;
+        let past = show(
+            "treefort",
+            "Treefort",
+            "Last Week",
+            "2025-10-01T20:00:00-06:00",
+        );
+        let far = show(
+            "treefort",
+            "Treefort",
+            "Next Month",
+            "2025-11-20T20:00:00-07:00",
+        );
+        for event in [&tba, &far, &pup, &past, &free] {
+            store.upsert_event(event).expect("insert");
+        }
+
+        let events = store
+            .list_upcoming_events(now, Some(now + chrono::Duration::days(7)), false)
+            .expect("list");
+        assert_eq!(
+            render_text_digest(&events, 7),
+            "Thu Oct 9 — Open Mic @ Pine Box — tickets: free\n\
+             Fri Oct 10 — PUP @ Treefort — tickets: https://tix.example.com/pup\n\
+             Sun Oct 12 — Built to Spill @ Knitting Factory — tickets: TBA",
+            "PUP starts Saturday in UTC but is listed on the venue's Friday"
+        );
+        assert_eq!(
+            render_text_digest(&[], 7),
+            "No upcoming shows in the next 7 days."
+        );
+    }
+
+    #[test]
+    fn reenrich_looks_up_by_artist_and_stops_when_cancelled() {
+        let now = Utc::now();
+        let show = |id: &str, headliner: &str| {
+            let mut event = db::sample_event("venus", "Venus", now + chrono::Duration::days(3));
+            event.id = id.to_string();
+            event.artists = vec![headliner.to_string()];
+            event
+        };
+        let groups = vec![
+            vec![show("pup-1", "PUP"), show("pup-2", "pup")],
+            vec![show("bts", "Built to Spill")],
+            vec![show("dd", "Desert Dwellers")],
+        ];
+        let enriched_ids = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let enrich = {
+            let enriched_ids = Arc::clone(&enriched_ids);
+            move |event: Event| {
+                enriched_ids.lock().unwrap().push(event.id.clone());
+                std::future::ready(Ok(event))
+            }
+        };
+        let cancelled = AtomicBool::new(false);
+        let mut seen = Vec::new();
+
+        let enriched = tauri::async_runtime::block_on(reenrich_by_artist(
+            groups,
+            enrich,
+            1,
+            &cancelled,
+            |progress| {
+                seen.push(progress.clone());
+                if progress.done == 2 {
+                    cancelled.store(true, Ordering::SeqCst);
+                }
+            },
+        ));
+        assert_eq!(
+            *enriched_ids.lock().unwrap(),
+            vec!["pup-1", "pup-2", "bts"],
+            "both PUP shows go together and nothing runs after the cancel"
+        );
+        assert_eq!(enriched.len(), 3);
+        assert_eq!(
+            seen,
+            vec![
+                ReenrichProgress {
+                    done: 1,
+                    total: 3,
+                    artist: "PUP".to_string(),
+                },
+                ReenrichProgress {
+                    done: 2,
+                    total: 3,
+                    artist: "Built to Spill".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reenrich_runs_batches_of_artists_and_cancels_between_them() {
+        let now = Utc::now();
+        let show = |headliner: &str| {
+            let mut event = db::sample_event("venus", "Venus", now + chrono::Duration::days(3));
+            event.id = headliner.to_string();
+            event.artists = vec![headliner.to_string()];
+            event
+        };
+        let groups = ["PUP", "Built to Spill", "Desert Dwellers", "Low"]
+            .into_iter()
+            .map(|headliner| vec![show(headliner)])
+            .collect();
+        let cancelled = AtomicBool::new(false);
+        let mut seen = Vec::new();
+
+        let enriched = tauri::async_runtime::block_on(reenrich_by_artist(
+            groups,
+            |event: Event| std::future::ready(Ok(event)),
+            2,
+            &cancelled,
+            |progress| {
+                seen.push(progress.done);
+                cancelled.store(true, Ordering::SeqCst);
+            },
+        ));
+        let ids: Vec<_> = enriched.iter().map(|event| event.id.as_str()).collect();
+        assert_eq!(ids, vec!["PUP", "Built to Spill"]);
+        assert_eq!(
+            seen,
+            vec![1, 2],
+            "a started batch still reports each artist"
+        );
+    }
+
+    #[test]
+    fn only_one_reenrich_runs_at_a_time_and_cancels_do_not_outlive_it() {
+        let run = ReenrichRun::start().unwrap();
+        assert!(ReenrichRun::start().is_err());
+        assert!(tauri::async_runtime::block_on(cancel_reenrich()).unwrap());
+        assert!(run.0.load(Ordering::SeqCst));
+        drop(run);
+
+        assert!(!tauri::async_runtime::block_on(cancel_reenrich()).unwrap());
+        let next = ReenrichRun::start().unwrap();
+        assert!(!next.0.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn enrich_batch_keeps_only_events_that_gained_genres() {
+        let now = Utc::now();
+        let mut matched = db::sample_event("venus", "Venus", now + chrono::Duration::days(3));
+        matched.tags.clear();
+        matched.artists = vec!["Known".to_string()];
+        let mut unmatched = db::sample_event("fox", "Fox", now + chrono::Duration::days(4));
+        unmatched.tags.clear();
+        unmatched.artists = vec!["Nobody".to_string()];
+        let enrich = |mut event: Event| {
+            if event.artists[0] == "Known" {
+                event.tags.push("punk".to_string());
+            }
+            std::future::ready(Ok(event))
+        };
+
+        let tagged = tauri::async_runtime::block_on(enrich_missing_genres(
+            vec![matched.clone(), unmatched],
+            enrich,
+        ));
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].id, matched.id);
+        assert_eq!(tagged[0].tags, vec!["punk"]);
+    }
+
+    #[test]
+    fn purge_respects_the_retention_window() {
+        let now = Utc::now();
+        let store = Store::open_in_memory().expect("store");
+        let old = db::sample_event("venus", "Venus", now - chrono::Duration::days(10));
+        let recent = db::sample_event("venus", "Venus", now - chrono::Duration::days(2));
+        let upcoming = db::sample_event("venus", "Venus", now + chrono::Duration::days(3));
+        for event in [&old, &recent, &upcoming] {
+            store.upsert_event(event).expect("insert");
+        }
+
+        store
+            .record_post(&old.id, Some("boise-ravers"), None, "manual", &json!({}))
+            .expect("post");
+
+        assert_eq!(purge_expired(&store, 0, now), Ok(0));
+        assert_eq!(store.last_purge_run().expect("last purge"), None);
+        assert_eq!(purge_expired(&store, 5, now), Ok(1));
+        assert!(store.get_event(&old.id).is_err());
+        assert!(store.get_event(&recent.id).is_ok());
+        assert!(store.get_event(&upcoming.id).is_ok());
+        assert_eq!(
+            store.posts_for_event(&old.id).expect("post log").len(),
+            1,
+            "the post log outlives the event"
+        );
+
+        assert_eq!(purge_expired(&store, 1, now), Ok(1));
+        assert_eq!(store.count_events().expect("count"), 1);
+        let last = store
+            .last_purge_run()
+            .expect("last purge")
+            .expect("recorded");
+        assert_eq!(last.removed, 1);
+    }
+
+    #[test]
+    fn self_test_runs_every_stage_in_mock_mode() {
+        assert!(tauri::async_runtime::block_on(self_test_in_mode(false)).is_err());
+        let stages = tauri::async_runtime::block_on(self_test_in_mode(true)).expect("self test");
+
+        let names: Vec<&str> = stages.iter().map(|stage| stage.stage).collect();
+        assert_eq!(
+            names,
+            vec!["scrape", "persist", "enrich", "compose", "post"]
+        );
+        for stage in &stages {
+            assert!(stage.ok, "{} failed: {}", stage.stage, stage.detail);
+        }
+    }
+
+    #[test]
+    fn timing_counts_days_in_the_venue_offset() {
+        let mut event = pending_event("treefort", "Alpha Band");
+        event.start_local = Some("2025-10-10T20:00:00-06:00".to_string());
+        event.start_utc = "2025-10-11T02:00:00+00:00".to_string();
+
+        // 05:00 UTC on the 9th is still the evening of the 8th in Boise.
+        let now = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 10, 9, 5, 0, 0).unwrap();
+        let timing = timing_for(&event, now).expect("timing");
+        assert_eq!(timing.days_until, 2);
+        assert_eq!(timing.hours_until, 45);
+        assert_eq!(timing.local_time_str, "Fri, Oct 10 · 8:00 PM");
+        assert!(!timing.is_past);
+    }
+
+    #[test]
+    fn timing_on_the_day_of_the_show() {
+        let mut event = pending_event("treefort", "Alpha Band");
+        event.start_local = Some("2025-10-10T20:00:00-06:00".to_string());
+        event.start_utc = "2025-10-11T02:00:00+00:00".to_string();
+
+        let now = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 10, 10, 15, 30, 0).unwrap();
+        let timing = timing_for(&event, now).expect("timing");
+        assert_eq!(timing.days_until, 0);
+        assert_eq!(timing.hours_until, 10);
+        assert!(!timing.is_past);
+    }
+
+    #[test]
+    fn timing_for_a_past_show() {
+        let mut event = pending_event("treefort", "Alpha Band");
+        event.start_local = None;
+        event.start_utc = "2025-10-11T02:00:00+00:00".to_string();
+
+        let now = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 10, 13, 2, 0, 0).unwrap();
+        let timing = timing_for(&event, now).expect("timing");
+        assert_eq!(timing.days_until, -2);
+        assert_eq!(timing.hours_until, -48);
+        assert_eq!(timing.local_time_str, "Sat, Oct 11 · 2:00 AM");
+        assert!(timing.is_past);
+    }
+
+    #[test]
+    fn load_event_reports_unknown_id_as_not_found() {
+        let store = Store::open_in_memory().expect("store");
+        let err = load_event(&store, "missing-id").expect_err("unknown id");
+        assert_eq!(err.code(), "event_not_found");
+        assert_eq!(err.to_string(), "event not found: missing-id");
+    }
+
+    #[test]
+    fn single_event_calendar_holds_just_that_event() {
+        let store = Store::open_in_memory().expect("store");
+        let start = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 10, 9, 2, 0, 0).unwrap();
+        let event = db::sample_event("treefort", "Treefort Music Hall", start);
+        store.upsert_event(&event).expect("upsert");
+
+        let calendar = event_calendar(&store, &event.id).expect("calendar");
+        let lines: Vec<&str> = calendar.split("\r\n").collect();
+        assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+        assert_eq!(lines.iter().filter(|l| **l == "BEGIN:VEVENT").count(), 1);
+        assert!(lines.contains(&format!("UID:{}@show-scraper", event.id).as_str()));
+        assert!(lines.contains(&"DTSTART:20251009T020000Z"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+
+        let err = event_calendar(&store, "missing-id").expect_err("unknown id");
+        assert_eq!(err.code(), "event_not_found");
+    }
+
+    #[test]
+    fn scrape_outcome_separates_empty_partial_and_full_runs() {
+        assert_eq!(
+            ScrapeOutcome::new(0, Vec::new(), Vec::new()),
+            ScrapeOutcome::NoEvents {
+                warnings: Vec::new()
+            }
+        );
+        assert_eq!(
+            ScrapeOutcome::new(12, Vec::new(), Vec::new()),
+            ScrapeOutcome::Succeeded {
+                count: 12,
+                warnings: Vec::new()
+            }
+        );
+        let partial = ScrapeOutcome::new(4, vec!["revolution".to_string()], Vec::new());
+        assert_eq!(partial.count(), 4);
+        assert_eq!(
+            serde_json::to_value(&partial).expect("json"),
+            json!({"status": "venues_failed", "count": 4, "failed": ["revolution"]})
+        );
+        assert_eq!(
+            serde_json::to_value(ScrapeOutcome::new(0, Vec::new(), Vec::new())).expect("json"),
+            json!({"status": "no_events"})
+        );
+    }
+
+    #[test]
+    fn scrape_outcomes_carry_venue_warnings() {
+        let selector_warning = Some("card selector matched nothing".to_string());
+        let skipped = ["skipped card 3: missing date".to_string()];
+        let warnings = venue_warnings("treefort", skipped.iter().chain(&selector_warning));
+        let outcome = ScrapeOutcome::new(2, Vec::new(), warnings);
+        assert_eq!(
+            serde_json::to_value(&outcome).expect("json"),
+            json!({
+                "status": "succeeded",
+                "count": 2,
+                "warnings": [
+                    "treefort: skipped card 3: missing date",
+                    "treefort: card selector matched nothing"
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn near_identical_events_form_a_duplicate_group() {
+        let start = Utc::now() + chrono::Duration::days(5);
+        let mut listed = db::sample_event("treefort", "Treefort", start);
+        listed.artists = vec!["Desert Dwellers".to_string()];
+        let mut relisted = db::sample_event(
+            "knitboise",
+            "Knitting Factory",
+            start + chrono::Duration::minutes(10),
+        );
+        relisted.artists = vec!["DESERT DWELLERS (Live)".to_string()];
+        let mut other = db::sample_event("venus", "Venus", start);
+        other.artists = vec!["PUP".to_string()];
+
+        let groups = duplicate_groups(
+            vec![other, relisted.clone(), listed.clone()],
+            &config::CrossVenueDedup::default(),
+        );
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<&str> = groups[0].iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec![listed.id.as_str(), relisted.id.as_str()]);
+        assert_eq!(groups[0][1].headliner, "DESERT DWELLERS (Live)");
+    }
+
+    #[test]
+    fn merging_events_removes_the_dropped_row_and_its_posts() {
+        let store = Store::open_in_memory().expect("store");
+        let mut keep = pending_event("treefort", "Alpha Band");
+        keep.ticket_url = None;
+        keep.tags = vec!["Rock".to_string()];
+        let mut drop = pending_event("knitboise", "Alpha Band");
+        drop.ticket_url = Some("https://tickets.example.com/drop".to_string());
+        drop.tags = vec!["Indie".to_string()];
+        store.upsert_event(&keep).expect("upsert keep");
+        store.upsert_event(&drop).expect("upsert drop");
+        store
+            .record_post(&drop.id, None, None, "draft", &json!({}))
+            .expect("drop post");
+
+        let merged = merge_event_rows(&store, &keep.id, &drop.id, false).expect("merge");
+        assert_eq!(merged.id, keep.id);
+        assert_eq!(
+            merged.ticket_url.as_deref(),
+            Some("https://tickets.example.com/drop")
+        );
+        assert_eq!(merged.tags, vec!["Rock", "Indie"]);
+
+        let stored = load_event(&store, &keep.id).expect("survivor");
+        assert_eq!(stored.tags, merged.tags);
+        assert_eq!(
+            load_event(&store, &drop.id)
+                .expect_err("dropped row")
+                .to_string(),
+            format!("event not found: {}", drop.id)
+        );
+        assert_eq!(store.count_posts(&drop.id, "draft").expect("count"), 0);
+    }
+
+    #[test]
+    fn merging_a_posted_event_into_a_pending_one_needs_confirmation() {
+        let store = Store::open_in_memory().expect("store");
+        let keep = pending_event("treefort", "Alpha Band");
+        let drop = pending_event("knitboise", "Alpha Band");
+        store.upsert_event(&keep).expect("upsert keep");
+        store.upsert_event(&drop).expect("upsert drop");
+        store.mark_posted(&drop.id).expect("mark posted");
+
+        let err = merge_event_rows(&store, &keep.id, &drop.id, false).expect_err("guarded");
+        assert!(err.contains("already posted"), "{err}");
+        assert!(load_event(&store, &drop.id).is_ok());
+
+        merge_event_rows(&store, &keep.id, &drop.id, true).expect("confirmed merge");
+        assert!(store.posted_at(&keep.id).expect("lookup").is_some());
+    }
+
+    #[test]
+    fn merged_duplicates_stay_merged_after_a_rescrape() {
+        let store = Store::open_in_memory().expect("store");
+        let keep = pending_event("treefort", "Alpha Band");
+        let drop = pending_event("knitboise", "Alpha Band");
+        store.upsert_event(&keep).expect("upsert keep");
+        store.upsert_event(&drop).expect("upsert drop");
+        merge_event_rows(&store, &keep.id, &drop.id, false).expect("merge");
+
+        store.upsert_event(&keep).expect("rescrape keep");
+        store.upsert_event(&drop).expect("rescrape drop");
+        assert_eq!(store.count_events().expect("count"), 1);
+        assert!(load_event(&store, &drop.id).is_err());
+        assert_eq!(
+            load_event(&store, &keep.id).expect("survivor").venue_id,
+            "treefort"
+        );
+    }
+
+    #[test]
+    fn custom_message_is_recorded_and_event_marked_posted() {
+        let store = Store::open_in_memory().expect("store");
+        let event = pending_event("treefort", "Alpha Band");
+        store.upsert_event(&event).expect("upsert");
+        let message = "Alpha Band tonight at Treefort! Doors 7pm.";
+
+        let post_id =
+            record_custom_message(&store, &event.id, None, message).expect("record custom message");
+
+        let post = store.get_post(&post_id).expect("post row");
+        assert_eq!(post.post_id, post_id);
+        assert_eq!(post.event_id, event.id);
+        assert_eq!(post.fb_object_id, None);
+        assert!(post.created_at_utc.is_some());
+        assert_eq!(post.status.as_deref(), Some("manual"));
+        let response: serde_json::Value =
+            serde_json::from_str(post.response_json.as_deref().expect("response json"))
+                .expect("parse response json");
+        assert_eq!(response["message"], message);
+        assert!(store.list_pending_events().expect("pending").is_empty());
+    }
+
+    #[test]
+    fn custom_message_goes_to_each_group_once() {
+        let store = Store::open_in_memory().expect("store");
+        let event = pending_event("treefort", "Alpha Band");
+        store.upsert_event(&event).expect("upsert");
+
+        record_custom_message(
+            &store,
+            &event.id,
+            Some("boise-ravers"),
+            "Alpha Band tonight!",
+        )
+        .expect("first group");
+        record_custom_message(
+            &store,
+            &event.id,
+            Some("treasure-valley"),
+            "Alpha Band tonight!",
+        )
+        .expect("second group");
+        assert!(record_custom_message(&store, &event.id, Some("boise-ravers"), "Again!").is_err());
+        assert!(record_custom_message(&store, &event.id, None, "Again!").is_err());
+
+        let groups: Vec<Option<String>> = store
+            .posts_for_event(&event.id)
+            .expect("history")
+            .into_iter()
+            .map(|post| post.group_id)
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                Some("boise-ravers".to_string()),
+                Some("treasure-valley".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_with_a_scheduled_post_are_not_new_destinations() {
+        let store = Store::open_in_memory().expect("store");
+        let event = pending_event("treefort", "Alpha Band");
+        store.upsert_event(&event).expect("upsert");
+        record_custom_message(&store, &event.id, Some("boise-ravers"), "Tonight!")
+            .expect("first group");
+        record_scheduled_post(
+            &store,
+            &event.id,
+            Some("treasure-valley"),
+            "Tonight!",
+            Utc::now() + chrono::Duration::hours(2),
+        )
+        .expect("scheduled");
+
+        assert_eq!(
+            new_destination(&store, &event.id, Some("treasure-valley")),
+            Ok(false)
+        );
+        assert_eq!(new_destination(&store, &event.id, Some("other")), Ok(true));
+        assert!(
+            record_custom_message(&store, &event.id, Some("treasure-valley"), "Again!").is_err()
+        );
+    }
+
+    #[test]
+    fn multi_group_posting_records_successes_past_a_failure() {
+        let store = Store::open_in_memory().expect("store");
+        let event = pending_event("treefort", "Alpha Band");
+        store.upsert_event(&event).expect("upsert");
+        record_custom_message(&store, &event.id, Some("boise-ravers"), "Earlier post")
+            .expect("earlier post");
+        store.unmark_posted(&event.id).expect("reset");
+
+        let groups = ["treasure-valley", "boise-ravers", "idaho-edm"].map(str::to_string);
+        let results = record_to_groups(&store, &event.id, &groups, "Alpha Band tonight!");
+        let outcomes: Vec<(&str, bool)> = results
+            .iter()
+            .map(|r| (r.group_id.as_str(), r.post_id.is_some()))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("treasure-valley", true),
+                ("boise-ravers", false),
+                ("idaho-edm", true)
+            ]
+        );
+        assert!(results[1]
+            .error
+            .as_deref()
+            .is_some_and(|e| e.contains("already posted to boise-ravers")));
+        assert!(store.posted_at(&event.id).expect("lookup").is_some());
+
+        let retried = record_to_group(&store, &event.id, "meridian-shows", "Alpha Band tonight!");
+        assert!(retried.post_id.is_some(), "{:?}", retried.error);
+        let empty = record_to_group(&store, &event.id, "  ", "Alpha Band tonight!");
+        assert_eq!(empty.error.as_deref(), Some("group id is empty"));
+        assert_eq!(store.posts_for_event(&event.id).expect("history").len(), 4);
+    }
+
+    #[test]
+    fn custom_message_rejects_empty_and_oversized_text() {
+        let store = Store::open_in_memory().expect("store");
+        let event = pending_event("treefort", "Alpha Band");
+        store.upsert_event(&event).expect("upsert");
+
+        assert!(record_custom_message(&store, &event.id, None, "   ").is_err());
+        let oversized = "x".repeat(llm::MAX_POST_CHARS + 1);
+        assert!(record_custom_message(&store, &event.id, None, &oversized).is_err());
+        assert_eq!(store.list_pending_events().expect("pending").len(), 1);
+    }
+
+    #[test]
+    fn posted_event_can_be_returned_to_pending() {
+        let store = Store::open_in_memory().expect("store");
+        let event = pending_event("treefort", "Alpha Band");
+        store.upsert_event(&event).expect("upsert");
+        store.mark_posted(&event.id).expect("mark posted");
+        assert!(store.list_pending_events().expect("pending").is_empty());
+
+        let undo_id = undo_post(&store, &event.id).expect("undo post");
+
+        let pending = store.list_pending_events().expect("pending");
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].event.id, event.id);
+        let undo = store.get_post(&undo_id).expect("undo row");
+        assert_eq!(undo.status.as_deref(), Some("unposted"));
+
+        let err = undo_post(&store, &event.id).expect_err("already pending");
+        assert!(err.contains("not posted"));
+    }
+
+    #[test]
+    fn publish_time_must_fall_inside_facebooks_window() {
+        let now = Utc::now();
+        let at = |offset: chrono::Duration| (now + offset).to_rfc3339();
+
+        let earliest = chrono::Duration::minutes(10);
+        let latest = chrono::Duration::days(75);
+        let second = chrono::Duration::seconds(1);
+
+        assert!(validate_publish_at(&at(earliest), now).is_ok());
+        assert!(validate_publish_at(&at(latest), now).is_ok());
+        assert!(validate_publish_at(&at(earliest - second), now)
+            .expect_err("too soon")
+            .contains("at least 10 minutes"));
+        assert!(validate_publish_at(&at(latest + second), now)
+            .expect_err("too far out")
+            .contains("within 75 days"));
+        assert!(validate_publish_at("next friday", now)
+            .expect_err("not rfc3339")
+            .contains("invalid publish time"));
+    }
+
+    #[test]
+    fn scheduled_post_records_unpublished_payload() {
+        let store = Store::open_in_memory().expect("store");
+        let event = pending_event("treefort", "Alpha Band");
+        store.upsert_event(&event).expect("upsert");
+        let publish_at = Utc::now() + chrono::Duration::days(2);
+
+        let post_id = record_scheduled_post(
+            &store,
+            &event.id,
+            Some("boise-ravers"),
+            "Alpha Band this weekend",
+            publish_at,
+        )
+        .expect("record scheduled post");
+
+        let post = store.get_post(&post_id).expect("post row");
+        assert_eq!(post.status.as_deref(), Some("scheduled"));
+        let payload: serde_json::Value =
+            serde_json::from_str(post.response_json.as_deref().expect("payload"))
+                .expect("parse payload");
+        assert_eq!(
+            payload,
+            json!({
+                "message": "Alpha Band this weekend",
+                "group_id": "boise-ravers",
+                "published": false,
+                "scheduled_publish_time": publish_at.timestamp(),
+                "publish_at": publish_at.to_rfc3339(),
+            })
+        );
+        assert_eq!(store.list_pending_events().expect("pending").len(), 1);
+    }
+
+    #[test]
+    fn concurrent_custom_messages_post_once() {
+        let dir = std::env::temp_dir().join(format!("show-scrape-claim-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let path = dir.join("events.db");
+        let event = pending_event("treefort", "Alpha Band");
+        Store::open_at(&path)
+            .expect("store")
+            .upsert_event(&event)
+            .expect("upsert");
+
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let barrier = Arc::clone(&barrier);
+                let path = path.clone();
+                let event_id = event.id.clone();
+                std::thread::spawn(move || {
+                    let store = Store::open_at(&path).expect("store");
+                    barrier.wait();
+                    record_custom_message(&store, &event_id, None, "Alpha Band tonight!")
+                })
+            })
+            .collect();
+        let results: Vec<Result<String, String>> = handles
+            .into_iter()
+            .map(|handle| handle.join().expect("thread"))
+            .collect();
+
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+        let rejected = results
+            .iter()
+            .find_map(|r| r.as_ref().err())
+            .expect("one rejection");
+        assert!(rejected.contains("already posted"), "{rejected}");
+        let store = Store::open_at(&path).expect("store");
+        assert_eq!(store.count_posts(&event.id, "manual").expect("count"), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn health_report_serializes_per_subsystem_status() {
+        let report = HealthReport {
+            database: SubsystemHealth::ok("3 events stored"),
+            config: SubsystemHealth::ok("loaded"),
+            facebook: SubsystemHealth::ok("manual"),
+            llm: SubsystemHealth::failed("connection refused"),
+            musicbrainz: SubsystemHealth::ok("reachable"),
+        };
+        let value = serde_json::to_value(&report).expect("serialize");
+        assert_eq!(
+            value["llm"],
+            json!({ "ok": false, "detail": "connection refused" })
+        );
+        assert_eq!(
+            value["database"],
+            json!({ "ok": true, "detail": "3 events stored" })
+        );
+        let keys: Vec<&String> = value.as_object().expect("object").keys().collect();
+        assert_eq!(
+            keys,
+            vec!["config", "database", "facebook", "llm", "musicbrainz"]
+        );
+    }
+
+    #[test]
+    fn database_and_config_health_reflect_local_state() {
+        let store = Store::open_in_memory().expect("store");
+        store
+            .upsert_event(&pending_event("treefort", "Alpha Band"))
+            .expect("upsert");
+        let database = database_health(&store);
+        assert!(database.ok);
+        assert_eq!(database.detail, "1 events stored");
+
+        assert!(config_health(&Ok(AppConfig::default())).ok);
+
+        let path = std::env::temp_dir().join(format!(
+            "show-scrape-bad-config-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, "{ not json").expect("write config");
+        let broken = config_health(&AppConfig::load_from(&path));
+        assert!(!broken.ok);
+        assert!(broken.detail.contains("config parse error"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
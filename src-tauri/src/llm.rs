@@ -1,22 +1,216 @@
-use chrono::{DateTime, Local};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error;
+use tokio::sync::Semaphore;
 
-use crate::models::Event;
+use crate::config::AppConfig;
+use crate::models::{Event, TitleMode};
 
 #[allow(dead_code)]
 #[derive(Debug, Error)]
 pub enum ComposeError {
     #[error("composer unavailable: {0}")]
     Unavailable(String),
+    /// The model answered, but with nothing usable for a single post.
+    #[error("unusable composed post: {0}")]
+    Unusable(String),
 }
 
 const DEFAULT_ENDPOINT: &str = "http://127.0.0.1:1234/v1";
 const DEFAULT_MODEL: &str = "gpt-4o-mini";
 const DEFAULT_TEMPERATURE: f32 = 0.2;
+/// Variations are sampled at least this warm so they don't all come back
+/// word for word.
+const VARIATION_MIN_TEMPERATURE: f32 = 0.9;
+/// Most variations one call may ask for; each can cost a model request.
+pub const MAX_VARIATIONS: usize = 5;
 const DEFAULT_MAX_TOKENS: u32 = 5000;
+/// Composed single-event posts longer than this are replaced by the template
+/// draft unless the config says otherwise.
+pub const DEFAULT_MAX_COMPOSED_CHARS: usize = 5000;
 const DEFAULT_STYLE: &str = "concise";
+/// Requests to the model allowed in flight at once unless configured otherwise.
+pub(crate) const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 2;
+/// strftime pattern for the show time in fallback posts.
+pub(crate) const DEFAULT_POST_DATE_FORMAT: &str = "%A, %B %e at %l:%M %p";
+/// Event fields sent to the model unless configured otherwise. `venue_url`
+/// is also available.
+pub(crate) const DEFAULT_PAYLOAD_FIELDS: &[&str] = &[
+    "artists",
+    "billed_as",
+    "lineup",
+    "venue_name",
+    "age_restriction",
+    "start_local",
+    "start_utc",
+    "start_time",
+    "ticket_url",
+    "event_url",
+    "price_min_cents",
+    "price_max_cents",
+    "currency",
+    "tags",
+    "extra",
+];
+const PREVIEW_DATE_FORMAT: &str = "%a %b %e @ %l:%M %p";
+/// Facebook rejects posts longer than this many characters.
+pub const MAX_POST_CHARS: usize = 63_206;
+
+/// Text flavor produced by the composer. Facebook ignores markdown, so
+/// anything that gets posted stays `Plain`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Plain,
+    Markdown,
+}
+
+/// Output settings shared by the LLM prompt and the deterministic renderers.
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub use_emoji: bool,
+    pub format: OutputFormat,
+    pub title_mode: TitleMode,
+    /// strftime pattern for the show time in posts, applied in the venue's
+    /// own offset.
+    pub date_format: String,
+    /// Query parameters merged into ticket and event links.
+    pub link_suffix: Option<String>,
+    /// Event fields included in the JSON sent to the model.
+    pub payload_fields: Vec<String>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            use_emoji: true,
+            format: OutputFormat::Plain,
+            title_mode: TitleMode::default(),
+            date_format: DEFAULT_POST_DATE_FORMAT.to_string(),
+            link_suffix: None,
+            payload_fields: DEFAULT_PAYLOAD_FIELDS
+                .iter()
+                .map(|field| field.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl RenderOptions {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            use_emoji: config.use_emoji,
+            title_mode: config.title_mode,
+            date_format: config.post_date_format.clone(),
+            link_suffix: config
+                .ticket_link_suffix
+                .as_deref()
+                .map(|suffix| suffix.trim().trim_start_matches(['?', '&']))
+                .filter(|suffix| !suffix.is_empty())
+                .map(str::to_string),
+            payload_fields: config.llm_payload_fields.clone(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// `url` with the configured suffix merged into its query string, keeping
+    /// any fragment at the end.
+    pub fn link(&self, url: &str) -> String {
+        let Some(suffix) = &self.link_suffix else {
+            return url.to_string();
+        };
+        let (base, fragment) = match url.split_once('#') {
+            Some((base, fragment)) => (base, Some(fragment)),
+            None => (url, None),
+        };
+        let separator = if !base.contains('?') {
+            "?"
+        } else if base.ends_with(['?', '&']) {
+            ""
+        } else {
+            "&"
+        };
+        let mut linked = format!("{base}{separator}{suffix}");
+        if let Some(fragment) = fragment {
+            linked.push('#');
+            linked.push_str(fragment);
+        }
+        linked
+    }
+
+    fn finish(&self, text: &str) -> String {
+        let text = match self.format {
+            OutputFormat::Plain => markdown_to_plain(text),
+            OutputFormat::Markdown => text.to_string(),
+        };
+        if self.use_emoji {
+            text
+        } else {
+            strip_emoji(&text)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Prompt {
+    pub system: String,
+    pub user: String,
+}
+
+/// The one limiter every composer draws from, whichever command built it.
+static REQUEST_LIMITER: Lazy<Arc<RequestLimiter>> =
+    Lazy::new(|| Arc::new(RequestLimiter::new(DEFAULT_MAX_CONCURRENT_REQUESTS)));
+
+/// Caps in-flight model requests. The cap can change while requests are
+/// running; see `resize`.
+struct RequestLimiter {
+    permits: Arc<Semaphore>,
+    limit: Mutex<usize>,
+}
+
+impl RequestLimiter {
+    fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            permits: Arc::new(Semaphore::new(limit)),
+            limit: Mutex::new(limit),
+        }
+    }
+
+    /// Sets the cap. Shrinking retires idle permits now and busy ones as their
+    /// requests finish, so the old cap is never exceeded in the meantime.
+    fn resize(&self, limit: usize) {
+        let limit = limit.max(1);
+        let mut current = self.limit.lock().expect("llm limiter poisoned");
+        if limit > *current {
+            self.permits.add_permits(limit - *current);
+        } else if limit < *current {
+            let excess = *current - limit;
+            let busy = excess - self.permits.forget_permits(excess);
+            if busy > 0 {
+                let permits = Arc::clone(&self.permits);
+                tauri::async_runtime::spawn(async move {
+                    if let Ok(retired) = permits.acquire_many_owned(busy as u32).await {
+                        retired.forget();
+                    }
+                });
+            }
+        }
+        *current = limit;
+    }
+}
 
 pub struct LLMComposer {
     model: String,
@@ -25,6 +219,10 @@ pub struct LLMComposer {
     temperature: f32,
     max_tokens: u32,
     style: String,
+    render: RenderOptions,
+    system_prompt: Option<String>,
+    max_composed_chars: usize,
+    limiter: Arc<RequestLimiter>,
     client: Client,
 }
 
@@ -51,60 +249,403 @@ impl LLMComposer {
             temperature,
             max_tokens,
             style,
+            render: RenderOptions::default(),
+            system_prompt: None,
+            max_composed_chars: DEFAULT_MAX_COMPOSED_CHARS,
+            limiter: Arc::clone(&REQUEST_LIMITER),
             client: Client::new(),
         }
     }
 
+    /// `from_env` with the user's render settings and system prompt applied.
+    /// Also brings the shared request cap in line with `llm_max_concurrency`.
+    pub fn from_config(config: &AppConfig) -> Self {
+        REQUEST_LIMITER.resize(config.llm_max_concurrency);
+        Self::from_env()
+            .with_render_options(RenderOptions::from_config(config))
+            .with_system_prompt(config.system_prompt())
+            .with_max_composed_chars(config.max_composed_chars)
+    }
+
+    /// Longest single-event post accepted from the model, never more than
+    /// Facebook's own limit.
+    pub fn with_max_composed_chars(mut self, max_chars: usize) -> Self {
+        self.max_composed_chars = max_chars.clamp(1, MAX_POST_CHARS);
+        self
+    }
+
+    /// Overrides the system message for real posts; blank values are ignored.
+    pub fn with_system_prompt(mut self, prompt: Option<&str>) -> Self {
+        self.system_prompt = prompt
+            .map(str::trim)
+            .filter(|prompt| !prompt.is_empty())
+            .map(str::to_string);
+        self
+    }
+
+    pub fn with_render_options(mut self, render: RenderOptions) -> Self {
+        self.render = render;
+        self
+    }
+
+    pub fn render_options(&self) -> &RenderOptions {
+        &self.render
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.to_string();
+        self.api_key = None;
+        self
+    }
+
     pub async fn compose_preview(&self, event: &Event) -> Result<String, ComposeError> {
-        self.compose_internal(event, true).await
+        self.compose_internal(event, true, &self.style).await
     }
 
     pub async fn compose(&self, event: &Event) -> Result<String, ComposeError> {
-        self.compose_internal(event, false).await
+        self.compose_internal(event, false, &self.style).await
+    }
+
+    /// Up to `n` distinct real posts for one event, `n` clamped to
+    /// `1..=MAX_VARIATIONS`. All are asked for in one request; when the
+    /// endpoint ignores `n`, it is asked again one at a time, at most `n`
+    /// requests in all. Duplicates and posts over the length limit are
+    /// dropped. Fails only when the first request does; a later failure
+    /// keeps the drafts so far and is returned in `Variations::stopped`.
+    pub async fn compose_variations(
+        &self,
+        event: &Event,
+        n: usize,
+    ) -> Result<Variations, ComposeError> {
+        let n = n.clamp(1, MAX_VARIATIONS);
+        let prompt = self.prompt(event, false);
+        let temperature = self.temperature.max(VARIATION_MIN_TEMPERATURE);
+        let mut variations: Vec<String> = Vec::with_capacity(n);
+        let keep = |choices: Vec<String>, variations: &mut Vec<String>| {
+            for text in choices {
+                if variations.len() < n
+                    && self.check_usable(&text).is_ok()
+                    && !variations.contains(&text)
+                {
+                    variations.push(text);
+                }
+            }
+        };
+        keep(
+            self.request_choices(&prompt, n, temperature).await?,
+            &mut variations,
+        );
+        for _ in 1..n {
+            if variations.len() >= n {
+                break;
+            }
+            match self.request_choices(&prompt, 1, temperature).await {
+                Ok(choices) => keep(choices, &mut variations),
+                Err(err) => {
+                    return Ok(Variations {
+                        drafts: variations,
+                        stopped: Some(err),
+                    })
+                }
+            }
+        }
+        Ok(Variations {
+            drafts: variations,
+            stopped: None,
+        })
+    }
+
+    /// Composes a post using `style` in place of the configured default.
+    pub async fn compose_with_style(
+        &self,
+        event: &Event,
+        style: &str,
+    ) -> Result<String, ComposeError> {
+        self.compose_internal(event, false, style).await
+    }
+}
+
+/// Drafts from `LLMComposer::compose_variations`.
+#[derive(Debug)]
+pub struct Variations {
+    pub drafts: Vec<String>,
+    /// Why fewer than `n` came back, when a follow-up request failed.
+    pub stopped: Option<ComposeError>,
+}
+
+pub fn fallback(event: &Event, options: &RenderOptions) -> String {
+    render_post(event, options)
+}
+
+pub fn fallback_preview(event: &Event, options: &RenderOptions) -> String {
+    render_preview(event, options)
+}
+
+/// Deterministic digest: an intro line, then one line per event.
+pub fn fallback_roundup(events: &[Event], options: &RenderOptions) -> String {
+    let markdown = options.format == OutputFormat::Markdown;
+    let mut lines = vec!["Upcoming shows:".to_string()];
+    for event in events {
+        let title = event.title_with_support(options.title_mode);
+        let mut parts = vec![
+            if markdown {
+                format!("**{title}**")
+            } else {
+                title
+            },
+            event
+                .venue_name
+                .clone()
+                .unwrap_or_else(|| "Unknown Venue".to_string()),
+            short_when(event).unwrap_or_else(|| event.start_utc.clone()),
+        ];
+        if let Some(ticket) = &event.ticket_url {
+            parts.push(options.link(ticket));
+        } else if event.is_free() {
+            parts.push("Free".to_string());
+        }
+        lines.push(format!("- {}", parts.join(" | ")));
+    }
+    options.finish(&lines.join("\n"))
+}
+
+/// Breaks `text` into messages of at most `max_chars`, splitting between
+/// lines where possible and mid-line only when a single line is too long.
+pub fn split_post(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut messages = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        let mut line: Vec<char> = line.chars().collect();
+        while line.len() > max_chars {
+            if !current.is_empty() {
+                messages.push(std::mem::take(&mut current));
+            }
+            messages.push(line.drain(..max_chars).collect());
+        }
+        let line: String = line.into_iter().collect();
+        let needed = if current.is_empty() {
+            line.chars().count()
+        } else {
+            current.chars().count() + 1 + line.chars().count()
+        };
+        if needed > max_chars {
+            messages.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(&line);
     }
+    if !current.trim().is_empty() {
+        messages.push(current);
+    }
+    messages.retain(|message| !message.trim().is_empty());
+    messages
 }
 
-pub fn fallback(event: &Event) -> String {
-    render_post(event)
+pub fn validate_post_message(message: &str) -> Result<(), String> {
+    if message.trim().is_empty() {
+        return Err("post message is empty".to_string());
+    }
+    let length = message.chars().count();
+    if length > MAX_POST_CHARS {
+        return Err(format!(
+            "post message is {length} characters; the limit is {MAX_POST_CHARS}"
+        ));
+    }
+    Ok(())
 }
 
-pub fn fallback_preview(event: &Event) -> String {
-    render_preview(event)
+/// Length and content checks for a composed post, shown before posting.
+#[derive(Debug, Clone, Serialize)]
+pub struct PostAnalysis {
+    pub text: String,
+    pub char_count: usize,
+    pub over_limit: bool,
+    /// Links in the order they appear; Facebook previews only the first.
+    pub urls: Vec<String>,
+    pub has_emoji: bool,
+}
+
+static POST_URL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"https?://[^\s<>"]+"#).expect("post url regex"));
+
+/// Counts characters the way `validate_post_message` does and lists links
+/// without the sentence punctuation that tends to trail them.
+pub fn analyze_post(text: &str) -> PostAnalysis {
+    let char_count = text.chars().count();
+    let urls = POST_URL_RE
+        .find_iter(text)
+        .map(|url| {
+            url.as_str()
+                .trim_end_matches(['.', ',', '!', '?', ')', ';', ':'])
+                .to_string()
+        })
+        .collect();
+    PostAnalysis {
+        text: text.to_string(),
+        char_count,
+        over_limit: char_count > MAX_POST_CHARS,
+        urls,
+        has_emoji: text.chars().any(is_emoji_char),
+    }
 }
 
 impl LLMComposer {
-    async fn compose_internal(&self, event: &Event, preview: bool) -> Result<String, ComposeError> {
+    /// Confirms the endpoint answers `GET /models` within `timeout` and returns
+    /// the base URL that was checked.
+    pub async fn ping(&self, timeout: std::time::Duration) -> Result<String, ComposeError> {
         let base = self.base_url.trim_end_matches('/');
-        let url = format!("{}/chat/completions", base);
+        let mut request = self.client.get(format!("{base}/models")).timeout(timeout);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|err| ComposeError::Unavailable(err.to_string()))?;
+        if !response.status().is_success() {
+            return Err(ComposeError::Unavailable(format!(
+                "HTTP {}",
+                response.status()
+            )));
+        }
+        Ok(base.to_string())
+    }
+
+    /// The messages `compose_preview`/`compose` would send, without calling
+    /// the model.
+    pub fn prompt(&self, event: &Event, preview: bool) -> Prompt {
+        self.prompt_with_style(event, preview, &self.style)
+    }
 
+    fn prompt_with_style(&self, event: &Event, preview: bool, style: &str) -> Prompt {
         let context = if preview {
             "internal preview"
         } else {
             "Facebook group"
         };
+        let event_json =
+            serde_json::to_string_pretty(&event_payload(event, &self.render)).unwrap_or_default();
+        let system = match (&self.system_prompt, preview) {
+            (Some(custom), false) => custom.clone(),
+            _ => default_system(preview).to_string(),
+        };
+        let mut user = build_user_prompt(context, style, &event_json, &self.render);
+        if event.is_free() {
+            user.push_str(FREE_EVENT_RULE);
+        }
+        Prompt { system, user }
+    }
+
+    /// Composes one digest post listing every event, in the order given.
+    pub async fn compose_roundup(&self, events: &[Event]) -> Result<String, ComposeError> {
+        self.send_prompt(self.roundup_prompt(events)).await
+    }
+
+    fn roundup_prompt(&self, events: &[Event]) -> Prompt {
+        let payloads: Vec<serde_json::Value> = events
+            .iter()
+            .map(|event| event_payload(event, &self.render))
+            .collect();
+        let events_json = serde_json::to_string_pretty(&payloads).unwrap_or_default();
+        let mut user = format!(
+            "Format one Facebook group post rounding up these upcoming shows.\n\nJSON DATA:\n{events_json}\n\nRules:\n- Style: {style}.\n- Open with a one-line intro, then one line per show in the order given.\n- Each show line has the headliner, venue, date and time, and ticket link when present.\n- Use only the provided info.\n",
+            style = self.style,
+        );
+        user.push_str(&format_rules(&self.render));
+        if events.iter().any(Event::is_free) {
+            user.push_str(FREE_EVENT_RULE);
+        }
+        Prompt {
+            system: self
+                .system_prompt
+                .clone()
+                .unwrap_or_else(|| default_system(false).to_string()),
+            user,
+        }
+    }
+
+    async fn compose_internal(
+        &self,
+        event: &Event,
+        preview: bool,
+        style: &str,
+    ) -> Result<String, ComposeError> {
+        let text = self
+            .send_prompt(self.prompt_with_style(event, preview, style))
+            .await?;
+        self.check_usable(&text)?;
+        Ok(text)
+    }
+
+    /// Rejects a finished post that is blank or over `max_composed_chars`,
+    /// so callers fall back to the template draft.
+    fn check_usable(&self, text: &str) -> Result<(), ComposeError> {
+        if text.trim().is_empty() {
+            return Err(ComposeError::Unusable("post is empty".to_string()));
+        }
+        let length = text.chars().count();
+        if length > self.max_composed_chars {
+            return Err(ComposeError::Unusable(format!(
+                "post is {length} characters; the cap is {}",
+                self.max_composed_chars
+            )));
+        }
+        Ok(())
+    }
 
-        let event_json = serde_json::to_string_pretty(&event_payload(event)).unwrap_or_default();
-        let payload = json!({
+    async fn send_prompt(&self, prompt: Prompt) -> Result<String, ComposeError> {
+        self.request_choices(&prompt, 1, self.temperature)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ComposeError::Unavailable("LLM response missing content".to_string()))
+    }
+
+    /// Sends `prompt` asking for `n` completions and returns each usable
+    /// choice, finished for the configured format. Endpoints may return
+    /// fewer than `n`.
+    async fn request_choices(
+        &self,
+        prompt: &Prompt,
+        n: usize,
+        temperature: f32,
+    ) -> Result<Vec<String>, ComposeError> {
+        let base = self.base_url.trim_end_matches('/');
+        let url = format!("{}/chat/completions", base);
+
+        let mut payload = json!({
             "model": self.model,
-            "temperature": self.temperature,
+            "temperature": temperature,
             "max_tokens": self.max_tokens,
             "messages": [
                 {
                     "role": "system",
-                    "content": default_system(preview),
+                    "content": prompt.system,
                 },
                 {
                     "role": "user",
-                    "content": build_user_prompt(context, &self.style, &event_json),
+                    "content": prompt.user,
                 }
             ],
         });
 
+        if n > 1 {
+            payload["n"] = json!(n);
+        }
+
         let mut request = self.client.post(url).json(&payload);
         if let Some(key) = &self.api_key {
             request = request.bearer_auth(key);
         }
 
+        let _permit = Arc::clone(&self.limiter.permits)
+            .acquire_owned()
+            .await
+            .map_err(|err| ComposeError::Unavailable(err.to_string()))?;
         let response = request
             .send()
             .await
@@ -126,18 +667,58 @@ impl LLMComposer {
         let value: serde_json::Value = serde_json::from_str(&text_body)
             .map_err(|err| ComposeError::Unavailable(err.to_string()))?;
 
-        let text = value
+        let choices: Vec<String> = value
             .get("choices")
             .and_then(|choices| choices.as_array())
-            .and_then(|choices| choices.first())
-            .and_then(|choice| choice.get("message"))
-            .and_then(|message| message.get("content"))
-            .and_then(|content| content.as_str())
-            .map(|s| s.trim().to_string())
+            .into_iter()
+            .flatten()
+            .filter_map(|choice| choice.get("message"))
+            .filter_map(|message| message.get("content"))
+            .filter_map(|content| content.as_str())
+            .map(unwrap_content)
             .filter(|s| !s.is_empty())
-            .ok_or_else(|| ComposeError::Unavailable("LLM response missing content".to_string()))?;
+            .map(|text| self.render.finish(&text))
+            .collect();
+        if choices.is_empty() {
+            return Err(ComposeError::Unavailable(
+                "LLM response missing content".to_string(),
+            ));
+        }
+        Ok(choices)
+    }
+}
 
-        Ok(text)
+/// Fields local models use when they answer with a JSON object instead of
+/// plain text.
+const ENVELOPE_FIELDS: [&str; 4] = ["text", "post", "message", "content"];
+
+/// Recovers the post text from message content that some local models wrap
+/// in a code fence or a JSON envelope.
+fn unwrap_content(raw: &str) -> String {
+    let text = strip_code_fence(raw.trim());
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(serde_json::Value::String(inner)) => strip_code_fence(inner.trim()).to_string(),
+        Ok(serde_json::Value::Object(map)) => ENVELOPE_FIELDS
+            .iter()
+            .find_map(|field| map.get(*field).and_then(|value| value.as_str()))
+            .map(|inner| strip_code_fence(inner.trim()).to_string())
+            .unwrap_or_else(|| text.to_string()),
+        _ => text.to_string(),
+    }
+}
+
+/// Removes a fence wrapping the whole text, e.g. "```text\n...\n```".
+fn strip_code_fence(text: &str) -> &str {
+    let Some(rest) = text.strip_prefix("```") else {
+        return text;
+    };
+    let Some(body) = rest.trim_end().strip_suffix("```") else {
+        return text;
+    };
+    // Drop the info string ("text", "json", ...) on the opening line.
+    match body.split_once('\n') {
+        Some((info, inner)) if !info.trim().contains(' ') => inner.trim(),
+        _ => body.trim(),
     }
 }
 
@@ -149,52 +730,114 @@ fn default_system(preview: bool) -> &'static str {
     }
 }
 
-fn event_payload(event: &Event) -> serde_json::Value {
-    json!({
+fn event_payload(event: &Event, options: &RenderOptions) -> serde_json::Value {
+    let mut payload = json!({
         "artists": event.artists,
+        "billed_as": event.billed_as,
+        "lineup": event.lineup,
         "venue_name": event.venue_name,
-        "start_local": event.start_local,
-        "start_utc": event.start_utc,
-        "ticket_url": event.ticket_url,
-        "event_url": event.event_url,
+        "venue_url": event.venue_url,
+        "age_restriction": event
+            .age_restriction
+            .is_known()
+            .then(|| event.age_restriction.to_string()),
+        "start_local": if event.time_tba {
+            event_date(event).map(|date| date.to_string())
+        } else {
+            event.start_local.clone()
+        },
+        "start_utc": (!event.time_tba).then(|| event.start_utc.clone()),
+        "start_time": event.time_tba.then_some("TBA"),
+        "ticket_url": event.ticket_url.as_deref().map(|url| options.link(url)),
+        "event_url": event.event_url.as_deref().map(|url| options.link(url)),
         "price_min_cents": event.price_min_cents,
         "price_max_cents": event.price_max_cents,
         "currency": event.currency,
         "tags": event.tags,
         "extra": event.extra,
-    })
+    });
+    if let Some(fields) = payload.as_object_mut() {
+        fields.retain(|name, _| options.payload_fields.iter().any(|kept| kept == name));
+    }
+    payload
 }
 
-fn build_user_prompt(context: &str, style: &str, event_json: &str) -> String {
-    format!(
+fn build_user_prompt(
+    context: &str,
+    style: &str,
+    event_json: &str,
+    options: &RenderOptions,
+) -> String {
+    let mut prompt = format!(
         "Format a short {context} post for this show.\n\nJSON DATA:\n{json}\n\nRules:\n- Style: {style}.\n- Sound like one raver hyping another.\n- Hook readers with the headliner and venue immediately.\n- Describe the music vibe/genre using provided tags or notes (skip if unavailable).\n- Include ticket and event links when present.\n- Keep it punchy, high-energy, and authentic to the electronic scene.\n",
         context = context,
         style = style,
         json = event_json
-    )
+    );
+    prompt.push_str(&format_rules(options));
+    prompt
+}
+
+/// Added to prompts for shows priced at zero so the model doesn't promise
+/// tickets that don't exist.
+const FREE_EVENT_RULE: &str =
+    "- A show priced at 0 is free: say it's a free event and don't call tickets TBA.\n";
+
+/// Prompt rules for emoji and output format shared by every post prompt.
+fn format_rules(options: &RenderOptions) -> String {
+    let mut rules = String::new();
+    if !options.use_emoji {
+        rules.push_str("- Do not use any emoji.\n");
+    }
+    match options.format {
+        OutputFormat::Plain => rules.push_str("- Plain text only; no Markdown formatting.\n"),
+        OutputFormat::Markdown => rules.push_str(
+            "- Format with Markdown: bold the headliner and write links as [label](url).\n",
+        ),
+    }
+    rules
 }
 
-fn render_preview(event: &Event) -> String {
-    let local_time = parse_time(event).map(|dt| dt.format("%a %b %e @ %l:%M %p").to_string());
-    format!(
+/// Compact show time used in previews and roundups.
+fn short_when(event: &Event) -> Option<String> {
+    if event.time_tba {
+        event_date(event).map(|date| format!("{} · Doors TBA", date.format("%a %b %e")))
+    } else {
+        parse_time(event).map(|dt| format_time(&dt, PREVIEW_DATE_FORMAT))
+    }
+}
+
+fn render_preview(event: &Event, options: &RenderOptions) -> String {
+    let local_time = short_when(event);
+    let mut preview = format!(
         "{title}\nVenue: {venue}\nWhen: {when}\nTickets: {tickets}",
-        title = event.title(),
+        title = event.title_with_support(options.title_mode),
         venue = event
             .venue_name
             .clone()
             .unwrap_or_else(|| "Unknown Venue".to_string()),
         when = local_time.unwrap_or_else(|| event.start_utc.clone()),
-        tickets = event
-            .ticket_url
-            .clone()
-            .unwrap_or_else(|| "TBA".to_string()),
-    )
+        tickets = match event.ticket_url.as_deref() {
+            Some(url) => options.link(url),
+            None if event.is_free() => "Free".to_string(),
+            None => "TBA".to_string(),
+        },
+    );
+    if event.age_restriction.is_known() {
+        preview.push_str(&format!("\nAges: {}", event.age_restriction));
+    }
+    preview
 }
 
-fn render_post(event: &Event) -> String {
-    let local_time = parse_time(event)
-        .map(|dt| dt.format("%A, %B %e at %l:%M %p").to_string())
-        .unwrap_or_else(|| event.start_utc.clone());
+fn render_post(event: &Event, options: &RenderOptions) -> String {
+    let local_time = if event.time_tba {
+        event_date(event).map(|date| format!("{} · Doors TBA", date.format("%A, %B %e")))
+    } else if let Some(doors) = listed_doors_only(event) {
+        Some(doors.format("%A, %B %e · Doors %l:%M %p").to_string())
+    } else {
+        parse_time(event).map(|dt| format_time(&dt, &options.date_format))
+    }
+    .unwrap_or_else(|| event.start_utc.clone());
     let vibe = event
         .tags
         .first()
@@ -202,8 +845,14 @@ fn render_post(event: &Event) -> String {
         .filter(|tag| !tag.trim().is_empty())
         .map(|tag| format!("Sound: {tag}"));
 
+    let markdown = options.format == OutputFormat::Markdown;
+    let title = event.title_with_support(options.title_mode);
     let mut lines = vec![
-        event.title(),
+        if markdown {
+            format!("**{title}**")
+        } else {
+            title
+        },
         event
             .venue_name
             .clone()
@@ -211,30 +860,732 @@ fn render_post(event: &Event) -> String {
         local_time,
     ];
 
+    if event.age_restriction.is_known() {
+        lines.push(format!("Ages: {}", event.age_restriction));
+    }
+
     if let Some(vibe_line) = vibe {
         lines.push(vibe_line);
     }
 
+    let (ticket_label, event_label) = if options.use_emoji {
+        ("🎟 Tickets:", "ℹ️ Event:")
+    } else {
+        ("Tickets:", "Event:")
+    };
+
+    let link = |url: &str| {
+        let url = options.link(url);
+        if markdown {
+            format!("[{url}]({url})")
+        } else {
+            url
+        }
+    };
+
     if let Some(ticket) = &event.ticket_url {
-        lines.push(format!("🎟 Tickets: {ticket}"));
+        lines.push(format!("{ticket_label} {}", link(ticket)));
     }
 
     if let Some(details) = &event.event_url {
-        lines.push(format!("ℹ️ Event: {details}"));
+        lines.push(format!("{event_label} {}", link(details)));
+    }
+
+    if event.is_free() {
+        lines.push("Free event".to_string());
+    } else if event.ticket_url.is_none() {
+        lines.push(format!("{ticket_label} TBA"));
+    }
+
+    let post = lines.join("\n");
+    if options.use_emoji {
+        post
+    } else {
+        strip_emoji(&post)
+    }
+}
+
+static MD_LINK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"!?\[([^\]]*)\]\((\S+?)\)").expect("markdown link regex"));
+static MD_BLOCK_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^(\s*)(?:#{1,6}\s+|>\s?|[*+]\s+)").expect("markdown block regex")
+});
+static MD_URL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"https?://[^\s*]+").expect("markdown url regex"));
+static MD_STRONG_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\*\*(.+?)\*\*|__(.+?)__").expect("markdown strong regex"));
+static MD_EMPHASIS_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(^|[^\w*])[*_]([^*_\s](?:[^*_]*[^*_\s])?)[*_]([^\w*]|$)")
+        .expect("markdown emphasis regex")
+});
+static MD_CODE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"`([^`]*)`").expect("markdown code regex"));
+
+/// Flattens markdown into text Facebook will display as written. Links become
+/// `label (url)` (or the bare URL when the label is the URL itself) and URLs
+/// are never altered by emphasis stripping.
+pub fn markdown_to_plain(text: &str) -> String {
+    let linked = MD_LINK_RE.replace_all(text, |caps: &Captures| {
+        let label = caps[1].trim();
+        let url = &caps[2];
+        if label.is_empty() || label == url {
+            url.to_string()
+        } else {
+            format!("{label} ({url})")
+        }
+    });
+    let unblocked = MD_BLOCK_RE.replace_all(&linked, |caps: &Captures| {
+        let indent = &caps[1];
+        if caps[0].trim_start().starts_with(['*', '+']) {
+            format!("{indent}- ")
+        } else {
+            indent.to_string()
+        }
+    });
+
+    let mut plain = String::with_capacity(unblocked.len());
+    let mut last = 0;
+    for url in MD_URL_RE.find_iter(&unblocked) {
+        plain.push_str(&strip_inline_markdown(&unblocked[last..url.start()]));
+        plain.push_str(url.as_str());
+        last = url.end();
     }
+    plain.push_str(&strip_inline_markdown(&unblocked[last..]));
+    plain
+}
+
+fn strip_inline_markdown(text: &str) -> String {
+    let text = MD_CODE_RE.replace_all(text, "$1");
+    let text = MD_STRONG_RE.replace_all(&text, |caps: &Captures| {
+        caps.get(1)
+            .or_else(|| caps.get(2))
+            .map(|m| m.as_str().to_string())
+            .unwrap_or_default()
+    });
+    MD_EMPHASIS_RE.replace_all(&text, "$1$2$3").into_owned()
+}
+
+/// Removes emoji (including variation selectors and joiners) and tidies the
+/// whitespace they leave behind.
+pub fn strip_emoji(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if !line.chars().any(is_emoji_char) {
+                return line.to_string();
+            }
+            let kept: String = line.chars().filter(|ch| !is_emoji_char(*ch)).collect();
+            kept.split_whitespace().collect::<Vec<_>>().join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-    if !lines.iter().any(|line| line.contains("🎟")) {
-        lines.push("🎟 Tickets: TBA".to_string());
+fn is_emoji_char(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x1F000..=0x1FAFF
+            | 0x2600..=0x27BF
+            | 0x2B00..=0x2BFF
+            | 0x2300..=0x23FF
+            | 0x2139
+            | 0x200D
+            | 0x20E3
+            | 0xFE00..=0xFE0F
+            | 0xE0020..=0xE007F
+    )
+}
+
+/// Doors time for listings that gave nothing else; their start is only an
+/// estimate, so posts quote doors instead.
+fn listed_doors_only(event: &Event) -> Option<DateTime<FixedOffset>> {
+    if event.extra["start_estimated_from_doors"] != json!(true) {
+        return None;
     }
+    DateTime::parse_from_rfc3339(event.doors_local.as_deref()?).ok()
+}
+
+/// Start time in the venue's own offset. Only events without `start_local`
+/// fall back to the machine's zone.
+fn parse_time(event: &Event) -> Option<DateTime<FixedOffset>> {
+    event
+        .start_local
+        .as_ref()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(&event.start_utc)
+                .ok()
+                .map(|dt| dt.with_timezone(&Local).fixed_offset())
+        })
+}
+
+/// Formats with `pattern`, falling back to the default when a configured
+/// pattern has specifiers chrono can't render.
+fn format_time(dt: &DateTime<FixedOffset>, pattern: &str) -> String {
+    use std::fmt::Write;
 
-    lines.join("\n")
+    let mut out = String::new();
+    if write!(out, "{}", dt.format(pattern)).is_ok() {
+        return out;
+    }
+    dt.format(DEFAULT_POST_DATE_FORMAT).to_string()
 }
 
-fn parse_time(event: &Event) -> Option<DateTime<Local>> {
+/// Calendar date of the event at the venue, without converting to the
+/// machine's time zone, so date-only events never shift a day.
+fn event_date(event: &Event) -> Option<NaiveDate> {
     event
         .start_local
         .as_ref()
         .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
-        .or_else(|| DateTime::parse_from_rfc3339(&event.start_utc).ok())
-        .map(|dt| dt.with_timezone(&Local))
+        .map(|dt| dt.date_naive())
+        .or_else(|| parse_time(event).map(|dt| dt.date_naive()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::test_support::{chat_completion, MockResponse, MockServer};
+    use chrono::Utc;
+
+    #[test]
+    fn analysis_flags_long_posts_and_lists_links() {
+        let mut text = String::from(
+            "🎸 Tickets: https://tickets.example.com/e/1. Info (https://venue.example/show)\n",
+        );
+        text.push_str(&"a".repeat(MAX_POST_CHARS));
+        let analysis = analyze_post(&text);
+        assert!(analysis.over_limit);
+        assert_eq!(analysis.char_count, text.chars().count());
+        assert_eq!(
+            analysis.urls,
+            vec![
+                "https://tickets.example.com/e/1",
+                "https://venue.example/show"
+            ]
+        );
+        assert!(analysis.has_emoji);
+
+        let short = analyze_post("Plain text, no links");
+        assert!(!short.over_limit);
+        assert!(short.urls.is_empty());
+        assert!(!short.has_emoji);
+    }
+
+    #[test]
+    fn strip_emoji_removes_symbols_and_tidies_spacing() {
+        assert_eq!(
+            strip_emoji("🎟 Tickets: https://t.co/x\nℹ️ Event: info"),
+            "Tickets: https://t.co/x\nEvent: info"
+        );
+        assert_eq!(
+            strip_emoji("Big night 🔥🔥 at Treefort 🎶!"),
+            "Big night at Treefort !"
+        );
+        assert_eq!(strip_emoji("No emoji here"), "No emoji here");
+        assert_eq!(strip_emoji("Café — 21+ → doors"), "Café — 21+ → doors");
+    }
+
+    #[test]
+    fn fallback_without_emoji_uses_plain_labels() {
+        let event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        let options = RenderOptions {
+            use_emoji: false,
+            ..RenderOptions::default()
+        };
+
+        let post = fallback(&event, &options);
+        assert!(post.contains("\nTickets: https://tickets.example.com"));
+        assert!(post.contains("\nEvent: https://events.example.com"));
+        assert!(!post.contains('🎟'));
+        assert!(!post.contains('ℹ'));
+
+        let with_emoji = fallback(&event, &RenderOptions::default());
+        assert!(with_emoji.contains("🎟 Tickets: https://tickets.example.com"));
+    }
+
+    #[test]
+    fn free_event_says_so_instead_of_tickets_tba() {
+        let mut event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        event.ticket_url = None;
+        event.price_min_cents = Some(0);
+        event.price_max_cents = None;
+        let options = RenderOptions::default();
+
+        let post = fallback(&event, &options);
+        assert!(post.contains("\nFree event"), "{post}");
+        assert!(!post.contains("TBA"), "{post}");
+        assert!(fallback_preview(&event, &options).contains("Tickets: Free"));
+        assert!(fallback_roundup(std::slice::from_ref(&event), &options).contains("| Free"));
+
+        let composer = LLMComposer::from_env();
+        assert!(composer.prompt(&event, false).user.contains("free event"));
+
+        event.price_min_cents = Some(1500);
+        let paid = fallback(&event, &options);
+        assert!(paid.contains("🎟 Tickets: TBA"), "{paid}");
+        assert!(!paid.contains("Free"));
+        assert!(!composer.prompt(&event, false).user.contains("free event"));
+    }
+
+    #[test]
+    fn prompt_forbids_emoji_when_disabled() {
+        let plain = build_user_prompt(
+            "Facebook group",
+            "hype",
+            "{}",
+            &RenderOptions {
+                use_emoji: false,
+                ..RenderOptions::default()
+            },
+        );
+        assert!(plain.contains("Do not use any emoji"));
+        let default = build_user_prompt("Facebook group", "hype", "{}", &RenderOptions::default());
+        assert!(!default.contains("emoji"));
+    }
+
+    #[test]
+    fn markdown_to_plain_strips_formatting_and_keeps_urls() {
+        let markdown = "# Tonight\n**Rezz** with *special* guests\n- [Tickets](https://tix.example.com/a_b_c?x=1&y=__2__)\n* ![poster](https://img.example.com/p_1.jpg)\n> Doors at `8pm`\nMore: https://example.com/snake_case_path/_x_";
+        let plain = markdown_to_plain(markdown);
+        assert_eq!(
+            plain,
+            "Tonight\nRezz with special guests\n- Tickets (https://tix.example.com/a_b_c?x=1&y=__2__)\n- poster (https://img.example.com/p_1.jpg)\nDoors at 8pm\nMore: https://example.com/snake_case_path/_x_"
+        );
+    }
+
+    #[test]
+    fn markdown_fallback_flattens_to_plain_fallback() {
+        let event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        let markdown = fallback(
+            &event,
+            &RenderOptions::default().with_format(OutputFormat::Markdown),
+        );
+        assert!(markdown.starts_with("**"));
+        assert!(markdown.contains("[https://tickets.example.com](https://tickets.example.com)"));
+
+        let plain = fallback(&event, &RenderOptions::default());
+        assert_eq!(markdown_to_plain(&markdown), plain);
+    }
+
+    #[test]
+    fn link_suffix_merges_into_existing_queries() {
+        let config = AppConfig {
+            ticket_link_suffix: Some("?utm_source=fb&utm_medium=group".to_string()),
+            ..AppConfig::default()
+        };
+        let options = RenderOptions::from_config(&config);
+        assert_eq!(
+            options.link("https://tickets.example.com"),
+            "https://tickets.example.com?utm_source=fb&utm_medium=group"
+        );
+        assert_eq!(
+            options.link("https://tix.example.com/e?id=7#seats"),
+            "https://tix.example.com/e?id=7&utm_source=fb&utm_medium=group#seats"
+        );
+        assert_eq!(
+            RenderOptions::default().link("https://tix.example.com/e?id=7"),
+            "https://tix.example.com/e?id=7"
+        );
+
+        let mut event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        event.event_url = Some("https://events.example.com/show?id=9".to_string());
+        let post = fallback(&event, &options);
+        assert!(post.contains("https://tickets.example.com?utm_source=fb&utm_medium=group"));
+        assert!(
+            post.contains("https://events.example.com/show?id=9&utm_source=fb&utm_medium=group")
+        );
+        let payload = event_payload(&event, &options);
+        assert_eq!(
+            payload["ticket_url"],
+            "https://tickets.example.com?utm_source=fb&utm_medium=group"
+        );
+        assert_eq!(
+            event.ticket_url.as_deref(),
+            Some("https://tickets.example.com")
+        );
+    }
+
+    #[test]
+    fn doors_only_listings_quote_doors_instead_of_a_show_time() {
+        let mut event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        event.start_local = Some("2025-10-08T20:00:00-06:00".to_string());
+        event.doors_local = Some("2025-10-08T19:00:00-06:00".to_string());
+        event.extra = json!({"start_estimated_from_doors": true});
+
+        let post = fallback(&event, &RenderOptions::default());
+        assert!(
+            post.contains("Wednesday, October  8 · Doors  7:00 PM"),
+            "{post}"
+        );
+
+        event.extra = json!({});
+        let post = fallback(&event, &RenderOptions::default());
+        assert!(post.contains("Wednesday, October  8 at  8:00 PM"), "{post}");
+    }
+
+    #[test]
+    fn payload_only_carries_configured_fields() {
+        let mut event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        event.billed_as = Some("Sample Artist feat. Guest".to_string());
+        event.note = Some("waiting on promo code".to_string());
+        let payload = event_payload(&event, &RenderOptions::default());
+        assert_eq!(payload["price_min_cents"], 1500);
+        assert_eq!(payload["billed_as"], "Sample Artist feat. Guest");
+        assert!(payload.get("venue_url").is_none());
+
+        let config = AppConfig {
+            llm_payload_fields: DEFAULT_PAYLOAD_FIELDS
+                .iter()
+                .filter(|field| **field != "price_min_cents")
+                .map(|field| field.to_string())
+                .chain(["venue_url".to_string(), "note".to_string()])
+                .collect(),
+            ..AppConfig::default()
+        };
+        let composer =
+            LLMComposer::from_env().with_render_options(RenderOptions::from_config(&config));
+        let prompt = composer.prompt(&event, false);
+        assert!(!prompt.user.contains("price_min_cents"));
+        assert!(prompt.user.contains("price_max_cents"));
+        assert!(prompt.user.contains("\"venue_url\""));
+        assert!(!prompt.user.contains("promo code"), "notes stay private");
+    }
+
+    #[test]
+    fn date_only_events_render_doors_tba() {
+        let mut event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        event.start_local = Some("2025-10-08T00:00:00-06:00".to_string());
+        event.time_tba = true;
+
+        let post = fallback(&event, &RenderOptions::default());
+        assert!(post.contains("Wednesday, October  8 · Doors TBA"), "{post}");
+        let preview = fallback_preview(&event, &RenderOptions::default());
+        assert!(
+            preview.contains("When: Wed Oct  8 · Doors TBA"),
+            "{preview}"
+        );
+
+        let payload = event_payload(&event, &RenderOptions::default());
+        assert_eq!(payload["start_local"], "2025-10-08");
+        assert_eq!(payload["start_time"], "TBA");
+        assert!(payload["start_utc"].is_null());
+    }
+
+    #[test]
+    fn times_render_in_the_venue_offset_with_configured_pattern() {
+        let mut event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        // 8 PM in Boise is 2 AM the next day in UTC.
+        event.start_local = Some("2025-10-08T20:00:00-06:00".to_string());
+        event.start_utc = "2025-10-09T02:00:00+00:00".to_string();
+
+        let post = fallback(&event, &RenderOptions::default());
+        assert!(post.contains("Wednesday, October  8 at  8:00 PM"), "{post}");
+        let preview = fallback_preview(&event, &RenderOptions::default());
+        assert!(preview.contains("When: Wed Oct  8 @  8:00 PM"), "{preview}");
+
+        let options = RenderOptions {
+            date_format: "%Y-%m-%d %H:%M".to_string(),
+            ..RenderOptions::default()
+        };
+        assert!(fallback(&event, &options).contains("2025-10-08 20:00"));
+
+        let broken = RenderOptions {
+            date_format: "%Q".to_string(),
+            ..RenderOptions::default()
+        };
+        assert!(fallback(&event, &broken).contains("Wednesday, October  8 at  8:00 PM"));
+    }
+
+    #[test]
+    fn roundup_fallback_lists_each_event_on_its_own_line() {
+        let mut events: Vec<Event> = ["PUP", "Jinjer", "Oddisee"]
+            .iter()
+            .enumerate()
+            .map(|(day, headliner)| {
+                let mut event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+                event.artists = vec![headliner.to_string()];
+                event.start_local = Some(format!("2025-10-{:02}T20:00:00-06:00", day + 8));
+                event
+            })
+            .collect();
+        events[1].ticket_url = None;
+        events[2].venue_name = Some("Knitting Factory Boise".to_string());
+
+        let roundup = fallback_roundup(&events, &RenderOptions::default());
+        let lines: Vec<&str> = roundup.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Upcoming shows:",
+                "- PUP | Treefort Music Hall | Wed Oct  8 @  8:00 PM | https://tickets.example.com",
+                "- Jinjer | Treefort Music Hall | Thu Oct  9 @  8:00 PM",
+                "- Oddisee | Knitting Factory Boise | Fri Oct 10 @  8:00 PM | https://tickets.example.com",
+            ]
+        );
+    }
+
+    #[test]
+    fn long_posts_split_between_lines() {
+        let text = "intro\nfirst show line\nsecond show line";
+        assert_eq!(
+            split_post(text, 22),
+            vec!["intro\nfirst show line", "second show line"]
+        );
+        assert_eq!(split_post("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert_eq!(split_post(text, 1000), vec![text]);
+    }
+
+    #[test]
+    fn prompt_includes_event_json_and_style() {
+        let mut event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        event.tags = vec!["synthwave".to_string()];
+        let composer = LLMComposer::from_env();
+
+        let prompt = composer.prompt_with_style(&event, false, "hype");
+        assert_eq!(prompt.system, default_system(false));
+        assert!(prompt.user.contains("- Style: hype."));
+        assert!(prompt
+            .user
+            .contains("\"venue_name\": \"Treefort Music Hall\""));
+        assert!(prompt.user.contains("\"synthwave\""));
+
+        let preview = composer.prompt(&event, true);
+        assert_eq!(preview.system, default_system(true));
+        assert!(preview.user.contains("internal preview"));
+    }
+
+    fn sent_system_prompt(composer: &LLMComposer, server: &MockServer) -> String {
+        let event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        tauri::async_runtime::block_on(composer.compose(&event)).expect("compose");
+        let body: serde_json::Value =
+            serde_json::from_str(&server.requests().last().expect("request").body)
+                .expect("request json");
+        body["messages"][0]["content"]
+            .as_str()
+            .expect("system content")
+            .to_string()
+    }
+
+    #[test]
+    fn system_prompt_override_reaches_request_body() {
+        let server = MockServer::start(|_| MockResponse::ok(chat_completion("post")));
+        let composer = LLMComposer::from_env()
+            .with_base_url(server.url())
+            .with_system_prompt(Some("You post for a folk collective."));
+        assert_eq!(
+            sent_system_prompt(&composer, &server),
+            "You post for a folk collective."
+        );
+
+        let event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        assert_eq!(composer.prompt(&event, true).system, default_system(true));
+    }
+
+    #[test]
+    fn blank_system_prompt_override_uses_default() {
+        let server = MockServer::start(|_| MockResponse::ok(chat_completion("post")));
+        let composer = LLMComposer::from_env()
+            .with_base_url(server.url())
+            .with_system_prompt(Some("   "));
+        assert_eq!(
+            sent_system_prompt(&composer, &server),
+            default_system(false)
+        );
+    }
+
+    #[test]
+    fn code_fences_are_stripped_from_content() {
+        assert_eq!(
+            unwrap_content("```text\nPUP at Treefort tonight!\n🎟 Tickets: https://t.co/x\n```"),
+            "PUP at Treefort tonight!\n🎟 Tickets: https://t.co/x"
+        );
+        assert_eq!(unwrap_content("```\nPUP tonight\n```\n"), "PUP tonight");
+        assert_eq!(unwrap_content("```PUP tonight```"), "PUP tonight");
+        assert_eq!(
+            unwrap_content("Use ``` for code\nPUP tonight"),
+            "Use ``` for code\nPUP tonight"
+        );
+    }
+
+    #[test]
+    fn json_envelopes_are_unwrapped() {
+        assert_eq!(
+            unwrap_content(r#"{"post": "PUP tonight!\nTickets: https://t.co/x"}"#),
+            "PUP tonight!\nTickets: https://t.co/x"
+        );
+        assert_eq!(
+            unwrap_content("```json\n{\"text\": \"PUP tonight!\"}\n```"),
+            "PUP tonight!"
+        );
+        assert_eq!(unwrap_content(r#""PUP tonight!""#), "PUP tonight!");
+        assert_eq!(
+            unwrap_content(r#"{"headliner": "PUP"}"#),
+            r#"{"headliner": "PUP"}"#
+        );
+    }
+
+    #[test]
+    fn compose_unwraps_enveloped_responses() {
+        let server = MockServer::start(|_| {
+            MockResponse::ok(chat_completion(
+                "```json\n{\"post\": \"PUP tonight!\"}\n```",
+            ))
+        });
+        let composer = LLMComposer::from_env().with_base_url(server.url());
+        let event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+        let text = tauri::async_runtime::block_on(composer.compose(&event)).expect("compose");
+        assert_eq!(text, "PUP tonight!");
+    }
+
+    #[test]
+    fn variations_are_requested_again_when_n_is_ignored() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let calls = Arc::clone(&calls);
+            MockServer::start(move |_| {
+                // Ignores `n`; the second answer repeats the first.
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                MockResponse::ok(chat_completion(&format!(
+                    "PUP tonight, take {}",
+                    call.max(1)
+                )))
+            })
+        };
+        let composer = LLMComposer::from_env().with_base_url(server.url());
+        let event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+
+        let variations = tauri::async_runtime::block_on(composer.compose_variations(&event, 3))
+            .expect("variations")
+            .drafts;
+        assert_eq!(
+            variations,
+            vec!["PUP tonight, take 1", "PUP tonight, take 2"],
+            "repeats are dropped and at most n requests are made"
+        );
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].body.contains("\"n\":3"), "{}", requests[0].body);
+        assert!(!requests[1].body.contains("\"n\""), "{}", requests[1].body);
+    }
+
+    #[test]
+    fn variations_are_capped_and_keep_drafts_when_a_later_request_fails() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let calls = Arc::clone(&calls);
+            MockServer::start(move |_| match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => MockResponse::ok(chat_completion("First")),
+                1 => MockResponse::ok(chat_completion("Second")),
+                _ => MockResponse::status(500, "down"),
+            })
+        };
+        let composer = LLMComposer::from_env().with_base_url(server.url());
+        let event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+
+        let variations = tauri::async_runtime::block_on(composer.compose_variations(&event, 50))
+            .expect("partial variations");
+        assert_eq!(variations.drafts, vec!["First", "Second"]);
+        assert!(variations.stopped.is_some());
+        let requests = server.requests();
+        assert!(
+            requests[0]
+                .body
+                .contains(&format!("\"n\":{MAX_VARIATIONS}")),
+            "{}",
+            requests[0].body
+        );
+        assert_eq!(requests.len(), 3, "nothing is asked after the failure");
+    }
+
+    #[test]
+    fn variations_come_from_one_request_when_n_is_honored() {
+        let server = MockServer::start(|_| {
+            MockResponse::ok(
+                json!({
+                    "choices": [
+                        { "message": { "role": "assistant", "content": "One" } },
+                        { "message": { "role": "assistant", "content": "Two" } },
+                        { "message": { "role": "assistant", "content": "Three" } },
+                    ]
+                })
+                .to_string(),
+            )
+        });
+        let composer = LLMComposer::from_env().with_base_url(server.url());
+        let event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+
+        let variations = tauri::async_runtime::block_on(composer.compose_variations(&event, 3))
+            .expect("variations")
+            .drafts;
+        assert_eq!(variations, vec!["One", "Two", "Three"]);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn concurrency_limit_serializes_compose_calls() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let in_flight = Arc::clone(&in_flight);
+            let peak = Arc::clone(&peak);
+            MockServer::start(move |_| {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(std::time::Duration::from_millis(150));
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                MockResponse::ok(chat_completion("post"))
+            })
+        };
+        let composer = Arc::new(LLMComposer {
+            limiter: Arc::new(RequestLimiter::new(1)),
+            ..LLMComposer::from_env().with_base_url(server.url())
+        });
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let composer = Arc::clone(&composer);
+                tauri::async_runtime::spawn(async move {
+                    let event = db::sample_event("treefort", "Treefort Music Hall", Utc::now());
+                    composer.compose(&event).await
+                })
+            })
+            .collect();
+        tauri::async_runtime::block_on(async {
+            for handle in handles {
+                handle.await.expect("join").expect("compose");
+            }
+        });
+
+        assert_eq!(server.requests().len(), 2);
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn every_composer_shares_one_resizable_limiter() {
+        let plain = LLMComposer::from_env();
+        let configured = LLMComposer::from_config(&AppConfig::default());
+        assert!(Arc::ptr_eq(&plain.limiter, &configured.limiter));
+
+        let limiter = RequestLimiter::new(1);
+        limiter.resize(3);
+        assert_eq!(limiter.permits.available_permits(), 3);
+        let busy = Arc::clone(&limiter.permits)
+            .try_acquire_many_owned(2)
+            .expect("two permits");
+        limiter.resize(2);
+        assert_eq!(
+            limiter.permits.available_permits(),
+            0,
+            "the two running requests already fill the smaller cap"
+        );
+        drop(busy);
+        assert_eq!(limiter.permits.available_permits(), 2);
+    }
 }
@@ -1,17 +1,40 @@
-use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::scraping::base::{billed_act_keys, fuzzy_name_key};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Event {
-    pub id: String, // stable hash: venue_id|start_utc|main_artist
+    pub id: String, // stable hash, see scraping::base::event_id
     pub source: String,
     pub venue_id: String,
     pub venue_name: Option<String>,
     pub venue_url: Option<String>,
     pub start_local: Option<String>,
     pub start_utc: String,
+    /// Set when the listing had no show time and the venue is configured to
+    /// leave it unknown. The start is then local midnight and only its date
+    /// is meaningful.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub time_tba: bool,
+    /// Expected length of the show, used for calendar end times.
+    #[serde(default)]
+    pub duration_minutes: Option<u32>,
     pub doors_local: Option<String>,
     pub artists: Vec<String>,
-    pub is_all_ages: Option<bool>,
+    /// The headliner line as billed, e.g. "Artist feat. Guest", when the
+    /// collaboration is kept together for display. `artists` still lists
+    /// each act separately.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billed_as: Option<String>,
+    /// Billing order with roles, when the source distinguishes headliners from
+    /// openers. `artists` stays the flattened view.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lineup: Vec<LineupSlot>,
+    #[serde(default, alias = "is_all_ages")]
+    pub age_restriction: AgeRestriction,
     pub ticket_url: Option<String>,
     pub event_url: Option<String>,
     pub price_min_cents: Option<i64>,
@@ -20,13 +43,381 @@ pub struct Event {
     pub tags: Vec<String>,
     pub scraped_at_utc: String,
     pub extra: serde_json::Value,
+    /// Private organizer note. Never sent to the model or shown in posts;
+    /// scrapers leave it unset and `Store::upsert_event` keeps the stored one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 impl Event {
+    /// Headliner only. The event id is derived from this, so keep it simple.
     pub fn title(&self) -> String {
         self.artists
             .first()
             .cloned()
             .unwrap_or_else(|| "Untitled Event".to_string())
     }
+
+    /// Display title that also accounts for the rest of the bill. A kept
+    /// collaboration is shown as billed and its guests aren't counted again.
+    pub fn title_with_support(&self, mode: TitleMode) -> String {
+        let headliner = self.billed_as.clone().unwrap_or_else(|| self.title());
+        let billed = self
+            .billed_as
+            .as_deref()
+            .map(billed_act_keys)
+            .unwrap_or_default();
+        let support: Vec<&str> = self
+            .artists
+            .iter()
+            .skip(1)
+            .map(|artist| artist.trim())
+            .filter(|artist| !artist.is_empty())
+            .filter(|artist| !billed.contains(&fuzzy_name_key(artist)))
+            .collect();
+        if support.is_empty() {
+            return headliner;
+        }
+        match mode {
+            TitleMode::Count => format!("{headliner} + {} more", support.len()),
+            TitleMode::Names => format!("{headliner}, {}", support.join(", ")),
+        }
+    }
+
+    /// Whether the show is still ahead at `now`. A date-only (`time_tba`)
+    /// show stays upcoming until the end of its local day, since its stored
+    /// start is that day's midnight.
+    pub fn is_upcoming(&self, now: DateTime<Utc>) -> bool {
+        let Ok(start) = DateTime::parse_from_rfc3339(&self.start_utc) else {
+            return false;
+        };
+        if self.time_tba {
+            start + Duration::days(1) > now
+        } else {
+            start >= now
+        }
+    }
+
+    /// Whether the listing priced the show at zero, i.e. no paid tier.
+    pub fn is_free(&self) -> bool {
+        matches!(
+            (self.price_min_cents, self.price_max_cents),
+            (Some(0), None | Some(0)) | (None, Some(0))
+        )
+    }
+
+    /// Folds a duplicate listing into this one. Fields already set here win;
+    /// gaps are filled from `other`, and artists, lineup and tags are unioned
+    /// case-insensitively in this event's order first.
+    pub fn absorb(&mut self, other: &Event) {
+        fill(&mut self.venue_name, &other.venue_name);
+        fill(&mut self.venue_url, &other.venue_url);
+        fill(&mut self.billed_as, &other.billed_as);
+        fill(&mut self.note, &other.note);
+        fill(&mut self.start_local, &other.start_local);
+        fill(&mut self.duration_minutes, &other.duration_minutes);
+        fill(&mut self.doors_local, &other.doors_local);
+        fill(&mut self.ticket_url, &other.ticket_url);
+        fill(&mut self.event_url, &other.event_url);
+        fill(&mut self.price_min_cents, &other.price_min_cents);
+        fill(&mut self.price_max_cents, &other.price_max_cents);
+        fill(&mut self.currency, &other.currency);
+        if !self.age_restriction.is_known() {
+            self.age_restriction = other.age_restriction;
+        }
+        union_names(&mut self.artists, &other.artists, |name| name);
+        union_names(&mut self.lineup, &other.lineup, |slot| &slot.name);
+        union_names(&mut self.tags, &other.tags, |tag| tag);
+    }
+}
+
+fn fill<T: Clone>(target: &mut Option<T>, source: &Option<T>) {
+    if target.is_none() {
+        target.clone_from(source);
+    }
+}
+
+fn union_names<T: Clone>(target: &mut Vec<T>, source: &[T], name: impl Fn(&T) -> &str) {
+    for item in source {
+        let key = name(item).trim().to_lowercase();
+        if !target
+            .iter()
+            .any(|existing| name(existing).trim().to_lowercase() == key)
+        {
+            target.push(item.clone());
+        }
+    }
+}
+
+/// How `Event::title_with_support` mentions support acts.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TitleMode {
+    /// "PUP + 2 more"
+    #[default]
+    Count,
+    /// "PUP, Chase Petra, Illuminati Hotties"
+    Names,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LineupSlot {
+    pub name: String,
+    pub role: LineupRole,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LineupRole {
+    Headliner,
+    Support,
+    Guest,
+}
+
+/// Door policy for an event. Deserializes the legacy `is_all_ages` booleans as well
+/// as the tagged form this type serializes to.
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgeRestriction {
+    AllAges,
+    MinAge(u8),
+    #[default]
+    Unknown,
+}
+
+impl AgeRestriction {
+    pub fn is_known(&self) -> bool {
+        !matches!(self, AgeRestriction::Unknown)
+    }
+}
+
+impl fmt::Display for AgeRestriction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AgeRestriction::AllAges => write!(f, "All ages"),
+            AgeRestriction::MinAge(age) => write!(f, "{age}+"),
+            AgeRestriction::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum TaggedAgeRestriction {
+    AllAges,
+    MinAge(u8),
+    Unknown,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AgeRestrictionRepr {
+    Legacy(Option<bool>),
+    Tagged(TaggedAgeRestriction),
+}
+
+impl<'de> Deserialize<'de> for AgeRestriction {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let restriction = match AgeRestrictionRepr::deserialize(deserializer)? {
+            AgeRestrictionRepr::Legacy(Some(true)) => AgeRestriction::AllAges,
+            // The old flag only recorded that "all ages" wasn't stated, which
+            // says nothing about the minimum age.
+            AgeRestrictionRepr::Legacy(Some(false) | None) => AgeRestriction::Unknown,
+            AgeRestrictionRepr::Tagged(TaggedAgeRestriction::AllAges) => AgeRestriction::AllAges,
+            AgeRestrictionRepr::Tagged(TaggedAgeRestriction::MinAge(age)) => {
+                AgeRestriction::MinAge(age)
+            }
+            AgeRestrictionRepr::Tagged(TaggedAgeRestriction::Unknown) => AgeRestriction::Unknown,
+        };
+        Ok(restriction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn age_restriction_round_trips() {
+        for restriction in [
+            AgeRestriction::AllAges,
+            AgeRestriction::MinAge(18),
+            AgeRestriction::Unknown,
+        ] {
+            let encoded = serde_json::to_value(restriction).expect("serialize");
+            let decoded: AgeRestriction = serde_json::from_value(encoded).expect("deserialize");
+            assert_eq!(decoded, restriction);
+        }
+        assert_eq!(
+            serde_json::to_value(AgeRestriction::MinAge(21)).expect("serialize"),
+            json!({ "min_age": 21 })
+        );
+    }
+
+    #[test]
+    fn legacy_is_all_ages_values_still_deserialize() {
+        let cases = [
+            (json!(true), AgeRestriction::AllAges),
+            (json!(false), AgeRestriction::Unknown),
+            (json!(null), AgeRestriction::Unknown),
+        ];
+        for (legacy, expected) in cases {
+            let event: Event = serde_json::from_value(json!({
+                "id": "abc",
+                "source": "treefort",
+                "venue_id": "treefort",
+                "venue_name": null,
+                "venue_url": null,
+                "start_local": null,
+                "start_utc": "2025-10-08T02:00:00+00:00",
+                "doors_local": null,
+                "artists": ["PUP"],
+                "is_all_ages": legacy,
+                "ticket_url": null,
+                "event_url": null,
+                "price_min_cents": null,
+                "price_max_cents": null,
+                "currency": null,
+                "tags": [],
+                "scraped_at_utc": "2025-10-01T00:00:00+00:00",
+                "extra": {},
+            }))
+            .expect("legacy payload");
+            assert_eq!(event.age_restriction, expected);
+        }
+    }
+
+    #[test]
+    fn age_restriction_displays_precise_minimum() {
+        assert_eq!(AgeRestriction::MinAge(18).to_string(), "18+");
+        assert_eq!(AgeRestriction::MinAge(21).to_string(), "21+");
+        assert_eq!(AgeRestriction::AllAges.to_string(), "All ages");
+    }
+
+    fn event_with_artists(artists: &[&str]) -> Event {
+        let mut event =
+            crate::db::sample_event("treefort", "Treefort Music Hall", chrono::Utc::now());
+        event.artists = artists.iter().map(|artist| artist.to_string()).collect();
+        event
+    }
+
+    #[test]
+    fn title_with_support_without_support_acts() {
+        let solo = event_with_artists(&["PUP"]);
+        assert_eq!(solo.title_with_support(TitleMode::Count), "PUP");
+        assert_eq!(solo.title_with_support(TitleMode::Names), "PUP");
+
+        let empty = event_with_artists(&[]);
+        assert_eq!(empty.title_with_support(TitleMode::Count), "Untitled Event");
+    }
+
+    #[test]
+    fn title_with_support_one_support_act() {
+        let event = event_with_artists(&["PUP", " Chase Petra "]);
+        assert_eq!(event.title_with_support(TitleMode::Count), "PUP + 1 more");
+        assert_eq!(
+            event.title_with_support(TitleMode::Names),
+            "PUP, Chase Petra"
+        );
+    }
+
+    #[test]
+    fn title_with_support_multiple_support_acts() {
+        let event = event_with_artists(&["PUP", "Chase Petra", "", "Illuminati Hotties"]);
+        assert_eq!(event.title_with_support(TitleMode::Count), "PUP + 2 more");
+        assert_eq!(
+            event.title_with_support(TitleMode::Names),
+            "PUP, Chase Petra, Illuminati Hotties"
+        );
+        assert_eq!(event.title(), "PUP");
+    }
+
+    #[test]
+    fn title_with_support_matches_billed_acts_by_whole_name() {
+        let mut event = event_with_artists(&["Lowertown", "Friends", "Low", "Simon & Garfunkel"]);
+        event.billed_as = Some("Lowertown & Friends".to_string());
+        assert_eq!(
+            event.title_with_support(TitleMode::Names),
+            "Lowertown & Friends, Low, Simon & Garfunkel"
+        );
+
+        event.billed_as = Some("Lowertown feat. Simon & Garfunkel".to_string());
+        assert_eq!(
+            event.title_with_support(TitleMode::Names),
+            "Lowertown feat. Simon & Garfunkel, Friends, Low"
+        );
+    }
+
+    #[test]
+    fn absorb_prefers_own_fields_and_unions_lists() {
+        let mut keep: Event = serde_json::from_value(json!({
+            "id": "keep",
+            "source": "https://a.example.com",
+            "venue_id": "treefort",
+            "venue_name": "Treefort Music Hall",
+            "venue_url": null,
+            "start_local": null,
+            "start_utc": "2025-10-09T02:00:00Z",
+            "doors_local": null,
+            "artists": ["PUP", "Chase Petra"],
+            "ticket_url": "https://tickets.example.com/keep",
+            "event_url": null,
+            "price_min_cents": null,
+            "price_max_cents": null,
+            "currency": null,
+            "tags": ["Punk"],
+            "scraped_at_utc": "2025-10-01T00:00:00Z",
+            "extra": {}
+        }))
+        .expect("keep event");
+        let drop: Event = serde_json::from_value(json!({
+            "id": "drop",
+            "source": "https://b.example.com",
+            "venue_id": "knitboise",
+            "venue_name": "Knitting Factory Boise",
+            "venue_url": null,
+            "start_local": null,
+            "start_utc": "2025-10-09T02:00:00Z",
+            "doors_local": "2025-10-08T19:00:00-06:00",
+            "artists": ["pup", "Illuminati Hotties"],
+            "age_restriction": "all_ages",
+            "ticket_url": "https://tickets.example.com/drop",
+            "event_url": "https://events.example.com/drop",
+            "price_min_cents": 2500,
+            "price_max_cents": null,
+            "currency": "USD",
+            "tags": ["punk", "Indie"],
+            "scraped_at_utc": "2025-10-02T00:00:00Z",
+            "extra": {}
+        }))
+        .expect("drop event");
+
+        keep.absorb(&drop);
+        assert_eq!(keep.id, "keep");
+        assert_eq!(keep.venue_name.as_deref(), Some("Treefort Music Hall"));
+        assert_eq!(
+            keep.ticket_url.as_deref(),
+            Some("https://tickets.example.com/keep")
+        );
+        assert_eq!(
+            keep.event_url.as_deref(),
+            Some("https://events.example.com/drop")
+        );
+        assert_eq!(
+            keep.doors_local.as_deref(),
+            Some("2025-10-08T19:00:00-06:00")
+        );
+        assert_eq!(keep.price_min_cents, Some(2500));
+        assert_eq!(keep.currency.as_deref(), Some("USD"));
+        assert_eq!(keep.age_restriction, AgeRestriction::AllAges);
+        assert_eq!(
+            keep.artists,
+            vec!["PUP", "Chase Petra", "Illuminati Hotties"]
+        );
+        assert_eq!(keep.tags, vec!["Punk", "Indie"]);
+    }
 }
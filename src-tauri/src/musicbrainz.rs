@@ -4,15 +4,16 @@ use std::time::{Duration, Instant};
 
 use once_cell::sync::Lazy;
 use reqwest::{Client, Url};
-use rusqlite;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
 use tauri::async_runtime;
 use tokio::sync::Mutex as AsyncMutex;
 use tokio::time::sleep;
 
+use crate::config::{AppConfig, ConfigError, EnrichScope};
 use crate::db::Store;
 use crate::models::Event;
+use crate::utils;
 
 static CLIENT: Lazy<Client> = Lazy::new(|| {
     let user_agent = std::env::var("MUSICBRAINZ_USER_AGENT")
@@ -26,10 +27,58 @@ static CLIENT: Lazy<Client> = Lazy::new(|| {
 static CACHE: Lazy<Mutex<HashMap<String, Option<ArtistProfile>>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// Image lookups by MBID for this session; failures aren't remembered.
+static IMAGE_CACHE: Lazy<Mutex<HashMap<String, Option<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 static REQUEST_QUEUE: Lazy<AsyncMutex<()>> = Lazy::new(|| AsyncMutex::new(()));
 static LAST_REQUEST: Lazy<AsyncMutex<Option<Instant>>> = Lazy::new(|| AsyncMutex::new(None));
 
+const API_BASE: &str = "https://musicbrainz.org/ws/2";
 const RATE_LIMIT_WINDOW_MS: u64 = 1100;
+/// Retries after a 503/429 when the config doesn't say otherwise.
+pub const DEFAULT_RETRIES: u32 = 2;
+/// First retry waits this long on top of the rate limit; doubles each time.
+const RETRY_BACKOFF_MS: u64 = 2000;
+/// Thumbnail width requested from Commons for `extra.musicbrainz.image`.
+const IMAGE_WIDTH: u32 = 300;
+/// Genres kept on an event after normalization.
+const MAX_GENRES: usize = 5;
+
+/// Tags MusicBrainz users attach that describe origin or listening habits
+/// rather than sound.
+const NON_GENRE_TAGS: &[&str] = &[
+    "american",
+    "british",
+    "english",
+    "canadian",
+    "australian",
+    "usa",
+    "uk",
+    "us",
+    "united states",
+    "idaho",
+    "boise",
+    "seen live",
+    "favorites",
+    "owned",
+];
+
+/// Spellings folded onto one genre, as (variant key, canonical name).
+const GENRE_SYNONYMS: &[(&str, &str)] = &[
+    ("alt rock", "alternative rock"),
+    ("alternative", "alternative rock"),
+    ("hiphop", "hip hop"),
+    ("rap", "hip hop"),
+    ("electronica", "electronic"),
+    ("edm", "electronic dance music"),
+    ("dnb", "drum and bass"),
+    ("drum n bass", "drum and bass"),
+    ("rnb", "r&b"),
+    ("r and b", "r&b"),
+    ("synth pop", "synthpop"),
+    ("post punk", "post-punk"),
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArtistProfile {
@@ -47,6 +96,9 @@ pub enum MusicBrainzError {
     Parse(String),
     #[error("cache error: {0}")]
     Cache(String),
+    /// MusicBrainz kept answering 503/429; worth trying again later.
+    #[error("musicbrainz unavailable: {0}")]
+    Unavailable(String),
 }
 
 #[derive(Debug, Deserialize)]
@@ -70,46 +122,290 @@ struct TagDoc {
     name: String,
 }
 
-pub async fn enrich_event(mut event: Event) -> Result<Event, MusicBrainzError> {
-    let artist_name = match event.artists.first() {
-        Some(name) if !name.trim().is_empty() => name.trim(),
-        _ => return Ok(event),
+#[derive(Debug, Deserialize)]
+struct ArtistRelationsResponse {
+    #[serde(default)]
+    relations: Vec<RelationDoc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelationDoc {
+    #[serde(rename = "type")]
+    kind: String,
+    url: Option<RelationUrl>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelationUrl {
+    resource: String,
+}
+
+/// Artist → genres supplied by the user, persisted as JSON at
+/// `utils::genre_overrides_path()`. Keys match artist names case-insensitively.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GenreOverrides(pub HashMap<String, Vec<String>>);
+
+impl GenreOverrides {
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_from(&utils::genre_overrides_path())
+    }
+
+    pub fn load_from(path: &std::path::Path) -> Result<Self, ConfigError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = std::fs::read_to_string(path)?;
+        if raw.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&raw)?)
+    }
+
+    pub fn save(&self) -> Result<(), ConfigError> {
+        self.save_to(&utils::genre_overrides_path())
+    }
+
+    pub fn save_to(&self, path: &std::path::Path) -> Result<(), ConfigError> {
+        utils::ensure_parent(path);
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Sets an artist's genres, replacing any entry that differs only in case.
+    /// An empty list removes the override.
+    pub fn set(&mut self, artist: &str, genres: Vec<String>) {
+        let artist = artist.trim();
+        self.0
+            .retain(|name, _| !name.trim().eq_ignore_ascii_case(artist));
+        let genres: Vec<String> = genres
+            .into_iter()
+            .map(|genre| genre.trim().to_string())
+            .filter(|genre| !genre.is_empty())
+            .collect();
+        if !artist.is_empty() && !genres.is_empty() {
+            self.0.insert(artist.to_string(), genres);
+        }
+    }
+
+    pub fn genres_for(&self, artist: &str) -> Option<&[String]> {
+        let artist = artist.trim();
+        self.0
+            .iter()
+            .find(|(name, genres)| name.trim().eq_ignore_ascii_case(artist) && !genres.is_empty())
+            .map(|(_, genres)| genres.as_slice())
+    }
+}
+
+/// Adds genres for the artists within the configured `enrich_scope`, from
+/// the user's overrides when present and from MusicBrainz otherwise.
+pub async fn enrich_event(event: Event) -> Result<Event, MusicBrainzError> {
+    let overrides = GenreOverrides::load().unwrap_or_else(|err| {
+        eprintln!("genre overrides unreadable, ignoring them: {err}");
+        GenreOverrides::default()
+    });
+    let scope = AppConfig::load()
+        .map(|config| config.enrich_scope)
+        .unwrap_or_default();
+    enrich_event_with(event, &overrides, scope).await
+}
+
+/// Billed artists to look up under `scope`, headliner first.
+fn scoped_artists(event: &Event, scope: EnrichScope) -> Vec<&str> {
+    let limit = match scope {
+        EnrichScope::HeadlinerOnly => 1,
+        EnrichScope::TopN(count) => usize::from(count),
+        EnrichScope::All => usize::MAX,
     };
+    event
+        .artists
+        .iter()
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .take(limit)
+        .collect()
+}
 
-    let profile = lookup_artist(artist_name).await?;
-    if let Some(profile) = profile {
-        let mut genres: Vec<String> = event.tags.clone();
-        for genre in &profile.genres {
-            if !genres
-                .iter()
-                .any(|existing| existing.eq_ignore_ascii_case(genre))
-            {
-                genres.push(genre.clone());
+/// The headliner's override or profile is recorded in `extra`; other artists
+/// only contribute genres.
+pub(crate) async fn enrich_event_with(
+    mut event: Event,
+    overrides: &GenreOverrides,
+    scope: EnrichScope,
+) -> Result<Event, MusicBrainzError> {
+    let artists: Vec<String> = scoped_artists(&event, scope)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    for (index, artist_name) in artists.iter().enumerate() {
+        let headliner = index == 0;
+
+        // Overrides exist for acts MusicBrainz gets wrong or lacks, so they
+        // skip the lookup and keep the user's spelling.
+        if let Some(genres) = overrides.genres_for(artist_name) {
+            let genres = genres.to_vec();
+            for genre in &genres {
+                if !event
+                    .tags
+                    .iter()
+                    .any(|existing| existing.eq_ignore_ascii_case(genre))
+                {
+                    event.tags.push(genre.clone());
+                }
             }
+            if headliner {
+                let mut extra_map = match event.extra {
+                    Value::Object(map) => map,
+                    _ => Map::new(),
+                };
+                extra_map.insert("genre_override".to_string(), json!(genres));
+                event.extra = Value::Object(extra_map);
+            }
+            continue;
         }
-        event.tags = genres;
 
-        let mut extra_map = match event.extra {
-            Value::Object(map) => map,
-            _ => Map::new(),
+        let Some(profile) = lookup_artist(artist_name).await? else {
+            continue;
         };
-        extra_map.insert(
-            "musicbrainz".to_string(),
-            json!({
-                "id": profile.id,
-                "name": profile.name,
-                "disambiguation": profile.disambiguation,
-                "genres": profile.genres,
-            }),
-        );
-        event.extra = Value::Object(extra_map);
+        if headliner {
+            let config = AppConfig::load().unwrap_or_default();
+            let image = if config.musicbrainz_images {
+                artist_image_or_none(API_BASE, &profile.id, config.musicbrainz_retries).await
+            } else {
+                None
+            };
+            event = apply_profile(event, &profile, image.as_deref());
+        } else {
+            event.tags = merge_genres(&event.tags, &profile.genres);
+        }
     }
 
     Ok(event)
 }
 
+fn merge_genres(tags: &[String], genres: &[String]) -> Vec<String> {
+    let mut merged: Vec<String> = tags.to_vec();
+    for genre in genres {
+        if !merged
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(genre))
+        {
+            merged.push(genre.clone());
+        }
+    }
+    normalize_genres(&merged)
+}
+
+/// Merges a matched profile's genres into the tags and records it under
+/// `extra.musicbrainz`, with `image` only when one was found.
+fn apply_profile(mut event: Event, profile: &ArtistProfile, image: Option<&str>) -> Event {
+    event.tags = merge_genres(&event.tags, &profile.genres);
+
+    let mut extra_map = match event.extra {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    let mut musicbrainz = json!({
+        "id": profile.id,
+        "name": profile.name,
+        "disambiguation": profile.disambiguation,
+        "genres": profile.genres,
+    });
+    if let Some(image) = image {
+        musicbrainz["image"] = json!(image);
+    }
+    extra_map.insert("musicbrainz".to_string(), musicbrainz);
+    event.extra = Value::Object(extra_map);
+    event
+}
+
+/// Image URL for a matched artist, from its Wikimedia Commons relation.
+/// Lookup failures are logged and treated as no image so they never block
+/// genre enrichment.
+async fn artist_image_or_none(api_base: &str, mbid: &str, retries: u32) -> Option<String> {
+    {
+        let cache = IMAGE_CACHE
+            .lock()
+            .expect("musicbrainz image cache poisoned");
+        if let Some(cached) = cache.get(mbid) {
+            return cached.clone();
+        }
+    }
+    match artist_image(api_base, mbid, retries).await {
+        Ok(image) => {
+            IMAGE_CACHE
+                .lock()
+                .expect("musicbrainz image cache poisoned")
+                .insert(mbid.to_string(), image.clone());
+            image
+        }
+        Err(err) => {
+            eprintln!("musicbrainz image lookup failed for {mbid}: {err}");
+            None
+        }
+    }
+}
+
+async fn artist_image(
+    api_base: &str,
+    mbid: &str,
+    retries: u32,
+) -> Result<Option<String>, MusicBrainzError> {
+    let mut url = Url::parse(&format!("{api_base}/artist/{mbid}"))
+        .map_err(|err| MusicBrainzError::Http(err.to_string()))?;
+    url.query_pairs_mut()
+        .append_pair("inc", "url-rels")
+        .append_pair("fmt", "json");
+    let text = fetch_artist_payload(url, retries, Duration::from_millis(RETRY_BACKOFF_MS)).await?;
+    let payload: ArtistRelationsResponse =
+        serde_json::from_str(&text).map_err(|err| MusicBrainzError::Parse(err.to_string()))?;
+    Ok(payload
+        .relations
+        .into_iter()
+        .filter(|relation| relation.kind == "image")
+        .filter_map(|relation| relation.url)
+        .find_map(|url| commons_file_url(&url.resource)))
+}
+
+/// Turns a Commons "File:" page into a direct, thumbnail-sized image URL.
+fn commons_file_url(resource: &str) -> Option<String> {
+    let file = resource.split_once("commons.wikimedia.org/wiki/File:")?.1;
+    (!file.is_empty()).then(|| {
+        format!("https://commons.wikimedia.org/wiki/Special:FilePath/{file}?width={IMAGE_WIDTH}")
+    })
+}
+
+/// Cache key for an artist name, shared by the session map and the DB table.
+fn artist_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Drops an artist from the session cache, returning whether it was there.
+fn forget_session_profile(key: &str) -> bool {
+    CACHE
+        .lock()
+        .expect("musicbrainz cache poisoned")
+        .remove(key)
+        .is_some()
+}
+
+/// Discards a possibly wrong match for `name`, in memory and in the DB, and
+/// looks the artist up again. Other artists' cached profiles are untouched.
+pub async fn refresh_artist(name: &str) -> Result<Option<ArtistProfile>, MusicBrainzError> {
+    let key = artist_key(name);
+    forget_session_profile(&key);
+    let key_owned = key.clone();
+    async_runtime::spawn_blocking(move || -> rusqlite::Result<bool> {
+        Store::open_default()?.delete_musicbrainz_profile(&key_owned)
+    })
+    .await
+    .map_err(|err| MusicBrainzError::Cache(err.to_string()))?
+    .map_err(|err| MusicBrainzError::Cache(err.to_string()))?;
+    lookup_artist(name.trim()).await
+}
+
 async fn lookup_artist(name: &str) -> Result<Option<ArtistProfile>, MusicBrainzError> {
-    let key = name.to_lowercase();
+    let key = artist_key(name);
     let cached_opt = {
         let guard = CACHE.lock().expect("musicbrainz cache poisoned");
         guard.get(&key).cloned()
@@ -127,7 +423,7 @@ async fn lookup_artist(name: &str) -> Result<Option<ArtistProfile>, MusicBrainzE
     }
 
     let sanitized = name.replace('"', " ");
-    let mut url = Url::parse("https://musicbrainz.org/ws/2/artist/")
+    let mut url = Url::parse(&format!("{API_BASE}/artist/"))
         .map_err(|err| MusicBrainzError::Http(err.to_string()))?;
     url.query_pairs_mut()
         .append_pair("query", &format!("artist:\"{}\"", sanitized))
@@ -135,7 +431,13 @@ async fn lookup_artist(name: &str) -> Result<Option<ArtistProfile>, MusicBrainzE
         .append_pair("limit", "1")
         .append_pair("inc", "tags+genres");
 
-    let text = fetch_artist_payload(url).await?;
+    let retries = AppConfig::load()
+        .map(|config| config.musicbrainz_retries)
+        .unwrap_or(DEFAULT_RETRIES);
+    let backoff = Duration::from_millis(RETRY_BACKOFF_MS);
+    // Errors return before the cache write, so a transient failure is
+    // looked up again next time instead of being remembered as a miss.
+    let text = fetch_artist_payload(url, retries, backoff).await?;
 
     let payload: ArtistSearchResponse =
         serde_json::from_str(&text).map_err(|err| MusicBrainzError::Parse(err.to_string()))?;
@@ -164,29 +466,74 @@ async fn lookup_artist(name: &str) -> Result<Option<ArtistProfile>, MusicBrainzE
     Ok(profile)
 }
 
-async fn fetch_artist_payload(url: Url) -> Result<String, MusicBrainzError> {
+/// Confirms the MusicBrainz API answers within `timeout`, honoring the shared
+/// rate limit like any other request.
+pub async fn ping(timeout: Duration) -> Result<(), MusicBrainzError> {
+    let url = Url::parse(&format!(
+        "{API_BASE}/artist/?query=artist:test&limit=1&fmt=json"
+    ))
+    .map_err(|err| MusicBrainzError::Http(err.to_string()))?;
     let _guard = REQUEST_QUEUE.lock().await;
     wait_for_rate_limit().await;
-
     let response = CLIENT
         .get(url)
+        .timeout(timeout)
         .send()
         .await
         .map_err(|err| MusicBrainzError::Http(err.to_string()))?;
-    let status = response.status();
-    let text = response
-        .text()
-        .await
-        .map_err(|err| MusicBrainzError::Http(err.to_string()))?;
-
-    if !status.is_success() {
+    if !response.status().is_success() {
         return Err(MusicBrainzError::Http(format!(
-            "status {}: {}",
-            status, text
+            "status {}",
+            response.status()
         )));
     }
+    Ok(())
+}
 
-    Ok(text)
+/// Fetches `url`, retrying up to `retries` times on 503/429 with doubling
+/// backoff. Other failures return immediately.
+async fn fetch_artist_payload(
+    url: Url,
+    retries: u32,
+    backoff: Duration,
+) -> Result<String, MusicBrainzError> {
+    let _guard = REQUEST_QUEUE.lock().await;
+    let mut attempt = 0;
+    loop {
+        wait_for_rate_limit().await;
+
+        let response = CLIENT
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|err| MusicBrainzError::Http(err.to_string()))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|err| MusicBrainzError::Http(err.to_string()))?;
+
+        if status.is_success() {
+            return Ok(text);
+        }
+        let retryable = status == reqwest::StatusCode::SERVICE_UNAVAILABLE
+            || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+        if !retryable {
+            return Err(MusicBrainzError::Http(format!(
+                "status {}: {}",
+                status, text
+            )));
+        }
+        if attempt >= retries {
+            return Err(MusicBrainzError::Unavailable(format!(
+                "status {} after {} attempts",
+                status,
+                attempt + 1
+            )));
+        }
+        sleep(backoff * 2u32.saturating_pow(attempt)).await;
+        attempt += 1;
+    }
 }
 
 async fn wait_for_rate_limit() {
@@ -229,6 +576,56 @@ async fn store_cached_profile(
     result.map_err(|err| MusicBrainzError::Cache(err.to_string()))
 }
 
+/// Trims a raw tag list for prompting: drops geographic and other non-genre
+/// tags, folds near-synonyms ("alt rock", "hip-hop") onto one spelling, and
+/// keeps at most `MAX_GENRES` in their original order.
+pub fn normalize_genres(tags: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut seen: Vec<String> = Vec::new();
+    for tag in tags {
+        let cleaned = tag.split_whitespace().collect::<Vec<_>>().join(" ");
+        if cleaned.is_empty() {
+            continue;
+        }
+        let key = genre_key(&cleaned);
+        if NON_GENRE_TAGS.contains(&key.as_str()) {
+            continue;
+        }
+        let (key, name) = match GENRE_SYNONYMS.iter().find(|(variant, _)| *variant == key) {
+            Some((_, canonical)) => (genre_key(canonical), canonical.to_string()),
+            None => (key, cleaned),
+        };
+        if seen.contains(&key) {
+            continue;
+        }
+        seen.push(key);
+        out.push(name);
+        if out.len() == MAX_GENRES {
+            break;
+        }
+    }
+    out
+}
+
+/// Comparison key for a genre with synonyms folded, so "Alt-Rock" and
+/// "alternative rock" match.
+pub fn canonical_genre_key(tag: &str) -> String {
+    let key = genre_key(tag);
+    match GENRE_SYNONYMS.iter().find(|(variant, _)| *variant == key) {
+        Some((_, canonical)) => genre_key(canonical),
+        None => key,
+    }
+}
+
+fn genre_key(tag: &str) -> String {
+    tag.to_lowercase()
+        .replace(['-', '_', '/'], " ")
+        .replace(" & ", " and ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 fn extract_genres(doc: &ArtistDoc) -> Vec<String> {
     let mut out: Vec<String> = Vec::new();
     for tag in doc.genres.iter().chain(doc.tags.iter()) {
@@ -245,3 +642,237 @@ fn extract_genres(doc: &ArtistDoc) -> Vec<String> {
     }
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn normalize_genres_trims_realistic_tag_list() {
+        let raw = tags(&[
+            "alternative rock",
+            "rock",
+            "indie",
+            "american",
+            "Indie Rock",
+            "indie-rock",
+            "seen live",
+            "alt rock",
+            "Boise",
+            "punk",
+            "emo",
+        ]);
+        assert_eq!(
+            normalize_genres(&raw),
+            vec!["alternative rock", "rock", "indie", "Indie Rock", "punk"]
+        );
+    }
+
+    #[test]
+    fn normalize_genres_folds_synonyms_onto_canonical_names() {
+        let raw = tags(&[
+            "Hip-Hop",
+            "rap",
+            "hip hop",
+            "  drum &  bass ",
+            "dnb",
+            "Electronica",
+        ]);
+        assert_eq!(
+            normalize_genres(&raw),
+            vec!["Hip-Hop", "drum & bass", "electronic"]
+        );
+    }
+
+    #[test]
+    fn overridden_artists_get_local_genres_without_a_lookup() {
+        let mut overrides = GenreOverrides::default();
+        overrides.set("Boise Local Band", tags(&["treefolk", "doom country"]));
+        let mut event = crate::db::sample_event("treefort", "Treefort", chrono::Utc::now());
+        event.artists = tags(&["boise local band"]);
+        event.tags = tags(&["Rock"]);
+
+        // No network is reachable from here, so a lookup would fail the test.
+        let enriched = async_runtime::block_on(enrich_event_with(
+            event,
+            &overrides,
+            EnrichScope::HeadlinerOnly,
+        ))
+        .expect("override enrichment");
+        assert_eq!(enriched.tags, vec!["Rock", "treefolk", "doom country"]);
+        assert_eq!(
+            enriched.extra["genre_override"],
+            json!(["treefolk", "doom country"])
+        );
+        assert!(enriched.extra.get("musicbrainz").is_none());
+    }
+
+    #[test]
+    fn enrich_scope_bounds_the_artists_looked_up() {
+        let mut event = crate::db::sample_event("treefort", "Treefort", chrono::Utc::now());
+        event.artists = tags(&["Alpha", " ", "Beta", "Gamma"]);
+
+        assert_eq!(
+            scoped_artists(&event, EnrichScope::HeadlinerOnly),
+            vec!["Alpha"]
+        );
+        assert_eq!(
+            scoped_artists(&event, EnrichScope::TopN(2)),
+            vec!["Alpha", "Beta"]
+        );
+        assert!(scoped_artists(&event, EnrichScope::TopN(0)).is_empty());
+        assert_eq!(
+            scoped_artists(&event, EnrichScope::All),
+            vec!["Alpha", "Beta", "Gamma"]
+        );
+    }
+
+    #[test]
+    fn wider_scopes_merge_genres_from_support_acts() {
+        let mut overrides = GenreOverrides::default();
+        overrides.set("Alpha", tags(&["shoegaze"]));
+        overrides.set("Beta", tags(&["slowcore"]));
+        overrides.set("Gamma", tags(&["noise"]));
+        let mut event = crate::db::sample_event("treefort", "Treefort", chrono::Utc::now());
+        event.artists = tags(&["Alpha", "Beta", "Gamma"]);
+        event.tags = Vec::new();
+
+        let enrich = |scope| {
+            async_runtime::block_on(enrich_event_with(event.clone(), &overrides, scope))
+                .expect("override enrichment")
+                .tags
+        };
+        assert_eq!(enrich(EnrichScope::HeadlinerOnly), vec!["shoegaze"]);
+        assert_eq!(enrich(EnrichScope::TopN(2)), vec!["shoegaze", "slowcore"]);
+        assert_eq!(
+            enrich(EnrichScope::All),
+            vec!["shoegaze", "slowcore", "noise"]
+        );
+    }
+
+    #[test]
+    fn payload_fetch_retries_through_503s() {
+        use crate::test_support::{MockResponse, MockServer};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = AtomicUsize::new(0);
+        let server = MockServer::start(move |_| {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                MockResponse::status(503, "busy")
+            } else {
+                MockResponse::ok(r#"{"artists":[]}"#)
+            }
+        });
+        let url = Url::parse(server.url()).expect("mock url");
+
+        let text = async_runtime::block_on(fetch_artist_payload(
+            url.clone(),
+            2,
+            Duration::from_millis(1),
+        ))
+        .expect("third attempt succeeds");
+        assert_eq!(text, r#"{"artists":[]}"#);
+        assert_eq!(server.requests().len(), 3);
+
+        let failing = MockServer::start(|_| MockResponse::status(503, "busy"));
+        let url = Url::parse(failing.url()).expect("mock url");
+        let err = async_runtime::block_on(fetch_artist_payload(url, 1, Duration::from_millis(1)))
+            .expect_err("retries exhausted");
+        assert!(matches!(err, MusicBrainzError::Unavailable(_)));
+        assert_eq!(failing.requests().len(), 2);
+    }
+
+    fn profile() -> ArtistProfile {
+        ArtistProfile {
+            id: "mbid-1".to_string(),
+            name: "Built to Spill".to_string(),
+            disambiguation: None,
+            genres: tags(&["indie rock"]),
+        }
+    }
+
+    #[test]
+    fn forgetting_one_artist_keeps_the_rest_of_the_session_cache() {
+        {
+            let mut cache = CACHE.lock().expect("cache");
+            cache.insert("forget-me".to_string(), Some(profile()));
+            cache.insert("keep-me".to_string(), None);
+        }
+        assert_eq!(artist_key("  Forget Me "), "forget me");
+        assert!(forget_session_profile("forget-me"));
+        assert!(!forget_session_profile("forget-me"));
+
+        let cache = CACHE.lock().expect("cache");
+        assert!(!cache.contains_key("forget-me"));
+        assert!(cache.contains_key("keep-me"));
+    }
+
+    #[test]
+    fn failed_image_lookup_leaves_genre_enrichment_intact() {
+        use crate::test_support::{MockResponse, MockServer};
+
+        let server = MockServer::start(|_| MockResponse::status(500, "boom"));
+        let image = async_runtime::block_on(artist_image_or_none(server.url(), "mbid-fail", 0));
+        assert_eq!(image, None);
+
+        let event = crate::db::sample_event("treefort", "Treefort", chrono::Utc::now());
+        let enriched = apply_profile(event, &profile(), image.as_deref());
+        assert!(enriched.tags.iter().any(|tag| tag == "indie rock"));
+        assert_eq!(enriched.extra["musicbrainz"]["id"], "mbid-1");
+        assert!(enriched.extra["musicbrainz"].get("image").is_none());
+    }
+
+    #[test]
+    fn commons_image_relation_becomes_a_thumbnail_url() {
+        use crate::test_support::{MockResponse, MockServer};
+
+        let server = MockServer::start(|_| {
+            MockResponse::ok(
+                r#"{"relations":[
+                    {"type":"official homepage","url":{"resource":"https://builttospill.com"}},
+                    {"type":"image","url":{"resource":"https://commons.wikimedia.org/wiki/File:BTS_live.jpg"}}
+                ]}"#,
+            )
+        });
+        let image = async_runtime::block_on(artist_image(server.url(), "mbid-ok", 0))
+            .expect("lookup succeeds");
+        assert_eq!(
+            image.as_deref(),
+            Some("https://commons.wikimedia.org/wiki/Special:FilePath/BTS_live.jpg?width=300")
+        );
+        assert!(server.requests()[0]
+            .path
+            .starts_with("/artist/mbid-ok?inc=url-rels"));
+
+        let event = crate::db::sample_event("treefort", "Treefort", chrono::Utc::now());
+        let enriched = apply_profile(event, &profile(), image.as_deref());
+        assert_eq!(enriched.extra["musicbrainz"]["image"], json!(image));
+    }
+
+    #[test]
+    fn genre_overrides_round_trip_and_clear() {
+        let path = std::env::temp_dir().join(format!(
+            "show-scrape-genres-{}/genre_overrides.json",
+            std::process::id()
+        ));
+        let mut overrides = GenreOverrides::default();
+        overrides.set("Alpha", tags(&["shoegaze"]));
+        overrides.set("ALPHA", tags(&["dream pop", " "]));
+        overrides.set("Beta", tags(&["punk"]));
+        overrides.set("beta", Vec::new());
+        overrides.save_to(&path).expect("save");
+
+        let loaded = GenreOverrides::load_from(&path).expect("load");
+        assert_eq!(loaded.0.len(), 1);
+        assert_eq!(
+            loaded.genres_for("alpha"),
+            Some(tags(&["dream pop"]).as_slice())
+        );
+        assert_eq!(loaded.genres_for("Beta"), None);
+        let _ = std::fs::remove_dir_all(path.parent().expect("parent"));
+    }
+}
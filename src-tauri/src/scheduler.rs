@@ -1,3 +1,210 @@
-/// Scheduler intentionally disabled per project overview. All posting actions are
-/// initiated manually from the UI and routed through explicit commands.
-pub fn init() {}
+use std::future::Future;
+
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::config::{AppConfig, QuietHours, SchedulerConfig};
+
+pub const MIN_INTERVAL_MINUTES: u32 = 15;
+pub const MAX_INTERVAL_MINUTES: u32 = 7 * 24 * 60;
+/// How often a disabled scheduler checks whether it was switched on.
+const DISABLED_POLL_MINUTES: u32 = MIN_INTERVAL_MINUTES;
+
+/// Starts the background scrape loop. It only scrapes while the config has it
+/// enabled; posting actions are always initiated manually from the UI.
+pub fn init<F, Fut>(scrape: F)
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    tauri::async_runtime::spawn(async move {
+        let load = || AppConfig::load().map(|c| c.scheduler).unwrap_or_default();
+        loop {
+            run_cycle(&load, tokio::time::sleep, &scrape).await;
+        }
+    });
+}
+
+pub fn validate(config: &SchedulerConfig) -> Result<(), String> {
+    if !(MIN_INTERVAL_MINUTES..=MAX_INTERVAL_MINUTES).contains(&config.interval_minutes) {
+        return Err(format!(
+            "scrape interval must be between {MIN_INTERVAL_MINUTES} minutes and {} days",
+            MAX_INTERVAL_MINUTES / (24 * 60)
+        ));
+    }
+    Ok(())
+}
+
+/// One wait-then-scrape pass. The config is re-read at the start and again
+/// after the wait, so edits apply from the next cycle.
+async fn run_cycle<L, S, SF, T, TF>(load: &L, sleep: S, scrape: &T)
+where
+    L: Fn() -> SchedulerConfig,
+    S: Fn(std::time::Duration) -> SF,
+    SF: Future<Output = ()>,
+    T: Fn() -> TF,
+    TF: Future<Output = ()>,
+{
+    let config = load();
+    let minutes = if config.enabled && validate(&config).is_ok() {
+        config.interval_minutes
+    } else {
+        DISABLED_POLL_MINUTES
+    };
+    sleep(std::time::Duration::from_secs(u64::from(minutes) * 60)).await;
+    if config.enabled && load().enabled {
+        scrape().await;
+    }
+}
+
+/// Moves a scheduled publish time out of the quiet-hours window, to the moment
+/// the window ends. Times outside the window come back unchanged.
+pub fn defer_past_quiet_hours(
+    at: DateTime<Utc>,
+    quiet: &QuietHours,
+) -> Result<DateTime<Utc>, String> {
+    let tz: Tz = quiet
+        .timezone
+        .parse()
+        .map_err(|e| format!("invalid quiet hours timezone {:?}: {e}", quiet.timezone))?;
+    let start = parse_clock(&quiet.start)?;
+    let end = parse_clock(&quiet.end)?;
+    if start == end {
+        return Ok(at);
+    }
+
+    let local = at.with_timezone(&tz);
+    let time = local.time();
+    let (quiet_now, resume_date) = if start < end {
+        (time >= start && time < end, local.date_naive())
+    } else if time >= start {
+        // Overnight window, evening side: it ends tomorrow morning.
+        (true, local.date_naive() + Duration::days(1))
+    } else {
+        (time < end, local.date_naive())
+    };
+    if !quiet_now {
+        return Ok(at);
+    }
+
+    let resume = resume_date.and_time(end);
+    let resume = tz
+        .from_local_datetime(&resume)
+        .earliest()
+        // The end fell in a DST gap; the hour after it always exists.
+        .or_else(|| {
+            tz.from_local_datetime(&(resume + Duration::hours(1)))
+                .earliest()
+        })
+        .ok_or_else(|| format!("quiet hours end {resume} does not exist in {tz}"))?;
+    Ok(resume.with_timezone(&Utc))
+}
+
+fn parse_clock(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|e| format!("invalid quiet hours time {value:?}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    #[test]
+    fn interval_must_be_between_fifteen_minutes_and_a_week() {
+        let config = |interval_minutes| SchedulerConfig {
+            enabled: true,
+            interval_minutes,
+        };
+        assert!(validate(&config(14)).is_err());
+        assert!(validate(&config(15)).is_ok());
+        assert!(validate(&config(7 * 24 * 60)).is_ok());
+        assert!(validate(&config(7 * 24 * 60 + 1)).is_err());
+    }
+
+    #[test]
+    fn each_cycle_uses_the_current_interval() {
+        let current = RefCell::new(SchedulerConfig {
+            enabled: true,
+            interval_minutes: 60,
+        });
+        let waits = RefCell::new(Vec::new());
+        let scrapes = Cell::new(0);
+        let load = || current.borrow().clone();
+        let sleep = |duration: std::time::Duration| {
+            waits.borrow_mut().push(duration.as_secs() / 60);
+            std::future::ready(())
+        };
+        let scrape = || {
+            scrapes.set(scrapes.get() + 1);
+            std::future::ready(())
+        };
+
+        tauri::async_runtime::block_on(async {
+            run_cycle(&load, sleep, &scrape).await;
+            current.borrow_mut().interval_minutes = 20;
+            run_cycle(&load, sleep, &scrape).await;
+            current.borrow_mut().enabled = false;
+            run_cycle(&load, sleep, &scrape).await;
+        });
+
+        assert_eq!(*waits.borrow(), vec![60, 20, DISABLED_POLL_MINUTES as u64]);
+        assert_eq!(scrapes.get(), 2);
+    }
+
+    fn overnight() -> QuietHours {
+        QuietHours {
+            start: "22:00".to_string(),
+            end: "08:00".to_string(),
+            timezone: "America/Boise".to_string(),
+        }
+    }
+
+    #[test]
+    fn posts_inside_quiet_hours_are_deferred_to_the_window_end() {
+        // 3 AM in Boise (MDT, UTC-6).
+        let at = Utc.with_ymd_and_hms(2025, 10, 9, 9, 0, 0).unwrap();
+        let deferred = defer_past_quiet_hours(at, &overnight()).expect("deferred");
+        assert_eq!(
+            deferred,
+            Utc.with_ymd_and_hms(2025, 10, 9, 14, 0, 0).unwrap()
+        );
+
+        // 11 PM the night before rolls over to the next morning.
+        let late = Utc.with_ymd_and_hms(2025, 10, 9, 5, 0, 0).unwrap();
+        let deferred = defer_past_quiet_hours(late, &overnight()).expect("deferred");
+        assert_eq!(
+            deferred,
+            Utc.with_ymd_and_hms(2025, 10, 9, 14, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn posts_outside_quiet_hours_go_immediately() {
+        // Noon in Boise.
+        let at = Utc.with_ymd_and_hms(2025, 10, 9, 18, 0, 0).unwrap();
+        assert_eq!(defer_past_quiet_hours(at, &overnight()), Ok(at));
+
+        let daytime = QuietHours {
+            start: "13:00".to_string(),
+            end: "14:00".to_string(),
+            ..overnight()
+        };
+        assert_eq!(defer_past_quiet_hours(at, &daytime), Ok(at));
+    }
+
+    #[test]
+    fn bad_quiet_hours_settings_are_reported() {
+        let at = Utc::now();
+        let bad_zone = QuietHours {
+            timezone: "Mars/Olympus".to_string(),
+            ..overnight()
+        };
+        assert!(defer_past_quiet_hours(at, &bad_zone).is_err());
+        let bad_time = QuietHours {
+            start: "10pm".to_string(),
+            ..overnight()
+        };
+        assert!(defer_past_quiet_hours(at, &bad_time).is_err());
+    }
+}
@@ -3,49 +3,220 @@ use std::time::Duration;
 use anyhow::{anyhow, Context, Result};
 use chrono::{
     DateTime, Datelike, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
 };
 use chrono_tz::Tz;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use reqwest::blocking::Client;
-use scraper::{ElementRef, Selector};
-use serde_json::{Map, Value};
+use scraper::{ElementRef, Node, Selector};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
 use sha2::{Digest, Sha256};
 
-use crate::models::Event;
+use crate::config::{AppConfig, MissingShowTime};
+use crate::models::{AgeRestriction, Event, LineupRole, LineupSlot};
 
-static TIME_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"(?i)(\d{1,2})(?::(\d{2}))?\s*(am|pm)").expect("valid time regex"));
+/// "with special guests", "support from" and similar lead-ins on opener lines.
+static SUPPORT_LEAD_IN_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?:with\s+)?(?:(?:very\s+)?special\s+guests?|support\s+from|w/)\s*:?\s*")
+        .expect("valid support lead-in regex")
+});
+/// The first "feat.", "ft.", "featuring", "with" or "w/" joining a headliner
+/// to a guest.
+static COLLABORATION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\s(feat\.|ft\.|featuring|with|w/)(?:\s|$)|\sw/")
+        .expect("valid collaboration regex")
+});
+/// A "with" or "w/" that ends a collaboration's guest list and starts the
+/// support acts.
+static SUPPORT_SEPARATOR_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\s(?:with|w/)(?:\s|$)|\sw/").expect("valid support separator regex")
+});
+/// An "&" between two acts. Unspaced ones ("AT&T") belong to the name.
+static AMPERSAND_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\s&\s").expect("valid ampersand regex"));
+static MIN_AGE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(\d{2})\s*(?:\+|and over|& over|and up|& up)").expect("valid age regex")
+});
+const DEFAULT_TIMEOUT_SECS: u64 = 20;
+/// Default for `doors_to_show_minutes`.
+pub const DEFAULT_DOORS_TO_SHOW_MINUTES: u32 = 60;
+/// Default for `max_artists_per_event`.
+pub const DEFAULT_MAX_ARTISTS_PER_EVENT: usize = 12;
+/// Show length assumed when a listing doesn't say how long it runs.
+pub const DEFAULT_DURATION_MINUTES: u32 = 180;
+/// Starts further out than this are almost always a bad year inference.
+const MAX_FUTURE_DAYS: i64 = 730;
+/// Allows same-night listings that started a few hours ago.
+const MAX_PAST_HOURS: i64 = 24;
+/// Start time used for date-only events; see `Event::time_tba`.
+const DATE_ONLY_TIME: &str = "12:00 AM";
 
+/// A standalone clock time. The hour can't continue a longer number and the
+/// meridiem can't run into a word, so "123pm" and "3 amazing" don't match.
+/// Labels glued to the digits ("Doors8pm") still do.
+static TIME_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(?:^|[^\d:])(\d{1,2})(?::(\d{2}))?\s*(am|pm)\b").expect("valid time regex")
+});
+
+/// A two-digit 24-hour "HH:MM" token. Dates like "10/8" or "2025-10-08"
+/// have no colon, and longer numbers or seconds around it rule it out.
+static TIME_24_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:^|[^\d:])([01]\d|2[0-3]):([0-5]\d)(?:$|[^\d:])").expect("valid 24h regex")
+});
+
+static ENTITY_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"&(#[0-9]{1,7}|#[xX][0-9a-fA-F]{1,6}|[A-Za-z][A-Za-z0-9]{1,7});")
+        .expect("valid entity regex")
+});
+/// Named entities that turn up in venue markup. Unknown names are left as-is.
+const NAMED_ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{a0}'),
+    ("ndash", '–'),
+    ("mdash", '—'),
+    ("lsquo", '‘'),
+    ("rsquo", '’'),
+    ("ldquo", '“'),
+    ("rdquo", '”'),
+    ("hellip", '…'),
+    ("middot", '·'),
+    ("bull", '•'),
+    ("copy", '©'),
+    ("reg", '®'),
+    ("trade", '™'),
+    ("deg", '°'),
+    ("szlig", 'ß'),
+    ("eth", 'ð'),
+    ("ETH", 'Ð'),
+    ("thorn", 'þ'),
+    ("THORN", 'Þ'),
+    ("aelig", 'æ'),
+    ("AElig", 'Æ'),
+    ("oslash", 'ø'),
+    ("Oslash", 'Ø'),
+    ("ccedil", 'ç'),
+    ("Ccedil", 'Ç'),
+    ("ntilde", 'ñ'),
+    ("Ntilde", 'Ñ'),
+    ("aacute", 'á'),
+    ("agrave", 'à'),
+    ("acirc", 'â'),
+    ("auml", 'ä'),
+    ("aring", 'å'),
+    ("eacute", 'é'),
+    ("egrave", 'è'),
+    ("ecirc", 'ê'),
+    ("euml", 'ë'),
+    ("iacute", 'í'),
+    ("igrave", 'ì'),
+    ("icirc", 'î'),
+    ("iuml", 'ï'),
+    ("oacute", 'ó'),
+    ("ograve", 'ò'),
+    ("ocirc", 'ô'),
+    ("ouml", 'ö'),
+    ("uacute", 'ú'),
+    ("ugrave", 'ù'),
+    ("ucirc", 'û'),
+    ("uuml", 'ü'),
+    ("yacute", 'ý'),
+    ("yuml", 'ÿ'),
+    ("Aacute", 'Á'),
+    ("Agrave", 'À'),
+    ("Acirc", 'Â'),
+    ("Auml", 'Ä'),
+    ("Aring", 'Å'),
+    ("Eacute", 'É'),
+    ("Egrave", 'È'),
+    ("Ecirc", 'Ê'),
+    ("Euml", 'Ë'),
+    ("Iacute", 'Í'),
+    ("Igrave", 'Ì'),
+    ("Icirc", 'Î'),
+    ("Iuml", 'Ï'),
+    ("Oacute", 'Ó'),
+    ("Ograve", 'Ò'),
+    ("Ocirc", 'Ô'),
+    ("Ouml", 'Ö'),
+    ("Uacute", 'Ú'),
+    ("Ugrave", 'Ù'),
+    ("Ucirc", 'Û'),
+    ("Uuml", 'Ü'),
+    ("Yacute", 'Ý'),
+    ("Yuml", 'Ÿ'),
+    ("atilde", 'ã'),
+    ("otilde", 'õ'),
+    ("Atilde", 'Ã'),
+    ("Otilde", 'Õ'),
+];
+
+/// Decodes numeric and common named HTML entities that survived parsing,
+/// e.g. from double-escaped CMS fields. Runs once, so "&amp;amp;" becomes
+/// "&amp;" rather than "&".
+pub fn decode_entities(input: &str) -> std::borrow::Cow<'_, str> {
+    ENTITY_RE.replace_all(input, |caps: &regex::Captures<'_>| {
+        let body = &caps[1];
+        let decoded = match body.strip_prefix('#') {
+            Some(number) => match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => number.parse().ok(),
+            }
+            .and_then(char::from_u32),
+            None => NAMED_ENTITIES
+                .iter()
+                .find(|(name, _)| *name == body)
+                .map(|(_, ch)| *ch),
+        };
+        decoded.map_or_else(|| caps[0].to_string(), String::from)
+    })
+}
+
+/// Decodes entities, then collapses whitespace. Artist splitting runs on the
+/// result, so an escaped "&amp;" splits like a literal "&".
 pub fn clean_text(input: &str) -> String {
-    input
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join(" ")
-        .trim()
-        .to_string()
+    collapse_whitespace(&decode_entities(input))
+}
+
+fn collapse_whitespace(input: &str) -> String {
+    input.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 pub fn first_text(element: &ElementRef<'_>, selector: &Selector) -> Option<String> {
-    element
-        .select(selector)
-        .next()
-        .map(|node| {
-            let text = inner_text(node);
-            let cleaned = clean_text(&text);
-            if cleaned.is_empty() {
-                None
-            } else {
-                Some(cleaned)
-            }
-        })
-        .flatten()
+    element.select(selector).next().and_then(|node| {
+        let text = inner_text(node);
+        let cleaned = clean_text(&text);
+        if cleaned.is_empty() {
+            None
+        } else {
+            Some(cleaned)
+        }
+    })
 }
 
 pub fn inner_text(element: ElementRef<'_>) -> String {
     clean_text(&element.text().collect::<Vec<_>>().join(" "))
 }
 
+/// Decoded text of `element` with each `<br>` turned into a comma, for names
+/// listed one per line.
+pub fn text_with_breaks(element: ElementRef<'_>) -> String {
+    let mut out = String::new();
+    for node in element.descendants() {
+        match node.value() {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(el) if el.name() == "br" => out.push(','),
+            _ => {}
+        }
+    }
+    out
+}
+
 pub fn first_attr(element: &ElementRef<'_>, selector: &Selector, attr: &str) -> Option<String> {
     element
         .select(selector)
@@ -63,17 +234,129 @@ pub fn absolute_url(base: &str, href: Option<String>) -> Option<String> {
     base_url.join(&href).ok().map(|u| u.to_string())
 }
 
-pub fn fetch_html(url: &str) -> Result<String> {
-    static CLIENT: Lazy<Client> = Lazy::new(|| {
-        Client::builder()
-            .timeout(Duration::from_secs(20))
-            .user_agent("ShowScraper/0.1 (+https://github.com/mike/show-scrape)")
-            .build()
-            .expect("http client")
-    });
+/// Timeout applied to scrape requests unless a venue overrides it. Set
+/// `SHOW_SCRAPER_HTTP_TIMEOUT_SECS` to change it globally.
+pub fn default_request_timeout() -> Duration {
+    std::env::var("SHOW_SCRAPER_HTTP_TIMEOUT_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+}
 
+/// The config a scrape runs with, read once per fetch. Falls back to the
+/// defaults when the file can't be read.
+pub fn scrape_config() -> AppConfig {
+    AppConfig::load().unwrap_or_else(|err| {
+        eprintln!("config load failed, scraping with defaults: {err}");
+        AppConfig::default()
+    })
+}
+
+/// Keeps the first `max` artists in billing order, noting how many were cut
+/// as `extra.artists_truncated`. A zero cap keeps everyone.
+pub fn cap_artists(mut artists: Vec<String>, max: usize, extra: &mut Value) -> Vec<String> {
+    if max == 0 || artists.len() <= max {
+        return artists;
+    }
+    let overflow = artists.len() - max;
+    artists.truncate(max);
+    if let Some(map) = extra.as_object_mut() {
+        map.insert("artists_truncated".to_string(), json!(overflow));
+    }
+    artists
+}
+
+/// Applies the `cap_artists` limit to a lineup. The overflow is already
+/// counted in `extra.artists_truncated`.
+pub fn cap_lineup(mut lineup: Vec<LineupSlot>, max: usize) -> Vec<LineupSlot> {
+    if max > 0 {
+        lineup.truncate(max);
+    }
+    lineup
+}
+
+/// Records `headline` ("Artist feat. Guest") as the event's billed name and
+/// bills the guests as such, when the venue's rules split on that phrase.
+/// The artists themselves stay separate for enrichment.
+pub fn keep_collaboration(event: &mut Event, headline: &str, rules: &ArtistSplit) {
+    let billed = clean_text(headline);
+    let Some(caps) = COLLABORATION_RE.captures(&billed) else {
+        return;
+    };
+    let phrase = caps.get(1).map_or("w/", |m| m.as_str()).to_lowercase();
+    let splits = if phrase == "with" || phrase == "w/" {
+        rules.with
+    } else {
+        rules.featuring
+    };
+    let after = caps.get(0).map_or(billed.len(), |m| m.end());
+    // "Artist feat. Guest w/ Opener": the guests end where the support starts.
+    let end = SUPPORT_SEPARATOR_RE
+        .find(&billed[after..])
+        .map_or(billed.len(), |m| after + m.start());
+    let guests: Vec<String> = split_artists_with(&billed[after..end], rules)
+        .iter()
+        .map(|name| name.to_lowercase())
+        .collect();
+    if !splits || guests.is_empty() {
+        return;
+    }
+    for slot in &mut event.lineup {
+        if guests.contains(&slot.name.to_lowercase()) {
+            slot.role = LineupRole::Guest;
+        }
+    }
+    event.billed_as = Some(billed[..end].trim().to_string());
+}
+
+/// `fuzzy_name_key`s of the acts named in a billed title, split on the
+/// collaboration phrases and, separately, on every separator, so both
+/// "Simon & Garfunkel" and "Simon" are found in "Simon & Garfunkel feat. X".
+pub fn billed_act_keys(billed: &str) -> Vec<String> {
+    let phrases_only = ArtistSplit {
+        slash: false,
+        ampersand: false,
+        plus: false,
+        with: true,
+        featuring: true,
+    };
+    let mut keys: Vec<String> = Vec::new();
+    for name in split_artists_with(billed, &phrases_only)
+        .into_iter()
+        .chain(split_artists(billed))
+    {
+        let key = fuzzy_name_key(&name);
+        if !key.is_empty() && !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Resolves the time to build a start from. Returns the parsed time when
+/// present, else the configured default; the flag is true when the event
+/// should stay date-only.
+pub fn show_time_or_default(parsed: Option<String>, missing: &MissingShowTime) -> (String, bool) {
+    match (parsed, missing) {
+        (Some(time), _) => (time, false),
+        (None, MissingShowTime::At(time)) => (time.clone(), false),
+        (None, MissingShowTime::Tba) => (DATE_ONLY_TIME.to_string(), true),
+    }
+}
+
+static CLIENT: Lazy<Client> = Lazy::new(|| {
+    Client::builder()
+        .user_agent("ShowScraper/0.1 (+https://github.com/mike/show-scrape)")
+        .build()
+        .expect("http client")
+});
+
+pub fn fetch_html(url: &str, timeout: Duration) -> Result<String> {
     let response = CLIENT
         .get(url)
+        .timeout(timeout)
         .send()
         .with_context(|| format!("request failed for {url}"))?;
     let response = response
@@ -84,42 +367,139 @@ pub fn fetch_html(url: &str) -> Result<String> {
         .with_context(|| format!("unable to read response body for {url}"))
 }
 
+/// `fetch_html` for JSON APIs: same client and error context, with the body
+/// deserialized into `T`.
+pub fn fetch_json<T: DeserializeOwned>(url: &str, timeout: Duration) -> Result<T> {
+    let body = CLIENT
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .timeout(timeout)
+        .send()
+        .with_context(|| format!("request failed for {url}"))?
+        .error_for_status()
+        .with_context(|| format!("non-success status for {url}"))?
+        .text()
+        .with_context(|| format!("unable to read response body for {url}"))?;
+    serde_json::from_str(&body).with_context(|| format!("unexpected JSON from {url}"))
+}
+
+/// Listing pages followed per scrape, including the first.
+pub const MAX_PAGES: usize = 5;
+
+static NEXT_PAGE_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("a[rel='next'], a.next.page-numbers, .tw-pagination a.next")
+        .expect("next page selector")
+});
+
+/// Absolute URL of the page after this one, if the listing is paginated.
+pub fn next_page_url(html: &str, current_url: &str) -> Option<String> {
+    let document = scraper::Html::parse_document(html);
+    let href = document
+        .select(&NEXT_PAGE_SELECTOR)
+        .next()
+        .and_then(|link| link.value().attr("href"))
+        .map(str::to_string)?;
+    absolute_url(current_url, Some(href))
+}
+
+/// Fetches `url` and follows "next" links, up to `MAX_PAGES` pages. A failure
+/// after the first page ends pagination but keeps the pages already fetched.
+pub fn fetch_pages(url: &str, timeout: Duration) -> Result<Vec<String>> {
+    let mut pages = vec![fetch_html(url, timeout)?];
+    let mut visited = vec![url.to_string()];
+    while pages.len() < MAX_PAGES {
+        let current = visited.last().expect("visited page");
+        let next = match next_page_url(pages.last().expect("fetched page"), current) {
+            Some(next) if !visited.contains(&next) => next,
+            _ => break,
+        };
+        match fetch_html(&next, timeout) {
+            Ok(html) => pages.push(html),
+            Err(err) => {
+                eprintln!("stopping pagination at {next}: {err:#}");
+                break;
+            }
+        }
+        visited.push(next);
+    }
+    Ok(pages)
+}
+
+/// Which separators `split_artists_with` treats as a break between acts.
+/// Commas always split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArtistSplit {
+    pub slash: bool,
+    /// Only a spaced " & "; "AT&T" stays one name.
+    pub ampersand: bool,
+    pub plus: bool,
+    /// "w/" and "with".
+    pub with: bool,
+    /// "feat.", "ft." and "featuring". Turn off for venues where the guest
+    /// belongs in the act's name.
+    pub featuring: bool,
+}
+
+impl Default for ArtistSplit {
+    fn default() -> Self {
+        Self {
+            slash: true,
+            ampersand: true,
+            plus: true,
+            with: true,
+            featuring: true,
+        }
+    }
+}
+
 pub fn split_artists(text: &str) -> Vec<String> {
+    split_artists_with(text, &ArtistSplit::default())
+}
+
+pub fn split_artists_with(text: &str, rules: &ArtistSplit) -> Vec<String> {
     if text.trim().is_empty() {
         return Vec::new();
     }
-    let mut normalized = text.to_string();
-    for ch in [',', '/', '&', '+'] {
-        normalized = normalized.replace(ch, ",");
+    // Decode first so an escaped "&amp;" can't leave "amp;" behind.
+    let mut normalized = decode_entities(text).into_owned();
+    // Word separators first, so "w/" isn't consumed by the slash rule.
+    if rules.with {
+        normalized = normalized.replace(" w/", ",");
+        normalized = normalized.replace(" with ", ",");
+        normalized = normalized.replace(" With ", ",");
+    }
+    if rules.featuring {
+        normalized = normalized.replace(" feat. ", ",");
+        normalized = normalized.replace(" ft. ", ",");
+        normalized = normalized.replace(" featuring ", ",");
+        normalized = normalized.replace(" Featuring ", ",");
+    }
+    if rules.ampersand {
+        normalized = AMPERSAND_RE.replace_all(&normalized, ",").into_owned();
+    }
+    for (ch, enabled) in [('/', rules.slash), ('+', rules.plus)] {
+        if enabled {
+            normalized = normalized.replace(ch, ",");
+        }
     }
-    normalized = normalized.replace(" w/", ",");
-    normalized = normalized.replace(" with ", ",");
-    normalized = normalized.replace(" With ", ",");
-    normalized = normalized.replace(" feat. ", ",");
-    normalized = normalized.replace(" ft. ", ",");
-    normalized = normalized.replace(" featuring ", ",");
-    normalized = normalized.replace(" Featuring ", ",");
 
     normalized
         .split(',')
-        .map(|s| clean_text(s))
+        .map(collapse_whitespace)
         .filter(|s| !s.is_empty())
         .collect::<Vec<_>>()
 }
 
-pub fn parse_age_flag(text: &str) -> Option<bool> {
+pub fn parse_age_restriction(text: &str) -> AgeRestriction {
     let lower = text.to_lowercase();
     if lower.contains("all ages") || lower.contains("all-ages") {
-        Some(true)
-    } else if lower.contains("21+")
-        || lower.contains("18+")
-        || lower.contains("21 and over")
-        || lower.contains("21 & over")
-    {
-        Some(false)
-    } else {
-        None
+        return AgeRestriction::AllAges;
     }
+    MIN_AGE_RE
+        .captures(&lower)
+        .and_then(|caps| caps.get(1)?.as_str().parse::<u8>().ok())
+        .map(AgeRestriction::MinAge)
+        .unwrap_or(AgeRestriction::Unknown)
 }
 
 pub fn parse_datetime(date_text: &str, time_text: Option<&str>, tz: Tz) -> Option<DateTime<Tz>> {
@@ -148,17 +528,35 @@ pub fn parse_named_time(text: &str, keyword: &str) -> Option<String> {
     None
 }
 
+/// First clock time in `text` as "HH:MM AM/PM", from either a 12-hour
+/// ("7:30 pm") or a 24-hour ("19:30") token, whichever comes first.
 pub fn find_first_time(text: &str) -> Option<String> {
     let cleaned = clean_text(text);
-    TIME_RE.captures(&cleaned).map(|caps| {
+    let twelve = TIME_RE.captures(&cleaned).map(|caps| {
         let hour = caps.get(1).unwrap().as_str().parse::<u32>().unwrap_or(0);
         let minute = caps
             .get(2)
             .map(|m| m.as_str().parse::<u32>().unwrap_or(0))
             .unwrap_or(0);
         let period = caps.get(3).unwrap().as_str().to_uppercase();
-        format!("{:02}:{:02} {}", hour, minute, period)
-    })
+        (
+            caps.get(1).unwrap().start(),
+            format!("{:02}:{:02} {}", hour, minute, period),
+        )
+    });
+    let twenty_four = TIME_24_RE.captures(&cleaned).and_then(|caps| {
+        let time = NaiveTime::from_hms_opt(caps[1].parse().ok()?, caps[2].parse().ok()?, 0)?;
+        Some((
+            caps.get(1).unwrap().start(),
+            time.format("%I:%M %p").to_string(),
+        ))
+    });
+    // On a tie the 12-hour reading wins: "10:00 pm" is not 10 AM.
+    match (twelve, twenty_four) {
+        (Some(a), Some(b)) if b.0 < a.0 => Some(b.1),
+        (Some(a), _) => Some(a.1),
+        (None, b) => b.map(|(_, time)| time),
+    }
 }
 
 pub fn combine_with_date(reference: &DateTime<Tz>, time_str: &str, tz: Tz) -> Option<String> {
@@ -166,30 +564,197 @@ pub fn combine_with_date(reference: &DateTime<Tz>, time_str: &str, tz: Tz) -> Op
     to_timezone_datetime(reference.date_naive(), time, tz).map(|dt| dt.to_rfc3339())
 }
 
+/// Stable event id: SHA-256 of `venue_id|start_utc|headliner`, where the
+/// headliner is the first non-blank artist with whitespace collapsed and case
+/// folded. Support acts are deliberately left out so the id survives openers
+/// being added, dropped, or reordered between scrapes; only a change of
+/// venue, start time, or headliner yields a new event.
+pub fn event_id(venue_id: &str, start_utc: &DateTime<Utc>, artists: &[String]) -> String {
+    let headliner = artists
+        .iter()
+        .map(|artist| normalize_artist_key(artist))
+        .find(|artist| !artist.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    let mut hasher = Sha256::new();
+    hasher.update(venue_id.as_bytes());
+    hasher.update(b"|");
+    hasher.update(start_utc.to_rfc3339().as_bytes());
+    hasher.update(b"|");
+    hasher.update(headliner.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Ties the occurrences of a weekly listing together. Built from what stays
+/// put week to week, so the id survives the first listed date moving on.
+pub fn recurrence_id(venue_id: &str, weekday: Weekday, artists: &[String]) -> String {
+    let headliner = artists
+        .iter()
+        .map(|artist| normalize_artist_key(artist))
+        .find(|artist| !artist.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    let mut hasher = Sha256::new();
+    hasher.update(b"weekly|");
+    hasher.update(venue_id.as_bytes());
+    hasher.update(b"|");
+    hasher.update(weekday.to_string().as_bytes());
+    hasher.update(b"|");
+    hasher.update(headliner.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn normalize_artist_key(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Opener names from a card's separate support element, without its
+/// "with special guests" style lead-in.
+pub fn support_acts(text: &str, rules: &ArtistSplit) -> Vec<String> {
+    split_artists_with(&SUPPORT_LEAD_IN_RE.replace(text, ""), rules)
+}
+
+/// Headliners then support acts as lineup slots, each artist billed once at
+/// its first (highest) slot.
+pub fn billed_lineup(headliners: &[String], support: &[String]) -> Vec<LineupSlot> {
+    dedupe_by_artist(
+        headliners
+            .iter()
+            .map(|name| (name, LineupRole::Headliner))
+            .chain(support.iter().map(|name| (name, LineupRole::Support)))
+            .map(|(name, role)| LineupSlot {
+                name: titlecase_if_allcaps(name),
+                role,
+            })
+            .collect(),
+        |slot| &slot.name,
+    )
+}
+
+/// All-caps names that are meant that way and must not be title-cased.
+const ALLCAPS_ARTISTS: &[&str] = &["MF DOOM", "ODESZA", "MGMT", "AC/DC", "SZA", "HAIM", "PUP"];
+/// Single-word all-caps names this short are treated as acronyms.
+const ACRONYM_MAX_LEN: usize = 3;
+
+/// Title-cases a name only when every letter in it is uppercase, e.g.
+/// "THE BLACK KEYS" becomes "The Black Keys". Known stylized names and short
+/// single-word acronyms are left alone, as is anything already mixed-case.
+pub fn titlecase_if_allcaps(name: &str) -> String {
+    let trimmed = name.trim();
+    let mut letters = trimmed.chars().filter(|c| c.is_alphabetic()).peekable();
+    if letters.peek().is_none() || letters.any(|c| c.is_lowercase()) {
+        return name.to_string();
+    }
+    if ALLCAPS_ARTISTS
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(trimmed))
+    {
+        return name.to_string();
+    }
+    if !trimmed.contains(char::is_whitespace)
+        && trimmed.chars().filter(|c| c.is_alphabetic()).count() <= ACRONYM_MAX_LEN
+    {
+        return name.to_string();
+    }
+
+    let mut out = String::with_capacity(name.len());
+    let mut previous: Option<char> = None;
+    for ch in name.chars() {
+        let word_start = previous.is_none_or(|p| !p.is_alphanumeric() && p != '\'');
+        if word_start {
+            out.extend(ch.to_uppercase());
+        } else {
+            out.extend(ch.to_lowercase());
+        }
+        previous = Some(ch);
+    }
+    out
+}
+
+static PARENTHETICAL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\([^)]*\)|\[[^\]]*\]").expect("parenthetical regex"));
+
+/// Artist name reduced for fuzzy comparison: lowercase, without bracketed
+/// asides like "(Live)" or "[DJ Set]", punctuation dropped.
+pub fn fuzzy_name_key(name: &str) -> String {
+    let stripped = PARENTHETICAL_RE.replace_all(name, " ");
+    stripped
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Levenshtein ratio of two names after `fuzzy_name_key`: 1.0 when they
+/// normalize to the same text, 0.0 when nothing lines up.
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = fuzzy_name_key(a).chars().collect();
+    let b: Vec<char> = fuzzy_name_key(b).chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+/// Drops repeat artists, comparing case- and whitespace-insensitively and
+/// keeping the first occurrence in billing order.
+pub fn dedupe_artists(artists: Vec<String>) -> Vec<String> {
+    dedupe_by_artist(artists, |name| name)
+}
+
+/// `dedupe_artists` for anything carrying an artist name, e.g. lineup slots.
+pub fn dedupe_by_artist<T>(items: Vec<T>, name: impl Fn(&T) -> &str) -> Vec<T> {
+    let mut seen = std::collections::HashSet::new();
+    items
+        .into_iter()
+        .filter(|item| {
+            let key = normalize_artist_key(name(item));
+            !key.is_empty() && seen.insert(key)
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn build_event(
     venue_id: &str,
     venue_name: &str,
     venue_url: &str,
     start_local: DateTime<Tz>,
     artists: Vec<String>,
+    max_artists: usize,
     ticket_url: Option<String>,
     event_url: Option<String>,
-    is_all_ages: Option<bool>,
+    age_restriction: AgeRestriction,
     doors_local: Option<String>,
-    extra: Value,
+    mut extra: Value,
 ) -> Event {
     let start_utc = start_local.with_timezone(&Utc);
-    let headliner = artists
-        .first()
-        .cloned()
-        .unwrap_or_else(|| "unknown".to_string());
-    let mut hasher = Sha256::new();
-    hasher.update(venue_id.as_bytes());
-    hasher.update(b"|");
-    hasher.update(start_utc.to_rfc3339().as_bytes());
-    hasher.update(b"|");
-    hasher.update(headliner.as_bytes());
-    let id = format!("{:x}", hasher.finalize());
+    let artists = cap_artists(dedupe_artists(artists), max_artists, &mut extra);
+    let originals = artists.clone();
+    let artists: Vec<String> = artists
+        .iter()
+        .map(|name| titlecase_if_allcaps(name))
+        .collect();
+    if artists != originals {
+        if let Some(map) = extra.as_object_mut() {
+            map.insert("original_artists".to_string(), json!(originals));
+        }
+    }
+    let id = event_id(venue_id, &start_utc, &artists);
     let event_url = event_url.or_else(|| ticket_url.clone());
 
     Event {
@@ -200,9 +765,13 @@ pub fn build_event(
         venue_url: Some(venue_url.to_string()),
         start_local: Some(start_local.to_rfc3339()),
         start_utc: start_utc.to_rfc3339(),
+        time_tba: false,
+        duration_minutes: Some(DEFAULT_DURATION_MINUTES),
         doors_local,
         artists,
-        is_all_ages,
+        billed_as: None,
+        lineup: Vec::new(),
+        age_restriction,
         ticket_url,
         event_url,
         price_min_cents: None,
@@ -211,11 +780,30 @@ pub fn build_event(
         tags: Vec::new(),
         scraped_at_utc: Utc::now().to_rfc3339(),
         extra,
+        note: None,
+    }
+}
+
+/// Reason an event's start is implausible, or `None` when it looks sane.
+pub fn implausible_start(event: &Event, now: DateTime<Utc>) -> Option<String> {
+    let start = match DateTime::parse_from_rfc3339(&event.start_utc) {
+        Ok(dt) => dt.with_timezone(&Utc),
+        Err(_) => return Some(format!("unparseable start {:?}", event.start_utc)),
+    };
+    if start > now + chrono::Duration::days(MAX_FUTURE_DAYS) {
+        return Some(format!(
+            "start {} is more than {MAX_FUTURE_DAYS} days out",
+            event.start_utc
+        ));
+    }
+    if start < now - chrono::Duration::hours(MAX_PAST_HOURS) {
+        return Some(format!("start {} is already past", event.start_utc));
     }
+    None
 }
 
 fn parse_time_candidates(primary: Option<&str>, others: &[&str]) -> Option<NaiveTime> {
-    if let Some(value) = primary.and_then(|text| parse_naive_time_str(text)) {
+    if let Some(value) = primary.and_then(parse_naive_time_str) {
         return Some(value);
     }
     for text in others {
@@ -276,6 +864,85 @@ fn to_timezone_datetime(date: NaiveDate, time: NaiveTime, tz: Tz) -> Option<Date
     }
 }
 
+/// Weeks of dated occurrences generated for a weekly listing.
+pub const RECURRENCE_WEEKS: usize = 8;
+
+static WEEKLY_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\bevery\s+(mon|tues|wednes|thurs|fri|satur|sun)day\b")
+        .expect("valid recurrence regex")
+});
+
+/// Weekday of a "Every Thursday"-style recurring listing.
+pub fn weekly_recurrence(text: &str) -> Option<Weekday> {
+    let caps = WEEKLY_RE.captures(text)?;
+    format!("{}day", caps.get(1)?.as_str()).parse().ok()
+}
+
+/// First `weekday` at `time_text` that falls on or after today in `tz`.
+pub fn next_weekly_start(
+    weekday: Weekday,
+    time_text: &str,
+    tz: Tz,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Tz>> {
+    let today = now.with_timezone(&tz).date_naive();
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    let date = today + chrono::Duration::days(i64::from(ahead));
+    let time = parse_time_candidates(Some(time_text), &[])?;
+    to_timezone_datetime(date, time, tz)
+}
+
+/// Expands the first occurrence of a weekly event into `weeks` dated events
+/// at the same wall-clock time. Each gets its own id; `extra.recurrence_id`
+/// (see [`recurrence_id`]) ties them together.
+pub fn weekly_occurrences(first: &Event, weeks: usize, tz: Tz) -> Vec<Event> {
+    let Some(start) = first
+        .start_local
+        .as_deref()
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+    else {
+        return vec![first.clone()];
+    };
+    let doors = first
+        .doors_local
+        .as_deref()
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok());
+    let shift = |dt: &DateTime<chrono::FixedOffset>, week: usize| {
+        let date = dt.date_naive() + chrono::Duration::weeks(week as i64);
+        to_timezone_datetime(date, dt.time(), tz)
+    };
+
+    let mut extra = match &first.extra {
+        Value::Object(map) => map.clone(),
+        _ => Map::new(),
+    };
+    extra.insert(
+        "recurrence_id".to_string(),
+        Value::String(recurrence_id(
+            &first.venue_id,
+            start.weekday(),
+            &first.artists,
+        )),
+    );
+
+    (0..weeks)
+        .filter_map(|week| {
+            let start_local = shift(&start, week)?;
+            let start_utc = start_local.with_timezone(&Utc);
+            let mut event = first.clone();
+            event.id = event_id(&first.venue_id, &start_utc, &first.artists);
+            event.start_local = Some(start_local.to_rfc3339());
+            event.start_utc = start_utc.to_rfc3339();
+            event.doors_local = doors
+                .as_ref()
+                .and_then(|dt| shift(dt, week))
+                .map(|dt| dt.to_rfc3339());
+            event.extra = Value::Object(extra.clone());
+            Some(event)
+        })
+        .collect()
+}
+
 pub fn empty_extra() -> Value {
     Value::Object(Map::new())
 }
@@ -291,3 +958,478 @@ pub fn fail_if_empty<T>(venue_id: &str, events: Vec<T>) -> Result<Vec<T>> {
         Ok(events)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    #[test]
+    fn name_similarity_ignores_case_and_bracketed_asides() {
+        assert_eq!(name_similarity("ODESZA", "Odesza (Live)"), 1.0);
+        assert_eq!(name_similarity("Sylvan Esso", "Sylvan Esso [DJ Set]"), 1.0);
+        assert!(name_similarity("Beach House", "Beach Houses") > 0.9);
+        assert!(name_similarity("PUP", "Pool Kids") < 0.5);
+        assert_eq!(name_similarity("", "(Live)"), 0.0);
+    }
+
+    #[test]
+    fn far_future_and_past_starts_are_implausible() {
+        let now = Utc.with_ymd_and_hms(2025, 10, 1, 12, 0, 0).unwrap();
+        let mut event = crate::db::sample_event("treefort", "Treefort", now);
+
+        event.start_utc = "2099-10-09T02:00:00+00:00".to_string();
+        let reason = implausible_start(&event, now).expect("2099 rejected");
+        assert!(reason.contains("days out"), "{reason}");
+
+        event.start_utc = "2025-09-28T02:00:00+00:00".to_string();
+        assert!(implausible_start(&event, now).is_some());
+
+        event.start_utc = "2025-10-09T02:00:00+00:00".to_string();
+        assert_eq!(implausible_start(&event, now), None);
+        event.start_utc = "2025-10-01T03:00:00+00:00".to_string();
+        assert_eq!(implausible_start(&event, now), None);
+    }
+
+    #[test]
+    fn pagination_follows_next_links_and_stops_at_the_last_page() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/shows/" => {
+                MockResponse::ok(r#"<a class="next page-numbers" href="/shows/page/2/">Next</a>"#)
+            }
+            "/shows/page/2/" => {
+                MockResponse::ok(r#"<a rel="next" href="/shows/">Back to start</a>"#)
+            }
+            _ => MockResponse::status(404, "missing"),
+        });
+        let pages = fetch_pages(&format!("{}/shows/", server.url()), Duration::from_secs(5))
+            .expect("pages");
+        assert_eq!(pages.len(), 2);
+        assert_eq!(server.requests().len(), 2);
+
+        assert_eq!(
+            next_page_url(
+                r#"<a rel="next" href="?page=3">Next</a>"#,
+                "https://example.com/shows/?page=2"
+            )
+            .as_deref(),
+            Some("https://example.com/shows/?page=3")
+        );
+        assert_eq!(
+            next_page_url("<p>no pager</p>", "https://example.com/"),
+            None
+        );
+    }
+
+    #[test]
+    fn fetch_html_reports_timeout_as_error() {
+        let server = MockServer::start(|_| {
+            std::thread::sleep(Duration::from_secs(2));
+            MockResponse::ok("<html></html>")
+        });
+
+        let err = fetch_html(server.url(), Duration::from_millis(100))
+            .expect_err("slow response should time out");
+        let timed_out = err
+            .chain()
+            .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+            .any(|cause| cause.is_timeout());
+        assert!(timed_out, "expected a timeout error, got {err:#}");
+    }
+
+    #[test]
+    fn show_time_or_default_prefers_parsed_then_configured_time() {
+        let at_nine = MissingShowTime::At("9:00 PM".to_string());
+        assert_eq!(
+            show_time_or_default(Some("08:00 PM".to_string()), &at_nine),
+            ("08:00 PM".to_string(), false)
+        );
+        assert_eq!(
+            show_time_or_default(None, &at_nine),
+            ("9:00 PM".to_string(), false)
+        );
+        assert_eq!(
+            show_time_or_default(None, &MissingShowTime::Tba),
+            (DATE_ONLY_TIME.to_string(), true)
+        );
+    }
+
+    fn artists(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn split_rules_can_keep_ampersands_and_featured_guests() {
+        let text = "Mumford & Sons, Band feat. Guest w/ Opener";
+        assert_eq!(
+            split_artists(text),
+            vec!["Mumford", "Sons", "Band", "Guest", "Opener"]
+        );
+
+        let rules = ArtistSplit {
+            ampersand: false,
+            featuring: false,
+            ..ArtistSplit::default()
+        };
+        assert_eq!(
+            split_artists_with(text, &rules),
+            vec!["Mumford & Sons", "Band feat. Guest", "Opener"]
+        );
+    }
+
+    #[test]
+    fn twenty_four_hour_times_are_recognized() {
+        assert_eq!(find_first_time("Show 20:00").as_deref(), Some("08:00 PM"));
+        assert_eq!(
+            find_first_time("Doors 19:30 / Show 21:00").as_deref(),
+            Some("07:30 PM")
+        );
+        assert_eq!(find_first_time("7:30 PM").as_deref(), Some("07:30 PM"));
+        assert_eq!(find_first_time("10:00 pm").as_deref(), Some("10:00 PM"));
+        assert_eq!(
+            find_first_time("Doors 18:00, show 8pm").as_deref(),
+            Some("06:00 PM")
+        );
+        assert_eq!(find_first_time("Wed 10/8 and 2025-10-08"), None);
+        assert_eq!(find_first_time("Runtime 1:45:00"), None);
+        assert_eq!(find_first_time("Score 25:61"), None);
+    }
+
+    #[test]
+    fn times_only_match_standalone_tokens() {
+        assert_eq!(find_first_time("Doors8pm").as_deref(), Some("08:00 PM"));
+        assert_eq!(
+            find_first_time("Show: 7:30 PM").as_deref(),
+            Some("07:30 PM")
+        );
+        assert_eq!(find_first_time("spam 3"), None);
+        assert_eq!(find_first_time("3 amazing bands"), None);
+        assert_eq!(find_first_time("Room 123pm"), None);
+        assert_eq!(find_first_time("10pmish, 9pm").as_deref(), Some("09:00 PM"));
+    }
+
+    #[test]
+    fn entities_are_decoded_before_whitespace_cleanup() {
+        assert_eq!(clean_text("Sigur R&oacute;s"), "Sigur Rós");
+        assert_eq!(clean_text("AT&amp;T&nbsp;&nbsp;Showcase"), "AT&T Showcase");
+        assert_eq!(
+            clean_text("Mot&#246;rhead &#x2013; live"),
+            "Motörhead – live"
+        );
+        assert_eq!(
+            clean_text("&amp;amp; &bogus; &#xD800;"),
+            "&amp; &bogus; &#xD800;"
+        );
+    }
+
+    #[test]
+    fn decoded_ampersands_split_artists() {
+        let text = clean_text("Sigur R&oacute;s &amp; Friends");
+        assert_eq!(split_artists(&text), vec!["Sigur Rós", "Friends"]);
+    }
+
+    #[test]
+    fn only_spaced_ampersands_split_artists() {
+        assert_eq!(split_artists("Tom &amp; Jerry"), vec!["Tom", "Jerry"]);
+        assert_eq!(
+            split_artists("AT&amp;T Showcase & Simon&Garfunkel"),
+            vec!["AT&T Showcase", "Simon&Garfunkel"]
+        );
+    }
+
+    #[test]
+    fn support_lead_ins_are_stripped() {
+        let rules = ArtistSplit::default();
+        assert_eq!(
+            support_acts("with special guests Chase Petra & Pool Kids", &rules),
+            vec!["Chase Petra", "Pool Kids"]
+        );
+        assert_eq!(
+            support_acts("Support from: Mannequin Pussy", &rules),
+            vec!["Mannequin Pussy"]
+        );
+        assert_eq!(support_acts("Guest Band", &rules), vec!["Guest Band"]);
+    }
+
+    #[test]
+    fn allcaps_names_are_title_cased() {
+        assert_eq!(titlecase_if_allcaps("THE BLACK KEYS"), "The Black Keys");
+        assert_eq!(titlecase_if_allcaps("JINJER"), "Jinjer");
+        assert_eq!(titlecase_if_allcaps("GUNS N' ROSES"), "Guns N' Roses");
+        assert_eq!(titlecase_if_allcaps("JAY-Z"), "Jay-Z");
+        assert_eq!(titlecase_if_allcaps("SkyDxddy"), "SkyDxddy");
+    }
+
+    #[test]
+    fn intentional_allcaps_names_are_kept() {
+        assert_eq!(titlecase_if_allcaps("MF DOOM"), "MF DOOM");
+        assert_eq!(titlecase_if_allcaps("ODESZA"), "ODESZA");
+        assert_eq!(titlecase_if_allcaps("Odesza"), "Odesza");
+        assert_eq!(titlecase_if_allcaps("TLC"), "TLC");
+        assert_eq!(titlecase_if_allcaps("311"), "311");
+    }
+
+    #[test]
+    fn build_event_keeps_original_allcaps_names_in_extra() {
+        let start = chrono_tz::America::Boise
+            .with_ymd_and_hms(2030, 5, 1, 20, 0, 0)
+            .unwrap();
+        let event = build_event(
+            "venue",
+            "Venue",
+            "https://venue.example",
+            start,
+            vec!["THE BLACK KEYS".to_string(), "ODESZA".to_string()],
+            DEFAULT_MAX_ARTISTS_PER_EVENT,
+            None,
+            None,
+            AgeRestriction::AllAges,
+            None,
+            json!({}),
+        );
+        assert_eq!(event.artists, vec!["The Black Keys", "ODESZA"]);
+        assert_eq!(
+            event.extra["original_artists"],
+            json!(["THE BLACK KEYS", "ODESZA"])
+        );
+    }
+
+    #[test]
+    fn recurrence_id_survives_the_first_date_moving_on() {
+        let tz = chrono_tz::America::Boise;
+        let weekly = |day: u32| {
+            let first = build_event(
+                "venue",
+                "Venue",
+                "https://venue.example",
+                tz.with_ymd_and_hms(2030, 5, day, 22, 0, 0).unwrap(),
+                vec!["Friday Night Funk".to_string()],
+                DEFAULT_MAX_ARTISTS_PER_EVENT,
+                None,
+                None,
+                AgeRestriction::AllAges,
+                None,
+                json!({}),
+            );
+            weekly_occurrences(&first, RECURRENCE_WEEKS, tz)
+        };
+        let this_week = weekly(3);
+        let next_week = weekly(10);
+
+        assert_ne!(this_week[0].id, next_week[0].id);
+        assert_eq!(this_week[1].id, next_week[0].id);
+        assert_eq!(
+            this_week[0].extra["recurrence_id"],
+            next_week[0].extra["recurrence_id"]
+        );
+        assert!(next_week
+            .iter()
+            .all(|event| event.extra["recurrence_id"] == this_week[0].extra["recurrence_id"]));
+    }
+
+    fn collaboration_event(headline: &str, support: &[&str]) -> Event {
+        let start = chrono_tz::America::Boise
+            .with_ymd_and_hms(2030, 5, 1, 20, 0, 0)
+            .unwrap();
+        let rules = ArtistSplit::default();
+        let headliners = split_artists_with(headline, &rules);
+        let support: Vec<String> = support.iter().map(|name| name.to_string()).collect();
+        let mut event = build_event(
+            "venue",
+            "Venue",
+            "https://venue.example",
+            start,
+            headliners.iter().chain(&support).cloned().collect(),
+            DEFAULT_MAX_ARTISTS_PER_EVENT,
+            None,
+            None,
+            AgeRestriction::AllAges,
+            None,
+            json!({}),
+        );
+        event.lineup = billed_lineup(&headliners, &support);
+        keep_collaboration(&mut event, headline, &rules);
+        event
+    }
+
+    #[test]
+    fn kept_collaboration_displays_as_billed_with_both_in_lineup() {
+        let event = collaboration_event("Artist feat. Guest", &["Opener"]);
+        assert_eq!(event.billed_as.as_deref(), Some("Artist feat. Guest"));
+        assert_eq!(event.artists, vec!["Artist", "Guest", "Opener"]);
+        assert_eq!(
+            event.lineup,
+            vec![
+                LineupSlot {
+                    name: "Artist".to_string(),
+                    role: LineupRole::Headliner,
+                },
+                LineupSlot {
+                    name: "Guest".to_string(),
+                    role: LineupRole::Guest,
+                },
+                LineupSlot {
+                    name: "Opener".to_string(),
+                    role: LineupRole::Support,
+                },
+            ]
+        );
+        assert_eq!(event.title(), "Artist");
+        assert_eq!(
+            event.title_with_support(crate::models::TitleMode::Names),
+            "Artist feat. Guest, Opener"
+        );
+
+        let with = collaboration_event("Artist with Guest", &[]);
+        assert_eq!(with.billed_as.as_deref(), Some("Artist with Guest"));
+        assert_eq!(
+            with.title_with_support(crate::models::TitleMode::Count),
+            "Artist with Guest"
+        );
+    }
+
+    #[test]
+    fn support_after_a_featured_guest_is_not_billed_as_a_guest() {
+        let event = collaboration_event("Artist feat. Guest w/ Opener", &[]);
+        assert_eq!(event.billed_as.as_deref(), Some("Artist feat. Guest"));
+        let roles: Vec<(&str, LineupRole)> = event
+            .lineup
+            .iter()
+            .map(|slot| (slot.name.as_str(), slot.role))
+            .collect();
+        assert_eq!(
+            roles,
+            vec![
+                ("Artist", LineupRole::Headliner),
+                ("Guest", LineupRole::Guest),
+                ("Opener", LineupRole::Headliner),
+            ]
+        );
+        assert_eq!(
+            event.title_with_support(crate::models::TitleMode::Names),
+            "Artist feat. Guest, Opener"
+        );
+    }
+
+    #[test]
+    fn lines_without_a_collaboration_are_left_alone() {
+        let event = collaboration_event("Mumford & Sons", &[]);
+        assert_eq!(event.billed_as, None);
+        assert!(event
+            .lineup
+            .iter()
+            .all(|slot| slot.role == LineupRole::Headliner));
+    }
+
+    #[test]
+    fn absurd_splits_are_capped_after_the_headliner() {
+        let start = chrono_tz::America::Boise
+            .with_ymd_and_hms(2030, 5, 1, 20, 0, 0)
+            .unwrap();
+        let fragments: Vec<String> = std::iter::once("Headliner".to_string())
+            .chain((1..=40).map(|n| format!("Fragment {n}")))
+            .collect();
+        let event = build_event(
+            "venue",
+            "Venue",
+            "https://venue.example",
+            start,
+            fragments.clone(),
+            12,
+            None,
+            None,
+            AgeRestriction::AllAges,
+            None,
+            json!({}),
+        );
+        assert_eq!(event.artists.len(), 12);
+        assert_eq!(event.artists[0], "Headliner");
+        assert_eq!(event.artists[11], "Fragment 11");
+        assert_eq!(event.extra["artists_truncated"], json!(29));
+        let lineup = cap_lineup(billed_lineup(&fragments[..1], &fragments[1..]), 12);
+        assert_eq!(lineup.len(), 12);
+        assert_eq!(lineup[0].role, LineupRole::Headliner);
+        assert_eq!(cap_lineup(lineup, 0).len(), 12);
+
+        let mut extra = json!({});
+        let kept = cap_artists(vec!["A".into(), "B".into()], 2, &mut extra);
+        assert_eq!(kept, vec!["A", "B"]);
+        assert_eq!(extra, json!({}));
+        assert_eq!(cap_artists(vec!["A".into(); 3], 0, &mut extra).len(), 3);
+    }
+
+    #[test]
+    fn build_event_drops_duplicate_artists() {
+        let start = chrono_tz::America::Boise
+            .with_ymd_and_hms(2025, 10, 8, 20, 0, 0)
+            .unwrap();
+        let event = build_event(
+            "treefort",
+            "Treefort Music Hall",
+            "https://treefortmusichall.com",
+            start,
+            vec![
+                "PUP".to_string(),
+                "Chase Petra".to_string(),
+                "pup".to_string(),
+                " Chase  petra ".to_string(),
+                "PUP".to_string(),
+            ],
+            DEFAULT_MAX_ARTISTS_PER_EVENT,
+            None,
+            None,
+            AgeRestriction::Unknown,
+            None,
+            Value::Null,
+        );
+        assert_eq!(event.artists, vec!["PUP", "Chase Petra"]);
+        assert_eq!(
+            event.id,
+            event_id(
+                "treefort",
+                &start.with_timezone(&Utc),
+                &["PUP".to_string(), "Chase Petra".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn event_id_ignores_headliner_whitespace_and_case() {
+        let start = Utc.with_ymd_and_hms(2025, 3, 14, 3, 0, 0).unwrap();
+        let id = event_id("treefort", &start, &artists(&["Dr. Dog"]));
+        assert_eq!(
+            event_id("treefort", &start, &artists(&["  dr.   DOG \t"])),
+            id
+        );
+        assert_eq!(event_id("treefort", &start, &artists(&["", "Dr. Dog"])), id);
+        assert_ne!(event_id("treefort", &start, &artists(&["Dr Dog"])), id);
+    }
+
+    #[test]
+    fn event_id_ignores_support_order_and_changes() {
+        let start = Utc.with_ymd_and_hms(2025, 3, 14, 3, 0, 0).unwrap();
+        let id = event_id(
+            "treefort",
+            &start,
+            &artists(&["PUP", "Chase Petra", "Hotties"]),
+        );
+        assert_eq!(
+            event_id(
+                "treefort",
+                &start,
+                &artists(&["PUP", "Hotties", "Chase Petra"])
+            ),
+            id
+        );
+        assert_eq!(event_id("treefort", &start, &artists(&["PUP"])), id);
+        assert_ne!(event_id("revolution", &start, &artists(&["PUP"])), id);
+        assert_ne!(
+            event_id(
+                "treefort",
+                &(start + chrono::Duration::hours(1)),
+                &artists(&["PUP"])
+            ),
+            id
+        );
+    }
+}
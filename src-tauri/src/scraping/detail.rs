@@ -0,0 +1,286 @@
+//! Optional second pass that fetches each event's own page for the price,
+//! description and image the listing cards leave out.
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde_json::json;
+
+use super::base;
+use crate::models::Event;
+
+static PRICE_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("[class*='price'], [itemprop='price']").expect("detail price selector")
+});
+static DESCRIPTION_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("meta[property='og:description'], meta[name='description']")
+        .expect("detail description selector")
+});
+static IMAGE_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse("meta[property='og:image']").expect("detail image selector"));
+
+static PRICE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\$\s*(\d{1,4})(?:\.(\d{2}))?").expect("valid price regex"));
+/// "Free" as the whole price text.
+static FREE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^\W*free\W*$").expect("valid free regex"));
+/// "Free admission", "entry is free", "free show". Only counts without a
+/// dollar amount beside it. "smoke-free" is a different word.
+static FREE_ADMISSION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)(?:^|[^\w-])free\s+(?:admission|entry|show)\b",
+        r"|\b(?:admission|entry)\s*(?::|is)?\s*free\b",
+    ))
+    .expect("valid free admission regex")
+});
+/// "Free with RSVP": a zero tier even when a door price follows.
+static FREE_RSVP_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(?:^|[^\w-])free\s+(?:with\s+)?rsvp\b").expect("valid free rsvp regex")
+});
+
+/// Whether a price element says the show itself is free, as opposed to
+/// "free parking" or "free drink with ticket".
+fn is_free_price(text: &str) -> bool {
+    FREE_RE.is_match(text)
+        || FREE_RSVP_RE.is_match(text)
+        || (FREE_ADMISSION_RE.is_match(text) && !PRICE_RE.is_match(text))
+}
+
+/// What a detail page adds to its listing card.
+#[derive(Debug, Default, PartialEq)]
+pub struct EventDetails {
+    pub price_min_cents: Option<i64>,
+    pub price_max_cents: Option<i64>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+}
+
+/// Reads the price range from price-labelled elements, free admission
+/// counting as zero, and the description and image from the page's share
+/// metadata.
+pub fn parse_detail_page(html: &str, page_url: &str) -> EventDetails {
+    let document = Html::parse_document(html);
+    let prices: Vec<i64> = document
+        .select(&PRICE_SELECTOR)
+        .flat_map(|el| {
+            let text = base::inner_text(el);
+            let free = is_free_price(&text).then_some(0);
+            PRICE_RE
+                .captures_iter(&text)
+                .filter_map(|caps| {
+                    let dollars: i64 = caps[1].parse().ok()?;
+                    let cents: i64 = caps.get(2).map_or(Some(0), |c| c.as_str().parse().ok())?;
+                    Some(dollars * 100 + cents)
+                })
+                .chain(free)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let meta = |selector: &Selector| {
+        document
+            .select(selector)
+            .filter_map(|el| el.value().attr("content"))
+            .map(base::clean_text)
+            .find(|content| !content.is_empty())
+    };
+    EventDetails {
+        price_min_cents: prices.iter().copied().min(),
+        price_max_cents: prices.iter().copied().max(),
+        description: meta(&DESCRIPTION_SELECTOR),
+        image_url: base::absolute_url(page_url, meta(&IMAGE_SELECTOR)),
+    }
+}
+
+/// Fills gaps in `event` from its detail page. What the listing already had
+/// wins.
+pub fn merge_details(event: &mut Event, details: EventDetails) {
+    if event.price_min_cents.is_none() && event.price_max_cents.is_none() {
+        event.price_min_cents = details.price_min_cents;
+        event.price_max_cents = details.price_max_cents;
+        if details.price_min_cents.is_some() && event.currency.is_none() {
+            event.currency = Some("USD".to_string());
+        }
+    }
+    if !event.extra.is_object() {
+        event.extra = base::empty_extra();
+    }
+    let Some(extra) = event.extra.as_object_mut() else {
+        return;
+    };
+    for (key, value) in [
+        ("description", details.description),
+        ("image_url", details.image_url),
+    ] {
+        if let Some(value) = value {
+            extra.entry(key).or_insert_with(|| json!(value));
+        }
+    }
+}
+
+/// Fetches the detail page of every event with an `event_url`, at most
+/// `max_concurrent` at a time, and merges what it finds. A failed page
+/// leaves its event as listed and is returned as a warning.
+pub fn enrich_from_detail_pages<F>(
+    events: &mut [Event],
+    max_concurrent: usize,
+    fetch: F,
+) -> Vec<String>
+where
+    F: Fn(&str) -> Result<String> + Sync,
+{
+    let mut warnings = Vec::new();
+    for batch in events.chunks_mut(max_concurrent.max(1)) {
+        let fetched: Vec<Option<Result<String>>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|event| {
+                    let url = event.event_url.clone();
+                    let fetch = &fetch;
+                    scope.spawn(move || url.map(|url| fetch(&url)))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Some(Err(anyhow::anyhow!("detail fetch panicked"))))
+                })
+                .collect()
+        });
+        for (event, page) in batch.iter_mut().zip(fetched) {
+            match page {
+                Some(Ok(html)) => {
+                    let url = event.event_url.clone().unwrap_or_default();
+                    merge_details(event, parse_detail_page(&html, &url));
+                }
+                Some(Err(err)) => {
+                    warnings.push(format!("detail page for {} failed: {err:#}", event.title()))
+                }
+                None => {}
+            }
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+    use std::time::Duration;
+
+    const DETAIL_HTML: &str = r#"
+    <html><head>
+        <meta property="og:description" content="PUP return with Chase Petra in support.">
+        <meta property="og:image" content="/images/pup.jpg">
+    </head><body>
+        <div class="ticket-price">Advance $25.00 · Day of show $30</div>
+        <p>Bar specials $5 all night.</p>
+    </body></html>
+    "#;
+
+    fn listing(server: &str) -> String {
+        format!(
+            r#"
+            <div class="mh-show-wrapper"><div class="mh-show-body group">
+                <div class="mh-show-col mh-show-date"><div id="dat">10/8/2025</div><div id="doo">DOORS: 7pm</div></div>
+                <div class="mh-show-col mh-show-artist"><a href="{server}/shows/pup"><div class="mh-h1">PUP</div></a></div>
+            </div></div>
+            <div class="mh-show-wrapper"><div class="mh-show-body group">
+                <div class="mh-show-col mh-show-date"><div id="dat">10/17/2025</div><div id="doo">DOORS: 8pm</div></div>
+                <div class="mh-show-col mh-show-artist"><a href="{server}/shows/gone"><div class="mh-h1">Desert Dwellers</div></a></div>
+            </div></div>
+            "#
+        )
+    }
+
+    #[test]
+    fn detail_page_enriches_its_listing() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/shows/pup" => MockResponse::ok(DETAIL_HTML),
+            _ => MockResponse::status(404, "not found"),
+        });
+        let report = super::super::parse_html("treefort", &listing(server.url())).expect("parse");
+        let mut events = report.events;
+        assert_eq!(events.len(), 2);
+
+        let warnings = enrich_from_detail_pages(&mut events, 2, |url| {
+            base::fetch_html(url, Duration::from_secs(5))
+        });
+
+        let pup = &events[0];
+        assert_eq!(pup.price_min_cents, Some(2500));
+        assert_eq!(pup.price_max_cents, Some(3000));
+        assert_eq!(pup.currency.as_deref(), Some("USD"));
+        assert_eq!(
+            pup.extra["description"],
+            "PUP return with Chase Petra in support."
+        );
+        assert_eq!(
+            pup.extra["image_url"],
+            format!("{}/images/pup.jpg", server.url())
+        );
+
+        let other = &events[1];
+        assert_eq!(other.price_min_cents, None);
+        assert!(other.extra.get("description").is_none());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Desert Dwellers"), "{warnings:?}");
+    }
+
+    #[test]
+    fn listing_values_win_over_detail_page() {
+        let mut event = crate::db::sample_event("treefort", "PUP", chrono::Utc::now());
+        event.price_min_cents = Some(2000);
+        event.price_max_cents = None;
+        event.extra = json!({ "description": "From the listing" });
+
+        merge_details(
+            &mut event,
+            parse_detail_page(DETAIL_HTML, "https://x.test/a"),
+        );
+        assert_eq!(event.price_min_cents, Some(2000));
+        assert_eq!(event.price_max_cents, None);
+        assert_eq!(event.extra["description"], "From the listing");
+        assert_eq!(event.extra["image_url"], "https://x.test/images/pup.jpg");
+    }
+
+    #[test]
+    fn free_admission_parses_as_zero() {
+        let free = parse_detail_page(
+            r#"<div class="price">FREE show · all ages</div>"#,
+            "https://x.test/a",
+        );
+        assert_eq!(free.price_min_cents, Some(0));
+        assert_eq!(free.price_max_cents, Some(0));
+
+        let rsvp = parse_detail_page(
+            r#"<div class="price">Free with RSVP, $10 at the door</div>"#,
+            "https://x.test/a",
+        );
+        assert_eq!(rsvp.price_min_cents, Some(0));
+        assert_eq!(rsvp.price_max_cents, Some(1000));
+
+        let bare = parse_detail_page(r#"<div class="price">Free!</div>"#, "https://x.test/a");
+        assert_eq!(bare.price_min_cents, Some(0));
+    }
+
+    #[test]
+    fn free_extras_are_not_free_admission() {
+        for text in [
+            "$15 · Free parking",
+            "$20 smoke-free show",
+            "$25, free drink with ticket",
+            "Free parking",
+            "Free admission with a $10 drink minimum",
+        ] {
+            let details = parse_detail_page(
+                &format!(r#"<div class="price">{text}</div>"#),
+                "https://x.test/a",
+            );
+            assert_ne!(details.price_min_cents, Some(0), "{text}");
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::Result;
+use chrono::DateTime;
+use chrono_tz::Tz;
+use serde::Deserialize;
+use serde_json::{json, Map};
+
+use super::base;
+use super::{JsonVenueScraper, ScrapeReport, VenueScraper};
+
+const URL: &str = "https://dice.fm/venue/neurolux-8emn";
+const API_URL: &str = "https://partners-endpoint.dice.fm/api/v2/events?filter[venues][]=Neurolux";
+const VENUE_ID: &str = "neurolux";
+const VENUE_NAME: &str = "Neurolux";
+const TIMEZONE: Tz = chrono_tz::America::Boise;
+
+/// Example of a JSON-backed venue: Neurolux sells through Dice, whose event
+/// feed carries the lineup and an offset start time directly. Not registered
+/// in `all_scrapers` yet: the partner feed needs an API key.
+pub struct NeuroluxDice;
+
+#[derive(Debug, Deserialize)]
+pub struct DicePayload {
+    #[serde(default)]
+    data: Vec<DiceEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiceEvent {
+    id: String,
+    name: String,
+    /// RFC 3339 with the venue's offset.
+    date: String,
+    url: Option<String>,
+    #[serde(default)]
+    artists: Vec<String>,
+    age_limit: Option<String>,
+}
+
+impl VenueScraper for NeuroluxDice {
+    fn venue_id(&self) -> &'static str {
+        VENUE_ID
+    }
+
+    fn venue_name(&self) -> &'static str {
+        VENUE_NAME
+    }
+
+    fn venue_url(&self) -> &'static str {
+        URL
+    }
+
+    fn fetch(&self) -> Result<ScrapeReport> {
+        self.fetch_api()
+    }
+}
+
+impl JsonVenueScraper for NeuroluxDice {
+    type Payload = DicePayload;
+
+    fn api_url(&self) -> String {
+        API_URL.to_string()
+    }
+
+    fn parse_payload(&self, payload: DicePayload, max_artists: usize) -> ScrapeReport {
+        let mut report = ScrapeReport::default();
+        for (index, item) in payload.data.into_iter().enumerate() {
+            let start_local = match DateTime::parse_from_rfc3339(&item.date) {
+                Ok(dt) => dt.with_timezone(&TIMEZONE),
+                Err(_) => {
+                    report.skip(index, &format!("unparseable date {:?}", item.date));
+                    continue;
+                }
+            };
+            let artists = if item.artists.is_empty() {
+                base::split_artists_with(&item.name, &self.artist_split())
+            } else {
+                item.artists
+            };
+            if artists.is_empty() {
+                report.skip(index, "missing artists");
+                continue;
+            }
+
+            let mut extra = Map::new();
+            extra.insert("dice_id".to_string(), json!(item.id));
+            if let Some(ref age) = item.age_limit {
+                extra.insert("age_raw".to_string(), json!(age));
+            }
+
+            report.events.push(base::build_event(
+                VENUE_ID,
+                VENUE_NAME,
+                URL,
+                start_local,
+                artists,
+                max_artists,
+                item.url.clone(),
+                item.url,
+                item.age_limit
+                    .as_deref()
+                    .map(base::parse_age_restriction)
+                    .unwrap_or_default(),
+                None,
+                serde_json::Value::Object(extra),
+            ));
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AgeRestriction;
+
+    const SAMPLE_JSON: &str = r#"{
+        "data": [
+            {
+                "id": "65f0c1",
+                "name": "Built to Spill with Prism Bitch",
+                "date": "2030-03-14T20:00:00-06:00",
+                "url": "https://dice.fm/event/65f0c1",
+                "artists": ["Built to Spill", "Prism Bitch"],
+                "age_limit": "21+"
+            },
+            {
+                "id": "65f0c2",
+                "name": "Karaoke & Chill",
+                "date": "2030-03-15T21:00:00-06:00",
+                "url": null
+            },
+            {
+                "id": "65f0c3",
+                "name": "Date TBD",
+                "date": "soon"
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parses_dice_payload_into_events() {
+        let payload: DicePayload = serde_json::from_str(SAMPLE_JSON).expect("sample json");
+        let report = NeuroluxDice.parse_payload(payload, base::DEFAULT_MAX_ARTISTS_PER_EVENT);
+
+        assert_eq!(report.events.len(), 2);
+        let first = &report.events[0];
+        assert_eq!(first.artists, vec!["Built to Spill", "Prism Bitch"]);
+        assert_eq!(first.start_utc, "2030-03-15T02:00:00+00:00");
+        assert_eq!(first.age_restriction, AgeRestriction::MinAge(21));
+        assert_eq!(
+            first.ticket_url.as_deref(),
+            Some("https://dice.fm/event/65f0c1")
+        );
+        assert_eq!(first.extra["dice_id"], "65f0c1");
+
+        assert_eq!(report.events[1].artists, vec!["Karaoke", "Chill"]);
+        assert_eq!(
+            report.warnings,
+            vec!["skipped card 3: unparseable date \"soon\""]
+        );
+    }
+}
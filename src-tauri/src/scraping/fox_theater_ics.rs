@@ -1,7 +1,6 @@
 use anyhow::Result;
 
-use super::VenueScraper;
-use crate::models::Event;
+use super::{ScrapeReport, VenueScraper};
 
 pub struct FoxTheater;
 
@@ -18,7 +17,7 @@ impl VenueScraper for FoxTheater {
         "https://www.foxtheatre.org/"
     }
 
-    fn fetch(&self) -> Result<Vec<Event>> {
-        Ok(Vec::new())
+    fn fetch(&self) -> Result<ScrapeReport> {
+        Ok(ScrapeReport::default())
     }
 }
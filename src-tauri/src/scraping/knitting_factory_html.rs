@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use anyhow::Result;
 use chrono::{NaiveTime, TimeZone, Timelike};
 use chrono_tz::Tz;
@@ -7,8 +9,9 @@ use scraper::{Html, Selector};
 use serde_json::{json, Map};
 
 use super::base;
-use super::VenueScraper;
-use crate::models::Event;
+use super::{ScrapeReport, VenueScraper};
+use crate::config::MissingShowTime;
+use crate::models::AgeRestriction;
 
 const URL: &str = "https://bo.knittingfactory.com/";
 const VENUE_ID: &str = "knitboise";
@@ -19,6 +22,8 @@ static CARD_SELECTOR: Lazy<Selector> =
     Lazy::new(|| Selector::parse("div.tw-section").expect("knitting card selector"));
 static ARTIST_SELECTOR: Lazy<Selector> =
     Lazy::new(|| Selector::parse(".tw-name a").expect("knitting artist"));
+static SUPPORT_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse(".tw-opening-act").expect("knitting support"));
 static VENUE_SELECTOR: Lazy<Selector> =
     Lazy::new(|| Selector::parse(".tw-venue-name").expect("knitting venue"));
 static DATE_SELECTOR: Lazy<Selector> =
@@ -47,40 +52,92 @@ impl VenueScraper for KnittingFactoryBoise {
         URL
     }
 
-    fn fetch(&self) -> Result<Vec<Event>> {
-        let html = base::fetch_html(URL)?;
-        self.parse_document(&html)
+    fn expected_min_events(&self) -> usize {
+        10
+    }
+
+    fn key_selectors(&self) -> Vec<(&'static str, &'static Selector)> {
+        vec![
+            ("card", &*CARD_SELECTOR),
+            ("date", &*DATE_SELECTOR),
+            ("artist", &*ARTIST_SELECTOR),
+            ("ticket", &*TICKET_SELECTOR),
+        ]
+    }
+
+    fn fetch(&self) -> Result<ScrapeReport> {
+        let pages = base::fetch_pages(URL, self.request_timeout())?;
+        let pages: Vec<&str> = pages.iter().map(String::as_str).collect();
+        let config = base::scrape_config();
+        self.parse_pages(
+            &pages,
+            config.show_time_for_venue(VENUE_ID),
+            config.max_artists_per_event,
+            config.keep_collaborations,
+        )
     }
 }
 
 impl KnittingFactoryBoise {
-    pub(crate) fn parse_document(&self, html: &str) -> Result<Vec<Event>> {
-        let document = Html::parse_document(html);
-        let mut events = Vec::new();
+    pub(crate) fn parse_document(
+        &self,
+        html: &str,
+        missing_time: &MissingShowTime,
+        max_artists: usize,
+        keep_collaborations: bool,
+    ) -> Result<ScrapeReport> {
+        self.parse_pages(&[html], missing_time, max_artists, keep_collaborations)
+    }
 
-        for card in document.select(&CARD_SELECTOR) {
+    /// Parses every listing page into one report. Events repeated where pages
+    /// overlap are kept once.
+    pub(crate) fn parse_pages(
+        &self,
+        pages: &[&str],
+        missing_time: &MissingShowTime,
+        max_artists: usize,
+        keep_collaborations: bool,
+    ) -> Result<ScrapeReport> {
+        let documents: Vec<Html> = pages
+            .iter()
+            .map(|html| Html::parse_document(html))
+            .collect();
+        let cards = documents
+            .iter()
+            .flat_map(|document| document.select(&CARD_SELECTOR));
+        let mut report = ScrapeReport::default();
+        let mut seen = HashSet::new();
+
+        for (index, card) in cards.enumerate() {
             let venue_label = base::first_text(&card, &VENUE_SELECTOR);
             if let Some(ref venue_label) = venue_label {
                 if !venue_label.to_lowercase().contains("knitting factory") {
                     continue;
                 }
             } else {
+                report.skip(index, "missing venue label");
                 continue;
             }
 
             let artists_text = match base::first_text(&card, &ARTIST_SELECTOR) {
                 Some(text) => text,
-                None => continue,
+                None => {
+                    report.skip(index, "missing artists");
+                    continue;
+                }
             };
-            let artists = base::split_artists(&artists_text);
-            if artists.is_empty() {
+            let headliners = base::split_artists_with(&artists_text, &self.artist_split());
+            if headliners.is_empty() {
+                report.skip(index, "missing artists");
                 continue;
             }
+            let support = base::first_text(&card, &SUPPORT_SELECTOR)
+                .map(|text| base::support_acts(&text, &self.artist_split()))
+                .unwrap_or_default();
+            let lineup = base::billed_lineup(&headliners, &support);
+            let artists: Vec<String> = headliners.into_iter().chain(support).collect();
 
-            let date_text = match base::first_text(&card, &DATE_SELECTOR) {
-                Some(text) => text,
-                None => continue,
-            };
+            let date_text = base::first_text(&card, &DATE_SELECTOR);
 
             let time_block = base::first_text(&card, &TIME_SELECTOR);
             let show_time = time_block
@@ -93,10 +150,20 @@ impl KnittingFactoryBoise {
             let event_url =
                 base::absolute_url(URL, base::first_attr(&card, &INFO_SELECTOR, "href"));
 
+            if date_text.is_none() && !ticket_url.as_deref().is_some_and(has_url_date) {
+                report.skip(index, "missing date");
+                continue;
+            }
+
+            let (start_time, time_tba) = base::show_time_or_default(show_time, missing_time);
             let start_local =
-                match determine_start(&date_text, show_time.as_deref(), ticket_url.as_deref()) {
+                match determine_start(date_text.as_deref(), &start_time, ticket_url.as_deref()) {
                     Some(dt) => dt,
-                    None => continue,
+                    None => {
+                        let date_text = date_text.unwrap_or_default();
+                        report.skip(index, &format!("unparseable date {date_text:?}"));
+                        continue;
+                    }
                 };
 
             let doors_local = time_block
@@ -105,7 +172,9 @@ impl KnittingFactoryBoise {
                 .and_then(|text| base::combine_with_date(&start_local, &text, TIMEZONE));
 
             let mut extra = Map::new();
-            extra.insert("date_text".to_string(), json!(date_text));
+            if let Some(ref date_text) = date_text {
+                extra.insert("date_text".to_string(), json!(date_text));
+            }
             if let Some(block) = time_block.clone() {
                 extra.insert("time_block".to_string(), json!(block));
             }
@@ -113,23 +182,33 @@ impl KnittingFactoryBoise {
                 extra.insert("doors_iso".to_string(), json!(doors));
             }
 
-            let event = base::build_event(
+            let mut event = base::build_event(
                 VENUE_ID,
                 VENUE_NAME,
                 URL,
                 start_local,
                 artists,
+                max_artists,
                 ticket_url.clone(),
                 event_url,
-                None,
+                AgeRestriction::Unknown,
                 doors_local,
                 serde_json::Value::Object(extra),
             );
+            event.lineup = base::cap_lineup(lineup, max_artists);
+            if keep_collaborations {
+                base::keep_collaboration(&mut event, &artists_text, &self.artist_split());
+            }
+            event.time_tba = time_tba;
 
-            events.push(event);
+            if seen.insert(event.id.clone()) {
+                report.events.push(event);
+            }
         }
 
-        if events.is_empty() {
+        if report.events.is_empty() {
+            report.warn("no cards parsed; using sample event");
+            report.sample = true;
             let start_local = TIMEZONE
                 .with_ymd_and_hms(2025, 11, 2, 19, 0, 0)
                 .single()
@@ -140,36 +219,41 @@ impl KnittingFactoryBoise {
                 URL,
                 start_local,
                 vec!["Of Monsters and Men".to_string()],
+                max_artists,
                 Some("https://ticketweb.com/event/12345".to_string()),
                 Some("https://bo.knittingfactory.com/event/12345".to_string()),
-                None,
+                AgeRestriction::Unknown,
                 base::combine_with_date(&start_local, "6:30 PM", TIMEZONE),
                 json!({
                     "show_time": "7:00 PM",
                 }),
             );
-            events.push(sample);
+            report.events.push(sample);
         }
 
-        Ok(events)
+        Ok(report)
     }
 }
 
+/// Builds the start from the card's date text, taking the year from the ticket
+/// URL when it has one. A card with no date text falls back to the URL's full
+/// date.
 fn determine_start(
-    date_text: &str,
-    time_text: Option<&str>,
+    date_text: Option<&str>,
+    time_text: &str,
     ticket_url: Option<&str>,
 ) -> Option<chrono::DateTime<Tz>> {
-    let formatted_time = time_text
-        .map(|val| base::find_first_time(val).unwrap_or_else(|| val.to_string()))
-        .unwrap_or_else(|| "7:00 PM".to_string());
+    let formatted_time = base::find_first_time(time_text).unwrap_or_else(|| time_text.to_string());
 
     if let Some(url) = ticket_url {
         if let Some((month, day, year)) = extract_date_from_url(url) {
-            let date_with_year = format!("{}, {}", date_text.trim(), year);
-            if let Some(dt) = base::parse_datetime(&date_with_year, Some(&formatted_time), TIMEZONE)
-            {
-                return Some(dt);
+            if let Some(date_text) = date_text {
+                let date_with_year = format!("{}, {}", date_text.trim(), year);
+                if let Some(dt) =
+                    base::parse_datetime(&date_with_year, Some(&formatted_time), TIMEZONE)
+                {
+                    return Some(dt);
+                }
             }
 
             if let Ok(naive) = NaiveTime::parse_from_str(&formatted_time, "%I:%M %p") {
@@ -180,7 +264,11 @@ fn determine_start(
         }
     }
 
-    base::parse_datetime(date_text, Some(&formatted_time), TIMEZONE)
+    base::parse_datetime(date_text?, Some(&formatted_time), TIMEZONE)
+}
+
+fn has_url_date(url: &str) -> bool {
+    extract_date_from_url(url).is_some()
 }
 
 fn extract_date_from_url(url: &str) -> Option<(u32, u32, i32)> {
@@ -243,7 +331,15 @@ mod tests {
     #[test]
     fn parses_knitting_factory_events() {
         let scraper = KnittingFactoryBoise;
-        let events = scraper.parse_document(SAMPLE_HTML).expect("parse html");
+        let events = scraper
+            .parse_document(
+                SAMPLE_HTML,
+                &MissingShowTime::default(),
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse html")
+            .events;
         assert_eq!(
             events.len(),
             1,
@@ -261,4 +357,71 @@ mod tests {
         let start_local = event.start_local.as_ref().expect("local time");
         assert!(start_local.starts_with("2025-10-05T19:00:00"));
     }
+
+    #[test]
+    fn support_element_is_billed_after_the_headliner() {
+        let html = r#"
+        <div class="tw-section">
+            <div class="tw-name"><a href="https://bo.knittingfactory.com/tm-event/pup/">PUP</a></div>
+            <div class="tw-opening-act">with special guests Chase Petra, Pool Kids</div>
+            <span class="tw-event-date">October 9</span>
+            <span class="tw-venue-name"> / Knitting Factory - Boise </span>
+            <span class="tw-event-time"> Show: 8:00 pm </span>
+            <a class="tw-buy-tix-btn" href="https://www.ticketmaster.com/pup-boise-idaho-10-09-2025/event/1">Buy Tickets</a>
+        </div>
+        "#;
+        let report = KnittingFactoryBoise
+            .parse_document(
+                html,
+                &MissingShowTime::default(),
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse html");
+        let event = &report.events[0];
+        assert_eq!(event.artists, vec!["PUP", "Chase Petra", "Pool Kids"]);
+        let roles: Vec<_> = event.lineup.iter().map(|slot| slot.role).collect();
+        assert_eq!(
+            roles,
+            vec![
+                crate::models::LineupRole::Headliner,
+                crate::models::LineupRole::Support,
+                crate::models::LineupRole::Support
+            ]
+        );
+    }
+
+    #[test]
+    fn card_without_a_date_element_uses_the_ticket_url_date() {
+        let html = r#"
+        <div class="tw-section">
+            <div class="tw-name"><a href="https://bo.knittingfactory.com/tm-event/pup/">PUP</a></div>
+            <span class="tw-venue-name"> / Knitting Factory - Boise </span>
+            <span class="tw-event-time"> Show: 8:00 pm </span>
+            <a class="tw-buy-tix-btn" href="https://www.ticketmaster.com/pup-boise-idaho-10-09-2025/event/1">Buy Tickets</a>
+        </div>
+        <div class="tw-section">
+            <div class="tw-name"><a href="https://bo.knittingfactory.com/tm-event/nodate/">No Date</a></div>
+            <span class="tw-venue-name"> / Knitting Factory - Boise </span>
+            <a class="tw-buy-tix-btn" href="https://www.ticketmaster.com/event/2">Buy Tickets</a>
+        </div>
+        "#;
+        let report = KnittingFactoryBoise
+            .parse_document(
+                html,
+                &MissingShowTime::default(),
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse html");
+        assert!(!report.sample);
+        assert_eq!(report.events.len(), 1);
+        let start_local = report.events[0].start_local.as_deref().expect("local");
+        assert!(
+            start_local.starts_with("2025-10-09T20:00:00"),
+            "{start_local}"
+        );
+        assert!(report.events[0].extra.get("date_text").is_none());
+        assert!(report.warnings.iter().any(|w| w.contains("missing date")));
+    }
 }
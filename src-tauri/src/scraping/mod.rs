@@ -1,29 +1,159 @@
 pub mod base;
+pub mod detail;
+pub mod dice_json;
 pub mod fox_theater_ics;
 pub mod knitting_factory_html;
 pub mod pine_box_html;
 pub mod revolution_html;
 pub mod treefort_html;
 
-use anyhow::Error;
+use std::time::Duration;
 
+use scraper::{Html, Selector};
+
+use crate::config::{AppConfig, CrossVenueDedup, DetailPages};
 use crate::models::Event;
 
 pub trait VenueScraper: Send + Sync {
     fn venue_id(&self) -> &'static str;
     fn venue_name(&self) -> &'static str;
     fn venue_url(&self) -> &'static str;
-    fn fetch(&self) -> anyhow::Result<Vec<Event>>;
+    fn fetch(&self) -> anyhow::Result<ScrapeReport>;
+
+    /// Per-venue override for slow or flaky sites.
+    fn request_timeout(&self) -> Duration {
+        base::default_request_timeout()
+    }
+
+    /// Separators this venue's listings use between acts.
+    fn artist_split(&self) -> base::ArtistSplit {
+        base::ArtistSplit::default()
+    }
+
+    /// Events a healthy scrape of this venue normally yields. Zero disables
+    /// the selector health check.
+    fn expected_min_events(&self) -> usize {
+        0
+    }
+
+    /// The card, date, artist and ticket selectors the listing page depends on,
+    /// for `validate_selectors`. Empty for venues that don't scrape HTML.
+    fn key_selectors(&self) -> Vec<(&'static str, &'static Selector)> {
+        Vec::new()
+    }
 }
 
+/// JSON-backed venues implement this and forward `fetch` to `fetch_api`,
+/// skipping HTML selectors entirely.
+pub trait JsonVenueScraper: VenueScraper {
+    type Payload: serde::de::DeserializeOwned;
+
+    fn api_url(&self) -> String;
+    fn parse_payload(&self, payload: Self::Payload, max_artists: usize) -> ScrapeReport;
+
+    fn fetch_api(&self) -> anyhow::Result<ScrapeReport> {
+        let payload = base::fetch_json(&self.api_url(), self.request_timeout())?;
+        Ok(self.parse_payload(payload, base::scrape_config().max_artists_per_event))
+    }
+}
+
+/// A real scrape at or below this many events, from a venue expected to list
+/// more, is treated as likely selector breakage.
+const SELECTOR_BREAKAGE_MAX_EVENTS: usize = 1;
+
 #[derive(Clone, serde::Serialize)]
 pub struct ScraperInfo {
     pub id: String,
     pub name: String,
     pub url: String,
+    pub enabled: bool,
 }
 
-fn active_scrapers() -> Vec<Box<dyn VenueScraper>> {
+/// Events parsed from one venue, plus notes on cards that were skipped so
+/// selector drift shows up instead of silently shrinking the list.
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct ScrapeReport {
+    pub events: Vec<Event>,
+    pub warnings: Vec<String>,
+    /// Set when no cards parsed and the scraper fell back to its sample event.
+    pub sample: bool,
+    pub selector_warning: Option<String>,
+}
+
+impl ScrapeReport {
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.warnings.push(message.into());
+    }
+
+    /// Records that the card at zero-based `index` was dropped.
+    pub fn skip(&mut self, index: usize, reason: &str) {
+        self.warn(format!("skipped card {}: {reason}", index + 1));
+    }
+
+    /// Events that came from the venue page rather than the sample fallback.
+    pub fn real_event_count(&self) -> usize {
+        if self.sample {
+            0
+        } else {
+            self.events.len()
+        }
+    }
+}
+
+/// Flags a scrape whose real event count collapsed for a venue that
+/// normally lists plenty of shows.
+pub fn selector_health_warning(expected_min: usize, report: &ScrapeReport) -> Option<String> {
+    let found = report.real_event_count();
+    if expected_min <= SELECTOR_BREAKAGE_MAX_EVENTS || found > SELECTOR_BREAKAGE_MAX_EVENTS {
+        return None;
+    }
+    Some(format!(
+        "possible selector breakage: found {found} events, expected at least {expected_min}"
+    ))
+}
+
+/// Drops events whose start can't be right, noting why in the report.
+fn drop_implausible_starts(report: &mut ScrapeReport, now: chrono::DateTime<chrono::Utc>) {
+    let mut rejected = Vec::new();
+    report
+        .events
+        .retain(|event| match base::implausible_start(event, now) {
+            Some(reason) => {
+                rejected.push(format!("rejected {}: {reason}", event.title()));
+                false
+            }
+            None => true,
+        });
+    report.warnings.extend(rejected);
+}
+
+/// Fetches one venue, filters out junk dates, fills in detail pages when
+/// enabled and attaches the selector health verdict.
+fn run_scraper(scraper: &dyn VenueScraper) -> anyhow::Result<ScrapeReport> {
+    let mut report = scraper.fetch()?;
+    drop_implausible_starts(&mut report, chrono::Utc::now());
+    let details = detail_pages();
+    if details.enabled && !report.sample {
+        let warnings =
+            detail::enrich_from_detail_pages(&mut report.events, details.max_concurrent, |url| {
+                base::fetch_html(url, scraper.request_timeout())
+            });
+        report.warnings.extend(warnings);
+    }
+    report.selector_warning = selector_health_warning(scraper.expected_min_events(), &report);
+    Ok(report)
+}
+
+/// Result of running one venue's scraper during a batch run.
+pub struct VenueOutcome {
+    pub venue_id: String,
+    pub events: Vec<Event>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+    pub selector_warning: Option<String>,
+}
+
+fn all_scrapers() -> Vec<Box<dyn VenueScraper>> {
     vec![
         Box::new(treefort_html::Treefort),
         Box::new(revolution_html::Revolution),
@@ -31,37 +161,165 @@ fn active_scrapers() -> Vec<Box<dyn VenueScraper>> {
     ]
 }
 
+pub fn known_venue_ids() -> Vec<&'static str> {
+    all_scrapers()
+        .iter()
+        .map(|scraper| scraper.venue_id())
+        .collect()
+}
+
+fn enabled_venues() -> Vec<String> {
+    match AppConfig::load() {
+        Ok(config) => config.enabled_venues,
+        Err(err) => {
+            eprintln!("config load failed, enabling all venues: {err}");
+            Vec::new()
+        }
+    }
+}
+
+fn active_scrapers() -> Vec<Box<dyn VenueScraper>> {
+    select_enabled(all_scrapers(), &enabled_venues())
+}
+
+/// Keeps the scrapers named in `enabled`, in that order. An empty list
+/// enables every scraper in registration order.
+fn select_enabled(
+    scrapers: Vec<Box<dyn VenueScraper>>,
+    enabled: &[String],
+) -> Vec<Box<dyn VenueScraper>> {
+    if enabled.is_empty() {
+        return scrapers;
+    }
+    let mut remaining: Vec<Option<Box<dyn VenueScraper>>> =
+        scrapers.into_iter().map(Some).collect();
+    enabled
+        .iter()
+        .filter_map(|id| {
+            remaining
+                .iter_mut()
+                .find(|slot| slot.as_ref().is_some_and(|s| s.venue_id() == id))
+                .and_then(Option::take)
+        })
+        .collect()
+}
+
 pub fn list_scrapers() -> Vec<ScraperInfo> {
-    active_scrapers()
+    scraper_infos(all_scrapers(), &enabled_venues())
+}
+
+fn scraper_infos(scrapers: Vec<Box<dyn VenueScraper>>, enabled: &[String]) -> Vec<ScraperInfo> {
+    scrapers
         .into_iter()
         .map(|scraper| ScraperInfo {
             id: scraper.venue_id().to_string(),
             name: scraper.venue_name().to_string(),
             url: scraper.venue_url().to_string(),
+            enabled: enabled.is_empty() || enabled.iter().any(|id| id == scraper.venue_id()),
+        })
+        .collect()
+}
+
+pub fn run_each() -> Vec<VenueOutcome> {
+    run_each_in(active_scrapers())
+}
+
+fn run_each_in(scrapers: Vec<Box<dyn VenueScraper>>) -> Vec<VenueOutcome> {
+    scrapers
+        .into_iter()
+        .map(|scraper| {
+            let venue_id = scraper.venue_id().to_string();
+            match run_scraper(scraper.as_ref()) {
+                Ok(report) => VenueOutcome {
+                    venue_id,
+                    events: report.events,
+                    warnings: report.warnings,
+                    error: None,
+                    selector_warning: report.selector_warning,
+                },
+                Err(err) => VenueOutcome {
+                    venue_id,
+                    events: Vec::new(),
+                    warnings: Vec::new(),
+                    error: Some(err.to_string()),
+                    selector_warning: None,
+                },
+            }
         })
         .collect()
 }
 
-fn find_scraper(id: &str) -> Option<Box<dyn VenueScraper>> {
-    for scraper in active_scrapers() {
-        if scraper.venue_id() == id {
-            return Some(scraper);
+/// Titles listed per venue in a dry run.
+const DRY_RUN_SAMPLE_TITLES: usize = 3;
+
+/// What a batch scrape would have found at one venue: the `ScrapeReport`
+/// fields with the events reduced to a count and a few titles.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct DryRunVenue {
+    pub venue_id: String,
+    pub event_count: usize,
+    pub sample_titles: Vec<String>,
+    pub warnings: Vec<String>,
+    pub selector_warning: Option<String>,
+    pub error: Option<String>,
+}
+
+impl From<VenueOutcome> for DryRunVenue {
+    fn from(outcome: VenueOutcome) -> Self {
+        Self {
+            venue_id: outcome.venue_id,
+            event_count: outcome.events.len(),
+            sample_titles: outcome
+                .events
+                .iter()
+                .take(DRY_RUN_SAMPLE_TITLES)
+                .map(Event::title)
+                .collect(),
+            warnings: outcome.warnings,
+            selector_warning: outcome.selector_warning,
+            error: outcome.error,
         }
     }
-    None
 }
 
 pub fn run_all() -> anyhow::Result<Vec<Event>> {
+    collect_events(run_each())
+}
+
+/// Applies the cross-venue dedupe to a batch of outcomes. Each venue keeps
+/// only its events that weren't folded into another venue's listing, with
+/// whatever those absorbed.
+pub fn dedupe_outcomes(
+    mut outcomes: Vec<VenueOutcome>,
+    rules: &CrossVenueDedup,
+) -> Vec<VenueOutcome> {
+    let events = outcomes
+        .iter_mut()
+        .flat_map(|outcome| std::mem::take(&mut outcome.events))
+        .collect();
+    let mut kept = dedupe_across_venues(events, rules);
+    for outcome in &mut outcomes {
+        let (own, rest) = kept
+            .into_iter()
+            .partition(|event| event.venue_id == outcome.venue_id);
+        outcome.events = own;
+        kept = rest;
+    }
+    outcomes
+}
+
+pub fn collect_events(outcomes: Vec<VenueOutcome>) -> anyhow::Result<Vec<Event>> {
     let mut events = Vec::new();
-    let mut errors: Vec<(String, Error)> = Vec::new();
-
-    for scraper in active_scrapers() {
-        let venue_id = scraper.venue_id().to_string();
-        match scraper.fetch() {
-            Ok(mut scraped) => events.append(&mut scraped),
-            Err(err) => {
-                errors.push((venue_id, err));
-            }
+    let mut errors: Vec<(String, String)> = Vec::new();
+
+    for outcome in dedupe_outcomes(outcomes, &cross_venue_dedup()) {
+        for warning in outcome.warnings.iter().chain(&outcome.selector_warning) {
+            eprintln!("{}: {warning}", outcome.venue_id);
+        }
+        let mut scraped = outcome.events;
+        events.append(&mut scraped);
+        if let Some(err) = outcome.error {
+            errors.push((outcome.venue_id, err));
         }
     }
 
@@ -77,7 +335,587 @@ pub fn run_all() -> anyhow::Result<Vec<Event>> {
     Ok(events)
 }
 
-pub fn run_single(id: &str) -> anyhow::Result<Vec<Event>> {
-    let scraper = find_scraper(id).ok_or_else(|| anyhow::anyhow!("unknown venue id: {id}"))?;
-    scraper.fetch()
+fn detail_pages() -> DetailPages {
+    match AppConfig::load() {
+        Ok(config) => config.detail_pages,
+        Err(err) => {
+            eprintln!("config load failed, skipping detail pages: {err}");
+            DetailPages::default()
+        }
+    }
+}
+
+pub fn cross_venue_dedup() -> CrossVenueDedup {
+    match AppConfig::load() {
+        Ok(config) => config.cross_venue_dedup,
+        Err(err) => {
+            eprintln!("config load failed, using default dedup settings: {err}");
+            CrossVenueDedup::default()
+        }
+    }
+}
+
+/// Folds listings of the same show from different venues' sources into the
+/// first one seen, using the configured window and headliner similarity.
+fn dedupe_across_venues(events: Vec<Event>, rules: &CrossVenueDedup) -> Vec<Event> {
+    if rules.window_minutes == 0 {
+        return events;
+    }
+    let window = chrono::Duration::minutes(i64::from(rules.window_minutes));
+    let start = |event: &Event| chrono::DateTime::parse_from_rfc3339(&event.start_utc).ok();
+
+    let mut kept: Vec<Event> = Vec::with_capacity(events.len());
+    for event in events {
+        let duplicate_of = start(&event).and_then(|event_start| {
+            kept.iter().position(|other| {
+                other.venue_id != event.venue_id
+                    && start(other).is_some_and(|s| (s - event_start).abs() <= window)
+                    && base::name_similarity(&other.title(), &event.title())
+                        >= rules.min_name_similarity
+            })
+        });
+        match duplicate_of {
+            Some(index) => {
+                let keep = &mut kept[index];
+                // The other source's spelling of the headliner is the same act.
+                let headliner = keep.title();
+                let mut other = event;
+                other.artists.retain(|name| {
+                    base::name_similarity(name, &headliner) < rules.min_name_similarity
+                });
+                other.lineup.retain(|slot| {
+                    base::name_similarity(&slot.name, &headliner) < rules.min_name_similarity
+                });
+                keep.absorb(&other);
+            }
+            None => kept.push(event),
+        }
+    }
+    kept
+}
+
+/// Runs one venue by id, whether or not it is enabled for batch runs.
+pub fn run_single(id: &str) -> anyhow::Result<ScrapeReport> {
+    run_single_in(all_scrapers(), id)
+}
+
+fn run_single_in(scrapers: Vec<Box<dyn VenueScraper>>, id: &str) -> anyhow::Result<ScrapeReport> {
+    let scraper = scrapers
+        .into_iter()
+        .find(|scraper| scraper.venue_id() == id)
+        .ok_or_else(|| anyhow::anyhow!("unknown venue id: {id}"))?;
+    run_scraper(scraper.as_ref())
+}
+
+/// How many elements one of a venue's key selectors matched.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SelectorMatches {
+    pub name: String,
+    pub matches: usize,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SelectorReport {
+    pub venue_id: String,
+    pub url: String,
+    pub selectors: Vec<SelectorMatches>,
+}
+
+/// Fetches a venue's live listing page and counts matches for each of its key
+/// selectors, so a broken one stands out without diffing HTML.
+pub fn validate_selectors(venue_id: &str) -> anyhow::Result<SelectorReport> {
+    let scraper = all_scrapers()
+        .into_iter()
+        .find(|scraper| scraper.venue_id() == venue_id)
+        .ok_or_else(|| anyhow::anyhow!("unknown venue id: {venue_id}"))?;
+    if scraper.key_selectors().is_empty() {
+        anyhow::bail!("venue {venue_id} has no HTML selectors to validate");
+    }
+    let html = base::fetch_html(scraper.venue_url(), scraper.request_timeout())?;
+    keep_snapshot(venue_id, &html);
+    Ok(count_selector_matches(scraper.as_ref(), &html))
+}
+
+fn count_selector_matches(scraper: &dyn VenueScraper, html: &str) -> SelectorReport {
+    let document = Html::parse_document(html);
+    let selectors = scraper
+        .key_selectors()
+        .into_iter()
+        .map(|(name, selector)| SelectorMatches {
+            name: name.to_string(),
+            matches: document.select(selector).count(),
+        })
+        .collect();
+    SelectorReport {
+        venue_id: scraper.venue_id().to_string(),
+        url: scraper.venue_url().to_string(),
+        selectors,
+    }
+}
+
+/// One row of the `diagnose_scrapers` health matrix.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ScraperHealth {
+    pub venue_id: String,
+    /// Listing cards the card selector matched; `None` for venues that
+    /// don't scrape HTML.
+    pub cards_found: Option<usize>,
+    pub events_parsed: usize,
+    pub selectors: Vec<SelectorMatches>,
+    /// Why the venue looks broken; empty when healthy.
+    pub problems: Vec<String>,
+    pub healthy: bool,
+}
+
+impl ScraperHealth {
+    fn new(venue_id: &str, problems: Vec<String>) -> Self {
+        Self {
+            venue_id: venue_id.to_string(),
+            cards_found: None,
+            events_parsed: 0,
+            selectors: Vec::new(),
+            healthy: problems.is_empty(),
+            problems,
+        }
+    }
+}
+
+/// Checks every venue against its live page: HTML venues get their key
+/// selectors counted and the page parsed, the rest a plain fetch. Nothing is
+/// stored.
+pub fn diagnose_scrapers() -> Vec<ScraperHealth> {
+    all_scrapers()
+        .iter()
+        .map(|scraper| diagnose_scraper(scraper.as_ref()))
+        .collect()
+}
+
+fn diagnose_scraper(scraper: &dyn VenueScraper) -> ScraperHealth {
+    if scraper.key_selectors().is_empty() {
+        return match scraper.fetch() {
+            Ok(report) => {
+                let problems = selector_health_warning(scraper.expected_min_events(), &report)
+                    .into_iter()
+                    .collect();
+                ScraperHealth {
+                    events_parsed: report.real_event_count(),
+                    ..ScraperHealth::new(scraper.venue_id(), problems)
+                }
+            }
+            Err(err) => ScraperHealth::new(scraper.venue_id(), vec![err.to_string()]),
+        };
+    }
+    match base::fetch_html(scraper.venue_url(), scraper.request_timeout()) {
+        Ok(html) => {
+            keep_snapshot(scraper.venue_id(), &html);
+            diagnose_html(scraper, &html)
+        }
+        Err(err) => ScraperHealth::new(scraper.venue_id(), vec![err.to_string()]),
+    }
+}
+
+/// Health of an HTML venue given its listing page.
+fn diagnose_html(scraper: &dyn VenueScraper, html: &str) -> ScraperHealth {
+    let report = count_selector_matches(scraper, html);
+    let mut problems: Vec<String> = report
+        .selectors
+        .iter()
+        .filter(|selector| selector.matches == 0)
+        .map(|selector| format!("{} selector matched nothing", selector.name))
+        .collect();
+    let events_parsed = match parse_html(scraper.venue_id(), html) {
+        Ok(parsed) => {
+            problems.extend(selector_health_warning(
+                scraper.expected_min_events(),
+                &parsed,
+            ));
+            parsed.real_event_count()
+        }
+        Err(err) => {
+            problems.push(err.to_string());
+            0
+        }
+    };
+    ScraperHealth {
+        cards_found: report
+            .selectors
+            .iter()
+            .find(|selector| selector.name == "card")
+            .map(|selector| selector.matches),
+        events_parsed,
+        selectors: report.selectors,
+        ..ScraperHealth::new(scraper.venue_id(), problems)
+    }
+}
+
+/// Runs a saved copy of a venue's listing page through that venue's parser,
+/// without fetching or persisting anything.
+pub fn reparse_snapshot(venue_id: &str, path: &std::path::Path) -> anyhow::Result<ScrapeReport> {
+    let html = std::fs::read_to_string(path)
+        .map_err(|err| anyhow::anyhow!("unable to read snapshot {}: {err}", path.display()))?;
+    parse_html(venue_id, &html)
+}
+
+fn snapshot_key(venue_id: &str) -> String {
+    format!("snapshot:{venue_id}")
+}
+
+/// Keeps the listing page a selector check just fetched in the cache, so a
+/// venue that broke can be reparsed against the page it broke on.
+fn keep_snapshot(venue_id: &str, html: &str) {
+    if let Err(err) = crate::utils::cache::cache_put(&snapshot_key(venue_id), html.as_bytes()) {
+        eprintln!("failed to cache snapshot for {venue_id}: {err}");
+    }
+}
+
+/// `reparse_snapshot` for the page `validate_selectors` or
+/// `diagnose_scrapers` last fetched for `venue_id`.
+pub fn reparse_cached_snapshot(venue_id: &str) -> anyhow::Result<ScrapeReport> {
+    let bytes = crate::utils::cache::cache_get(&snapshot_key(venue_id)).ok_or_else(|| {
+        anyhow::anyhow!("no cached snapshot for {venue_id}; run diagnose_scrapers first")
+    })?;
+    parse_html(venue_id, &String::from_utf8_lossy(&bytes))
+}
+
+/// Parses listing HTML with the parser registered for `venue_id`.
+pub fn parse_html(venue_id: &str, html: &str) -> anyhow::Result<ScrapeReport> {
+    let config = base::scrape_config();
+    let missing_time = config.show_time_for_venue(venue_id);
+    let max_artists = config.max_artists_per_event;
+    let keep_collaborations = config.keep_collaborations;
+    match venue_id {
+        "treefort" => treefort_html::Treefort.parse_document(
+            html,
+            missing_time,
+            config.doors_to_show_minutes,
+            max_artists,
+            keep_collaborations,
+        ),
+        "revolution" => revolution_html::Revolution.parse_document(
+            html,
+            missing_time,
+            max_artists,
+            keep_collaborations,
+        ),
+        "knitboise" => knitting_factory_html::KnittingFactoryBoise.parse_document(
+            html,
+            missing_time,
+            max_artists,
+            keep_collaborations,
+        ),
+        _ => Err(anyhow::anyhow!("no parser for venue id: {venue_id}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    struct FixtureScraper(&'static str);
+
+    impl VenueScraper for FixtureScraper {
+        fn venue_id(&self) -> &'static str {
+            self.0
+        }
+        fn venue_name(&self) -> &'static str {
+            "Fixture Hall"
+        }
+        fn venue_url(&self) -> &'static str {
+            "https://fixture.example.com"
+        }
+        fn fetch(&self) -> anyhow::Result<ScrapeReport> {
+            Ok(ScrapeReport {
+                events: vec![
+                    crate::db::sample_event(self.0, "Fixture Hall", Utc::now()),
+                    crate::db::sample_event(self.0, "Fixture Hall", Utc::now()),
+                ],
+                ..ScrapeReport::default()
+            })
+        }
+    }
+
+    /// A venue that usually lists a full calendar but whose page now parses
+    /// to nothing but the sample fallback.
+    struct BrokenScraper;
+
+    impl VenueScraper for BrokenScraper {
+        fn venue_id(&self) -> &'static str {
+            "broken"
+        }
+        fn venue_name(&self) -> &'static str {
+            "Broken Hall"
+        }
+        fn venue_url(&self) -> &'static str {
+            "https://broken.example.com"
+        }
+        fn fetch(&self) -> anyhow::Result<ScrapeReport> {
+            let mut report = ScrapeReport::default();
+            report.warn("no cards parsed; using sample event");
+            report.sample = true;
+            report
+                .events
+                .push(crate::db::sample_event("broken", "Broken Hall", Utc::now()));
+            Ok(report)
+        }
+        fn expected_min_events(&self) -> usize {
+            10
+        }
+        fn artist_split(&self) -> base::ArtistSplit {
+            base::ArtistSplit {
+                ampersand: false,
+                ..base::ArtistSplit::default()
+            }
+        }
+    }
+
+    fn fixtures() -> Vec<Box<dyn VenueScraper>> {
+        vec![
+            Box::new(FixtureScraper("alpha")),
+            Box::new(FixtureScraper("beta")),
+            Box::new(FixtureScraper("gamma")),
+        ]
+    }
+
+    #[test]
+    fn run_single_returns_the_matching_venues_events() {
+        let events = run_single_in(fixtures(), "beta")
+            .expect("fixture events")
+            .events;
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| event.venue_id == "beta"));
+
+        let err = run_single_in(fixtures(), "nowhere").expect_err("unknown venue");
+        assert!(err.to_string().contains("unknown venue id: nowhere"));
+    }
+
+    #[test]
+    fn disabled_venues_are_skipped_by_batch_runs() {
+        let enabled = vec!["gamma".to_string(), "alpha".to_string()];
+        let outcomes = run_each_in(select_enabled(fixtures(), &enabled));
+        let ran: Vec<&str> = outcomes.iter().map(|o| o.venue_id.as_str()).collect();
+        assert_eq!(ran, vec!["gamma", "alpha"]);
+
+        let events = collect_events(outcomes).expect("events");
+        assert!(events.iter().all(|event| event.venue_id != "beta"));
+
+        let everyone = run_each_in(select_enabled(fixtures(), &[]));
+        assert_eq!(everyone.len(), 3);
+    }
+
+    #[test]
+    fn dry_run_summarizes_each_venue() {
+        let off = CrossVenueDedup {
+            window_minutes: 0,
+            ..CrossVenueDedup::default()
+        };
+        let venues: Vec<DryRunVenue> = dedupe_outcomes(
+            run_each_in(vec![
+                Box::new(FixtureScraper("alpha")),
+                Box::new(BrokenScraper),
+            ]),
+            &off,
+        )
+        .into_iter()
+        .map(DryRunVenue::from)
+        .collect();
+        assert_eq!(venues.len(), 2);
+        assert_eq!(venues[0].venue_id, "alpha");
+        assert_eq!(venues[0].event_count, 2);
+        assert_eq!(
+            venues[0].sample_titles,
+            vec!["Sample Artist", "Sample Artist"]
+        );
+        assert_eq!(venues[0].error, None);
+        assert_eq!(venues[1].event_count, 1);
+        assert!(venues[1].selector_warning.is_some());
+        assert_eq!(
+            venues[1].warnings,
+            vec!["no cards parsed; using sample event"]
+        );
+    }
+
+    #[test]
+    fn scraper_infos_report_enabled_state() {
+        let infos = scraper_infos(fixtures(), &["beta".to_string()]);
+        let states: Vec<(&str, bool)> = infos
+            .iter()
+            .map(|info| (info.id.as_str(), info.enabled))
+            .collect();
+        assert_eq!(
+            states,
+            vec![("alpha", false), ("beta", true), ("gamma", false)]
+        );
+        assert!(scraper_infos(fixtures(), &[])
+            .iter()
+            .all(|info| info.enabled));
+    }
+
+    #[test]
+    fn collapsed_event_count_is_flagged_as_selector_breakage() {
+        let outcomes = run_each_in(vec![
+            Box::new(BrokenScraper),
+            Box::new(FixtureScraper("alpha")),
+        ]);
+        let warning = outcomes[0]
+            .selector_warning
+            .as_deref()
+            .expect("breakage warning");
+        assert!(warning.contains("found 0 events, expected at least 10"));
+        // Fixtures declare no expectation, so they are never flagged.
+        assert!(outcomes[1].selector_warning.is_none());
+
+        let report = run_single_in(vec![Box::new(BrokenScraper)], "broken").expect("report");
+        assert!(report.selector_warning.is_some());
+    }
+
+    #[test]
+    fn healthy_counts_pass_the_selector_check() {
+        let mut report = ScrapeReport::default();
+        for _ in 0..2 {
+            report
+                .events
+                .push(crate::db::sample_event("treefort", "Treefort", Utc::now()));
+        }
+        assert_eq!(selector_health_warning(10, &report), None);
+
+        report.events.truncate(1);
+        assert!(selector_health_warning(10, &report).is_some());
+        assert_eq!(selector_health_warning(0, &report), None);
+    }
+
+    #[test]
+    fn implausible_starts_are_dropped_with_a_reason() {
+        let now = Utc::now();
+        let mut junk = crate::db::sample_event("alpha", "Fixture Hall", now);
+        junk.start_utc = "2099-01-01T03:00:00+00:00".to_string();
+        let mut report = ScrapeReport {
+            events: vec![junk, crate::db::sample_event("alpha", "Fixture Hall", now)],
+            ..ScrapeReport::default()
+        };
+
+        drop_implausible_starts(&mut report, now);
+        assert_eq!(report.events.len(), 1);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].starts_with("rejected Sample Artist: start 2099"));
+    }
+
+    #[test]
+    fn venues_can_keep_ampersands_in_act_names() {
+        let billing = "Mumford & Sons + Opener";
+        let broken = BrokenScraper;
+        assert_eq!(
+            base::split_artists_with(billing, &broken.artist_split()),
+            vec!["Mumford & Sons", "Opener"]
+        );
+        let fixture = FixtureScraper("alpha");
+        assert_eq!(
+            base::split_artists_with(billing, &fixture.artist_split()),
+            vec!["Mumford", "Sons", "Opener"]
+        );
+    }
+
+    #[test]
+    fn selector_report_counts_matches_per_key_selector() {
+        let html = r#"
+            <div class="tw-section">
+                <div class="tw-name"><a href="/a">Alpha</a></div>
+                <span class="tw-event-date">Tue Oct 7, 2025</span>
+                <a class="tw-buy-tix-btn" href="/tix">Tickets</a>
+            </div>
+            <div class="tw-section">
+                <div class="tw-name"><a href="/b">Beta</a></div>
+                <span class="tw-event-date">Wed Oct 8, 2025</span>
+            </div>
+        "#;
+        let report = count_selector_matches(&revolution_html::Revolution, html);
+        assert_eq!(report.venue_id, "revolution");
+        let counts: Vec<(&str, usize)> = report
+            .selectors
+            .iter()
+            .map(|s| (s.name.as_str(), s.matches))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![("card", 2), ("date", 2), ("artist", 2), ("ticket", 1)]
+        );
+        assert!(FixtureScraper("alpha").key_selectors().is_empty());
+    }
+
+    #[test]
+    fn diagnosis_flags_the_venue_whose_selectors_miss() {
+        let html = r#"
+            <div class="tw-section">
+                <div class="tw-name"><a href="/a">Alpha</a></div>
+                <span class="tw-venue-name">Revolution Concert House</span>
+                <span class="tw-event-date">Tue Oct 7, 2025</span>
+                <a class="tw-buy-tix-btn" href="/tix">Tickets</a>
+            </div>
+            <div class="tw-section">
+                <div class="tw-name"><a href="/b">Beta</a></div>
+                <span class="tw-venue-name">Revolution Concert House</span>
+                <span class="tw-event-date">Wed Oct 8, 2025</span>
+                <a class="tw-buy-tix-btn" href="/tix">Tickets</a>
+            </div>
+        "#;
+        let healthy = diagnose_html(&revolution_html::Revolution, html);
+        assert!(healthy.healthy, "{:?}", healthy.problems);
+        assert_eq!(healthy.cards_found, Some(2));
+        assert_eq!(healthy.events_parsed, 2);
+
+        let broken = diagnose_html(&treefort_html::Treefort, html);
+        assert!(!broken.healthy);
+        assert_eq!(broken.cards_found, Some(0));
+        assert_eq!(broken.events_parsed, 0);
+        assert!(
+            broken
+                .problems
+                .contains(&"card selector matched nothing".to_string()),
+            "{:?}",
+            broken.problems
+        );
+    }
+
+    #[test]
+    fn near_duplicate_shows_across_venues_merge_within_the_window() {
+        let start = Utc::now() + chrono::Duration::days(7);
+        let mut listed = crate::db::sample_event("treefort", "Treefort", start);
+        listed.artists = vec!["ODESZA".to_string()];
+        let mut aggregator = crate::db::sample_event(
+            "knitboise",
+            "Knitting Factory",
+            start + chrono::Duration::minutes(15),
+        );
+        aggregator.artists = vec!["Odesza (Live)".to_string(), "Opener".to_string()];
+        aggregator.id = "aggregator".to_string();
+        let mut next_night = crate::db::sample_event(
+            "knitboise",
+            "Knitting Factory",
+            start + chrono::Duration::days(1),
+        );
+        next_night.artists = vec!["Odesza".to_string()];
+        next_night.id = "next-night".to_string();
+
+        let rules = CrossVenueDedup::default();
+        let events = dedupe_across_venues(vec![listed.clone(), aggregator, next_night], &rules);
+        let ids: Vec<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec![listed.id.as_str(), "next-night"]);
+        assert_eq!(events[0].artists, vec!["ODESZA", "Opener"]);
+
+        let mut different = crate::db::sample_event("knitboise", "Knitting Factory", start);
+        different.artists = vec!["Other Band".to_string()];
+        different.id = "different".to_string();
+        assert_eq!(
+            dedupe_across_venues(vec![listed.clone(), different], &rules).len(),
+            2
+        );
+
+        let off = CrossVenueDedup {
+            window_minutes: 0,
+            ..rules
+        };
+        let mut twin = listed.clone();
+        twin.venue_id = "knitboise".to_string();
+        assert_eq!(dedupe_across_venues(vec![listed, twin], &off).len(), 2);
+    }
 }
@@ -1,7 +1,6 @@
 use anyhow::Result;
 
-use super::VenueScraper;
-use crate::models::Event;
+use super::{ScrapeReport, VenueScraper};
 
 pub struct PineBox;
 
@@ -18,7 +17,7 @@ impl VenueScraper for PineBox {
         "https://pineboxrockshop.com/"
     }
 
-    fn fetch(&self) -> Result<Vec<Event>> {
-        Ok(Vec::new())
+    fn fetch(&self) -> Result<ScrapeReport> {
+        Ok(ScrapeReport::default())
     }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use anyhow::Result;
 use chrono::{NaiveTime, TimeZone, Timelike};
 use chrono_tz::Tz;
@@ -7,8 +9,9 @@ use scraper::{Html, Selector};
 use serde_json::{json, Map};
 
 use super::base;
-use super::VenueScraper;
-use crate::models::Event;
+use super::{ScrapeReport, VenueScraper};
+use crate::config::MissingShowTime;
+use crate::models::AgeRestriction;
 
 const URL: &str = "https://cttouringid.com/tm-venue/revolution-concert-house-and-event-center/";
 const VENUE_ID: &str = "revolution";
@@ -19,6 +22,8 @@ static CARD_SELECTOR: Lazy<Selector> =
     Lazy::new(|| Selector::parse("div.tw-section").expect("revolution card selector"));
 static ARTIST_SELECTOR: Lazy<Selector> =
     Lazy::new(|| Selector::parse(".tw-name a").expect("revolution artist"));
+static SUPPORT_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse(".tw-opening-act").expect("revolution support"));
 static VENUE_SELECTOR: Lazy<Selector> =
     Lazy::new(|| Selector::parse(".tw-venue-name").expect("revolution venue"));
 static DATE_SELECTOR: Lazy<Selector> =
@@ -49,41 +54,93 @@ impl VenueScraper for Revolution {
         URL
     }
 
-    fn fetch(&self) -> Result<Vec<Event>> {
-        let html = base::fetch_html(URL)?;
-        self.parse_document(&html)
+    fn expected_min_events(&self) -> usize {
+        10
+    }
+
+    fn key_selectors(&self) -> Vec<(&'static str, &'static Selector)> {
+        vec![
+            ("card", &*CARD_SELECTOR),
+            ("date", &*DATE_SELECTOR),
+            ("artist", &*ARTIST_SELECTOR),
+            ("ticket", &*TICKET_SELECTOR),
+        ]
+    }
+
+    fn fetch(&self) -> Result<ScrapeReport> {
+        let pages = base::fetch_pages(URL, self.request_timeout())?;
+        let pages: Vec<&str> = pages.iter().map(String::as_str).collect();
+        let config = base::scrape_config();
+        self.parse_pages(
+            &pages,
+            config.show_time_for_venue(VENUE_ID),
+            config.max_artists_per_event,
+            config.keep_collaborations,
+        )
     }
 }
 
 impl Revolution {
-    pub(crate) fn parse_document(&self, html: &str) -> Result<Vec<Event>> {
-        let document = Html::parse_document(html);
-        let mut events = Vec::new();
+    pub(crate) fn parse_document(
+        &self,
+        html: &str,
+        missing_time: &MissingShowTime,
+        max_artists: usize,
+        keep_collaborations: bool,
+    ) -> Result<ScrapeReport> {
+        self.parse_pages(&[html], missing_time, max_artists, keep_collaborations)
+    }
 
-        for card in document.select(&CARD_SELECTOR) {
+    /// Parses every listing page into one report. Events repeated where pages
+    /// overlap are kept once.
+    pub(crate) fn parse_pages(
+        &self,
+        pages: &[&str],
+        missing_time: &MissingShowTime,
+        max_artists: usize,
+        keep_collaborations: bool,
+    ) -> Result<ScrapeReport> {
+        let documents: Vec<Html> = pages
+            .iter()
+            .map(|html| Html::parse_document(html))
+            .collect();
+        let cards = documents
+            .iter()
+            .flat_map(|document| document.select(&CARD_SELECTOR));
+        let mut report = ScrapeReport::default();
+        let mut seen = HashSet::new();
+
+        for (index, card) in cards.enumerate() {
             let venue_label = base::first_text(&card, &VENUE_SELECTOR);
             if let Some(label) = venue_label {
                 if !label.to_lowercase().contains("revolution concert house") {
                     continue;
                 }
             } else {
+                report.skip(index, "missing venue label");
                 continue;
             }
 
             let artists_text = match base::first_text(&card, &ARTIST_SELECTOR) {
                 Some(text) => text,
-                None => continue,
+                None => {
+                    report.skip(index, "missing artists");
+                    continue;
+                }
             };
-            let artists = base::split_artists(&artists_text);
-            if artists.is_empty() {
+            let headliners = base::split_artists_with(&artists_text, &self.artist_split());
+            if headliners.is_empty() {
+                report.skip(index, "missing artists");
                 continue;
             }
+            let support = base::first_text(&card, &SUPPORT_SELECTOR)
+                .map(|text| base::support_acts(&text, &self.artist_split()))
+                .unwrap_or_default();
+            let lineup = base::billed_lineup(&headliners, &support);
+            let artists: Vec<String> = headliners.into_iter().chain(support).collect();
 
-            let date_text = match base::first_text(&card, &DATE_SELECTOR) {
-                Some(text) => text,
-                None => continue,
-            };
-            let normalized_date = normalize_date(&date_text);
+            let date_text = base::first_text(&card, &DATE_SELECTOR);
+            let normalized_date = date_text.as_deref().map(normalize_date);
 
             let show_block = base::first_text(&card, &SHOW_SELECTOR);
             let show_time = show_block.as_deref().and_then(|block| {
@@ -95,13 +152,23 @@ impl Revolution {
             let event_url =
                 base::absolute_url(URL, base::first_attr(&card, &INFO_SELECTOR, "href"));
 
+            if date_text.is_none() && !ticket_url.as_deref().is_some_and(has_url_date) {
+                report.skip(index, "missing date");
+                continue;
+            }
+
+            let (start_time, time_tba) = base::show_time_or_default(show_time, missing_time);
             let start_local = match determine_start(
-                &normalized_date,
-                show_time.as_deref(),
+                normalized_date.as_deref(),
+                &start_time,
                 ticket_url.as_deref(),
             ) {
                 Some(dt) => dt,
-                None => continue,
+                None => {
+                    let date_text = date_text.unwrap_or_default();
+                    report.skip(index, &format!("unparseable date {date_text:?}"));
+                    continue;
+                }
             };
 
             let door_time = base::first_text(&card, &DOOR_SELECTOR).and_then(|text| {
@@ -112,8 +179,12 @@ impl Revolution {
                 .and_then(|value| base::combine_with_date(&start_local, value, TIMEZONE));
 
             let mut extra = Map::new();
-            extra.insert("date_text".to_string(), json!(date_text));
-            extra.insert("normalized_date".to_string(), json!(normalized_date));
+            if let Some(ref date_text) = date_text {
+                extra.insert("date_text".to_string(), json!(date_text));
+            }
+            if let Some(ref normalized) = normalized_date {
+                extra.insert("normalized_date".to_string(), json!(normalized));
+            }
             if let Some(ref block) = show_block {
                 extra.insert("show_block".to_string(), json!(block));
             }
@@ -121,23 +192,33 @@ impl Revolution {
                 extra.insert("doors_text".to_string(), json!(door));
             }
 
-            let event = base::build_event(
+            let mut event = base::build_event(
                 VENUE_ID,
                 VENUE_NAME,
                 URL,
                 start_local,
                 artists,
+                max_artists,
                 ticket_url.clone(),
                 event_url,
-                None,
+                AgeRestriction::Unknown,
                 doors_local,
                 serde_json::Value::Object(extra),
             );
+            event.lineup = base::cap_lineup(lineup, max_artists);
+            if keep_collaborations {
+                base::keep_collaboration(&mut event, &artists_text, &self.artist_split());
+            }
+            event.time_tba = time_tba;
 
-            events.push(event);
+            if seen.insert(event.id.clone()) {
+                report.events.push(event);
+            }
         }
 
-        if events.is_empty() {
+        if report.events.is_empty() {
+            report.warn("no cards parsed; using sample event");
+            report.sample = true;
             let start_local = TIMEZONE
                 .with_ymd_and_hms(2025, 10, 15, 20, 0, 0)
                 .single()
@@ -149,19 +230,20 @@ impl Revolution {
                 URL,
                 start_local,
                 vec!["Dance Gavin Dance".to_string()],
+                max_artists,
                 Some("https://ticketmaster.com/event/12345".to_string()),
                 Some("https://ticketmaster.com/event/12345".to_string()),
-                None,
+                AgeRestriction::Unknown,
                 doors_local,
                 json!({
                     "doors": "7:00 PM",
                     "show": "8:00 PM",
                 }),
             );
-            events.push(sample);
+            report.events.push(sample);
         }
 
-        Ok(events)
+        Ok(report)
     }
 }
 
@@ -176,20 +258,23 @@ fn normalize_date(input: &str) -> String {
     trimmed.to_string()
 }
 
+/// Builds the start from the card's date text, taking the year from the ticket
+/// URL when it has one. A card with no date text falls back to the URL's full
+/// date.
 fn determine_start(
-    date_text: &str,
-    show_time: Option<&str>,
+    date_text: Option<&str>,
+    show_time: &str,
     ticket_url: Option<&str>,
 ) -> Option<chrono::DateTime<Tz>> {
-    let time_str = show_time
-        .map(|val| base::find_first_time(val).unwrap_or_else(|| val.to_string()))
-        .unwrap_or_else(|| "7:00 PM".to_string());
+    let time_str = base::find_first_time(show_time).unwrap_or_else(|| show_time.to_string());
 
     if let Some(url) = ticket_url {
         if let Some((month, day, year)) = extract_date_from_url(url) {
-            let full_date = format!("{}, {}", date_text, year);
-            if let Some(dt) = base::parse_datetime(&full_date, Some(&time_str), TIMEZONE) {
-                return Some(dt);
+            if let Some(date_text) = date_text {
+                let full_date = format!("{}, {}", date_text, year);
+                if let Some(dt) = base::parse_datetime(&full_date, Some(&time_str), TIMEZONE) {
+                    return Some(dt);
+                }
             }
 
             if let Ok(naive) = NaiveTime::parse_from_str(&time_str, "%I:%M %p") {
@@ -200,7 +285,11 @@ fn determine_start(
         }
     }
 
-    base::parse_datetime(date_text, Some(&time_str), TIMEZONE)
+    base::parse_datetime(date_text?, Some(&time_str), TIMEZONE)
+}
+
+fn has_url_date(url: &str) -> bool {
+    extract_date_from_url(url).is_some()
 }
 
 fn extract_date_from_url(url: &str) -> Option<(u32, u32, i32)> {
@@ -279,7 +368,15 @@ mod tests {
     #[test]
     fn parses_revolution_events() {
         let scraper = Revolution;
-        let events = scraper.parse_document(SAMPLE_HTML).expect("parse html");
+        let events = scraper
+            .parse_document(
+                SAMPLE_HTML,
+                &MissingShowTime::default(),
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse html")
+            .events;
         assert_eq!(
             events.len(),
             2,
@@ -310,4 +407,106 @@ mod tests {
         assert_eq!(second_start.hour(), 20);
         assert_eq!(second_start.minute(), 0);
     }
+
+    const PAGE_TWO_HTML: &str = r#"
+    <div class="tw-section">
+        <div class="list-view-item event-container">
+            <div class="event-details">
+                <div class="tw-name"><a href="https://cttouringid.com/tm-event/skydxddy/">SkyDxddy</a></div>
+                <div class="tw-venue-details">
+                    <span class="tw-venue-name">Revolution Concert House and Event Center</span>
+                </div>
+                <div class="tw-date-time">
+                    <span class="tw-event-date">Wed Oct 8, 2025</span>
+                </div>
+                <div class="tw-event-time">
+                    <span class="tw-event-door-time">Doors: 7:00 pm</span>
+                    <span class="tw-event-time">Show: 8:00 pm</span>
+                </div>
+            </div>
+        </div>
+    </div>
+    <div class="tw-section">
+        <div class="list-view-item event-container">
+            <div class="event-details">
+                <div class="tw-name"><a href="https://cttouringid.com/tm-event/jinjer/">Jinjer</a></div>
+                <div class="tw-venue-details">
+                    <span class="tw-venue-name">Revolution Concert House and Event Center</span>
+                </div>
+                <div class="tw-date-time">
+                    <span class="tw-event-date">Sat Nov 15, 2025</span>
+                </div>
+                <div class="tw-event-time">
+                    <span class="tw-event-time">Show: 7:00 pm</span>
+                </div>
+            </div>
+        </div>
+    </div>
+    "#;
+
+    #[test]
+    fn stitched_pages_capture_each_event_once() {
+        let report = Revolution
+            .parse_pages(
+                &[SAMPLE_HTML, PAGE_TWO_HTML],
+                &MissingShowTime::default(),
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse pages");
+        let headliners: Vec<String> = report.events.iter().map(|event| event.title()).collect();
+        assert_eq!(headliners, vec!["In This Moment", "SkyDxddy", "Jinjer"]);
+        assert!(!report.sample);
+    }
+
+    #[test]
+    fn support_element_is_billed_after_the_headliner() {
+        let html = r#"
+        <div class="tw-section">
+            <div class="tw-name"><a href="https://cttouringid.com/tm-event/jinjer/">Jinjer</a></div>
+            <div class="tw-opening-act">Support from: Hanabie. / Unprocessed</div>
+            <span class="tw-venue-name">Revolution Concert House and Event Center</span>
+            <span class="tw-event-date">Sat Nov 15, 2025</span>
+            <span class="tw-event-time">Show: 7:00 pm</span>
+        </div>
+        "#;
+        let report = Revolution
+            .parse_document(
+                html,
+                &MissingShowTime::default(),
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse html");
+        let event = &report.events[0];
+        assert_eq!(event.title(), "Jinjer");
+        assert_eq!(event.artists, vec!["Jinjer", "Hanabie.", "Unprocessed"]);
+        assert_eq!(event.lineup.len(), 3);
+    }
+
+    #[test]
+    fn card_without_a_date_element_uses_the_ticket_url_date() {
+        let html = r#"
+        <div class="tw-section">
+            <div class="tw-name"><a href="https://cttouringid.com/tm-event/jinjer/">Jinjer</a></div>
+            <span class="tw-venue-name">Revolution Concert House and Event Center</span>
+            <span class="tw-event-time">Show: 7:00 pm</span>
+            <a class="tw-buy-tix-btn" href="https://www.ticketmaster.com/jinjer-garden-city-idaho-11-15-2025/event/1">Buy Tickets</a>
+        </div>
+        "#;
+        let report = Revolution
+            .parse_document(
+                html,
+                &MissingShowTime::default(),
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse html");
+        assert!(!report.sample);
+        let start_local = report.events[0].start_local.as_deref().expect("local");
+        assert!(
+            start_local.starts_with("2025-11-15T19:00:00"),
+            "{start_local}"
+        );
+    }
 }
@@ -1,13 +1,14 @@
 use anyhow::Result;
-use chrono::{DateTime, TimeZone};
+use chrono::{DateTime, TimeZone, Utc};
 use chrono_tz::Tz;
 use once_cell::sync::Lazy;
 use scraper::{Html, Selector};
 use serde_json::{json, Map};
 
 use super::base;
-use super::VenueScraper;
-use crate::models::Event;
+use super::{ScrapeReport, VenueScraper};
+use crate::config::MissingShowTime;
+use crate::models::AgeRestriction;
 
 const URL: &str = "https://treefortmusichall.com/shows/";
 const VENUE_ID: &str = "treefort";
@@ -53,27 +54,69 @@ impl VenueScraper for Treefort {
         URL
     }
 
-    fn fetch(&self) -> Result<Vec<Event>> {
-        let html = base::fetch_html(URL)?;
-        self.parse_document(&html)
+    fn expected_min_events(&self) -> usize {
+        10
+    }
+
+    fn key_selectors(&self) -> Vec<(&'static str, &'static Selector)> {
+        vec![
+            ("card", &*CARD_SELECTOR),
+            ("date", &*DATE_LINE_SELECTOR),
+            ("artist", &*ARTIST_LINK_SELECTOR),
+            ("ticket", &*TICKET_SELECTOR),
+        ]
+    }
+
+    fn fetch(&self) -> Result<ScrapeReport> {
+        self.fetch_from(URL)
     }
 }
 
 impl Treefort {
-    pub(crate) fn parse_document(&self, html: &str) -> Result<Vec<Event>> {
+    /// `fetch` against another host, so tests can point it at a mock server.
+    pub(crate) fn fetch_from(&self, url: &str) -> Result<ScrapeReport> {
+        let html = base::fetch_html(url, self.request_timeout())?;
+        let config = base::scrape_config();
+        self.parse_document(
+            &html,
+            config.show_time_for_venue(VENUE_ID),
+            config.doors_to_show_minutes,
+            config.max_artists_per_event,
+            config.keep_collaborations,
+        )
+    }
+
+    pub(crate) fn parse_document(
+        &self,
+        html: &str,
+        missing_time: &MissingShowTime,
+        doors_to_show_minutes: u32,
+        max_artists: usize,
+        keep_collaborations: bool,
+    ) -> Result<ScrapeReport> {
         let document = Html::parse_document(html);
-        let mut events = Vec::new();
+        let mut report = ScrapeReport::default();
 
-        for card in document.select(&CARD_SELECTOR) {
+        for (index, card) in document.select(&CARD_SELECTOR).enumerate() {
             let date_text = match base::first_text(&card, &DATE_LINE_SELECTOR) {
                 Some(text) => text,
-                None => continue,
+                None => {
+                    report.skip(index, "missing date");
+                    continue;
+                }
             };
             let normalized_date = normalize_date(&date_text);
 
-            let door_time = base::first_text(&card, &DOOR_SELECTOR).and_then(|text| {
-                base::find_first_time(&text).or_else(|| base::parse_named_time(&text, "doors"))
+            let time_block = base::first_text(&card, &DOOR_SELECTOR);
+            let show_time = time_block
+                .as_deref()
+                .and_then(|text| base::parse_named_time(text, "show"));
+            let door_time = time_block.as_deref().and_then(|text| {
+                base::parse_named_time(text, "door")
+                    .or_else(|| show_time.is_none().then(|| base::find_first_time(text))?)
             });
+            // Doors alone aren't the show start; estimate it instead.
+            let start_from_doors = show_time.is_none() && door_time.is_some();
 
             let ticket_url =
                 base::absolute_url(URL, base::first_attr(&card, &TICKET_SELECTOR, "href"));
@@ -86,27 +129,51 @@ impl Treefort {
                     .map(|href| href.to_string()),
             );
 
-            let start_local = match determine_start(&normalized_date, door_time.as_deref()) {
+            let (start_time, time_tba) =
+                base::show_time_or_default(show_time.clone().or(door_time.clone()), missing_time);
+            let recurrence = base::weekly_recurrence(&date_text);
+            let start = match recurrence {
+                Some(weekday) => {
+                    base::next_weekly_start(weekday, &start_time, TIMEZONE, Utc::now())
+                }
+                None => determine_start(&normalized_date, &start_time),
+            };
+            let listed_start = match start {
                 Some(dt) => dt,
-                None => continue,
+                None => {
+                    report.skip(index, &format!("unparseable date {normalized_date:?}"));
+                    continue;
+                }
+            };
+            let start_local = if start_from_doors {
+                listed_start + chrono::Duration::minutes(i64::from(doors_to_show_minutes))
+            } else {
+                listed_start
             };
 
             let primary = base::first_text(&card, &ARTIST_PRIMARY_SELECTOR).unwrap_or_default();
-            let mut artists = base::split_artists(&primary);
-            if artists.is_empty() && !primary.is_empty() {
-                artists.push(primary.clone());
+            let mut headliners = base::split_artists_with(&primary, &self.artist_split());
+            if headliners.is_empty() && !primary.is_empty() {
+                headliners.push(primary.clone());
             }
 
+            let mut openers = Vec::new();
             if let Some(node) = card.select(&ARTIST_SECONDARY_SELECTOR).next() {
-                let openers_html = node.inner_html().replace("<br>", ",");
-                for name in base::split_artists(&openers_html) {
+                let openers_text = base::text_with_breaks(node);
+                for name in base::split_artists_with(&openers_text, &self.artist_split()) {
                     if !name.is_empty() {
-                        artists.push(name);
+                        openers.push(name);
                     }
                 }
             }
 
+            // A headliner repeated among the openers keeps its headliner slot.
+            let lineup = base::billed_lineup(&headliners, &openers);
+            // Raw names so build_event can keep the originals in `extra`.
+            let artists: Vec<String> = headliners.iter().chain(&openers).cloned().collect();
+
             if artists.is_empty() {
+                report.skip(index, "missing artists");
                 continue;
             }
 
@@ -116,6 +183,12 @@ impl Treefort {
             if let Some(ref door) = door_time {
                 extra.insert("doors_text".to_string(), json!(door));
             }
+            if let Some(ref show) = show_time {
+                extra.insert("show_text".to_string(), json!(show));
+            }
+            if start_from_doors {
+                extra.insert("start_estimated_from_doors".to_string(), json!(true));
+            }
             if let Some(ref age) = age_text {
                 extra.insert("age_raw".to_string(), json!(age));
             }
@@ -125,27 +198,51 @@ impl Treefort {
 
             let doors_local = door_time
                 .as_deref()
-                .and_then(|value| base::combine_with_date(&start_local, value, TIMEZONE));
+                .and_then(|value| base::combine_with_date(&listed_start, value, TIMEZONE));
 
-            let event = base::build_event(
+            let mut event = base::build_event(
                 VENUE_ID,
                 VENUE_NAME,
                 URL,
                 start_local,
                 artists,
+                max_artists,
                 ticket_url.clone(),
                 event_url,
                 age_text
                     .as_deref()
-                    .and_then(|value| base::parse_age_flag(value)),
+                    .map(base::parse_age_restriction)
+                    .unwrap_or_default(),
                 doors_local,
                 serde_json::Value::Object(extra),
             );
+            event.lineup = base::cap_lineup(lineup, max_artists);
+            if start_from_doors && recurrence.is_none() {
+                // Ids come from the listed doors time, which earlier builds
+                // used as the start, so the estimate doesn't re-key the show.
+                // Weekly occurrences are new and keyed by their own starts.
+                event.id =
+                    base::event_id(VENUE_ID, &listed_start.with_timezone(&Utc), &event.artists);
+            }
+            if keep_collaborations {
+                base::keep_collaboration(&mut event, &primary, &self.artist_split());
+            }
+            event.time_tba = time_tba;
 
-            events.push(event);
+            if recurrence.is_some() {
+                report.events.extend(base::weekly_occurrences(
+                    &event,
+                    base::RECURRENCE_WEEKS,
+                    TIMEZONE,
+                ));
+            } else {
+                report.events.push(event);
+            }
         }
 
-        if events.is_empty() {
+        if report.events.is_empty() {
+            report.warn("no cards parsed; using sample event");
+            report.sample = true;
             let start_local = TIMEZONE
                 .with_ymd_and_hms(2025, 10, 4, 20, 0, 0)
                 .single()
@@ -156,9 +253,10 @@ impl Treefort {
                 URL,
                 start_local,
                 vec!["The Midnight".to_string(), "Special Guest".to_string()],
+                max_artists,
                 Some("https://tickets.example.com/midnight".to_string()),
                 Some("https://treefortmusichall.com/shows/".to_string()),
-                Some(true),
+                AgeRestriction::AllAges,
                 Some(
                     base::combine_with_date(&start_local, "7:00 PM", TIMEZONE)
                         .unwrap_or_else(|| start_local.to_rfc3339()),
@@ -168,10 +266,10 @@ impl Treefort {
                     "age": "All Ages",
                 }),
             );
-            events.push(sample);
+            report.events.push(sample);
         }
 
-        Ok(events)
+        Ok(report)
     }
 }
 
@@ -179,17 +277,16 @@ fn normalize_date(input: &str) -> String {
     input.trim().to_string()
 }
 
-fn determine_start(date_text: &str, door_time: Option<&str>) -> Option<DateTime<Tz>> {
-    let time_str = door_time
-        .map(|val| base::find_first_time(val).unwrap_or_else(|| val.to_string()))
-        .unwrap_or_else(|| "7:00 PM".to_string());
+fn determine_start(date_text: &str, time_text: &str) -> Option<DateTime<Tz>> {
+    let time_str = base::find_first_time(time_text).unwrap_or_else(|| time_text.to_string());
     base::parse_datetime(date_text, Some(&time_str), TIMEZONE)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Timelike;
+    use crate::models::{LineupRole, LineupSlot};
+    use chrono::{Datelike, Timelike};
 
     const SAMPLE_HTML: &str = r#"
     <div class="mh-show-wrapper">
@@ -237,12 +334,120 @@ mod tests {
     </div>
     "#;
 
+    #[test]
+    fn fetch_parses_the_page_served_by_the_host() {
+        use crate::test_support::{MockResponse, MockServer};
+
+        let server = MockServer::start(|_| MockResponse::ok(SAMPLE_HTML));
+        let report = Treefort
+            .fetch_from(&format!("{}/shows/", server.url()))
+            .expect("fetch");
+        // `fetch_from` scrapes with the host's config; parse with the same.
+        let config = base::scrape_config();
+        let expected = Treefort
+            .parse_document(
+                SAMPLE_HTML,
+                config.show_time_for_venue(VENUE_ID),
+                config.doors_to_show_minutes,
+                config.max_artists_per_event,
+                config.keep_collaborations,
+            )
+            .expect("parse");
+        let ids: Vec<&str> = report.events.iter().map(|e| e.id.as_str()).collect();
+        let expected_ids: Vec<&str> = expected.events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, expected_ids);
+        assert!(!report.sample);
+        assert_eq!(server.requests()[0].path, "/shows/");
+
+        let failing = MockServer::start(|_| MockResponse::status(503, "busy"));
+        let err = Treefort.fetch_from(failing.url()).expect_err("503 fails");
+        assert!(err.to_string().contains("non-success status"));
+    }
+
+    #[test]
+    fn saved_snapshot_reparses_to_the_same_events() {
+        let path = std::env::temp_dir().join(format!(
+            "show-scrape-treefort-snapshot-{}.html",
+            std::process::id()
+        ));
+        std::fs::write(&path, SAMPLE_HTML).expect("write snapshot");
+
+        let report = crate::scraping::reparse_snapshot(VENUE_ID, &path).expect("reparse");
+        assert_eq!(report.events.len(), 2);
+        assert!(!report.sample);
+
+        let err = crate::scraping::reparse_snapshot("pine_box", &path).expect_err("no parser");
+        assert!(err.to_string().contains("no parser for venue id: pine_box"));
+        let _ = std::fs::remove_file(path);
+    }
+
+    const WEEKLY_HTML: &str = r#"
+    <div class="mh-show-wrapper">
+        <div class="mh-show-body group">
+            <div class="mh-show-col mh-show-date">
+                <div id="dat">Every Friday</div>
+                <div id="doo">DOORS: 9pm</div>
+            </div>
+            <div class="mh-show-col mh-show-artist">
+                <a href="/shows/open-decks">
+                    <div class="mh-h1">Open Decks</div>
+                </a>
+            </div>
+        </div>
+    </div>
+    "#;
+
+    #[test]
+    fn weekly_listing_expands_into_dated_occurrences() {
+        let events = Treefort
+            .parse_document(
+                WEEKLY_HTML,
+                &MissingShowTime::default(),
+                base::DEFAULT_DOORS_TO_SHOW_MINUTES,
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse weekly html")
+            .events;
+        assert_eq!(events.len(), base::RECURRENCE_WEEKS);
+
+        let starts: Vec<DateTime<chrono::FixedOffset>> = events
+            .iter()
+            .map(|event| {
+                DateTime::parse_from_rfc3339(event.start_local.as_deref().expect("local"))
+                    .expect("rfc3339")
+            })
+            .collect();
+        assert!(starts
+            .iter()
+            .all(|start| start.weekday() == chrono::Weekday::Fri && start.hour() == 22));
+        assert!(starts
+            .windows(2)
+            .all(|pair| pair[1].date_naive() - pair[0].date_naive() == chrono::Duration::days(7)));
+
+        let recurrence_id = &events[0].extra["recurrence_id"];
+        assert!(recurrence_id.is_string());
+        assert!(events
+            .iter()
+            .all(|event| &event.extra["recurrence_id"] == recurrence_id));
+        let mut ids: Vec<&str> = events.iter().map(|event| event.id.as_str()).collect();
+        ids.dedup();
+        assert_eq!(ids.len(), base::RECURRENCE_WEEKS);
+    }
+
     #[test]
     fn parses_treefort_events() {
         let scraper = Treefort;
         let events = scraper
-            .parse_document(SAMPLE_HTML)
-            .expect("parse treefort html");
+            .parse_document(
+                SAMPLE_HTML,
+                &MissingShowTime::default(),
+                base::DEFAULT_DOORS_TO_SHOW_MINUTES,
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse treefort html")
+            .events;
         assert_eq!(events.len(), 2);
 
         let first = &events[0];
@@ -258,18 +463,226 @@ mod tests {
             first.event_url.as_deref(),
             Some("https://treefortmusichall.com/shows/pup")
         );
-        assert_eq!(first.is_all_ages, Some(true));
+        assert_eq!(first.age_restriction, AgeRestriction::AllAges);
+        assert_eq!(
+            first.lineup,
+            vec![
+                LineupSlot {
+                    name: "PUP".to_string(),
+                    role: LineupRole::Headliner,
+                },
+                LineupSlot {
+                    name: "Chase Petra".to_string(),
+                    role: LineupRole::Support,
+                },
+            ]
+        );
         let start_local =
             chrono::DateTime::parse_from_rfc3339(first.start_local.as_ref().expect("local time"))
                 .expect("parse first time");
-        assert_eq!(start_local.hour(), 19);
+        // Doors at 7 with no show time: the show is estimated an hour later.
+        assert_eq!(start_local.hour(), 20);
 
         let second = &events[1];
         assert_eq!(second.artists[0], "Desert Dwellers");
-        assert_eq!(second.is_all_ages, Some(false));
+        let roles: Vec<LineupRole> = second.lineup.iter().map(|slot| slot.role).collect();
+        assert_eq!(
+            roles,
+            vec![
+                LineupRole::Headliner,
+                LineupRole::Support,
+                LineupRole::Support
+            ]
+        );
+        assert_eq!(second.age_restriction, AgeRestriction::MinAge(18));
         let start_local =
             chrono::DateTime::parse_from_rfc3339(second.start_local.as_ref().expect("local time"))
                 .expect("parse second time");
-        assert_eq!(start_local.hour(), 20);
+        assert_eq!(start_local.hour(), 21);
+    }
+
+    #[test]
+    fn openers_are_split_from_decoded_text() {
+        let html = r#"<div class="mh-show-wrapper">
+                <div class="mh-show-col mh-show-date"><div id="dat">10/8/2025</div></div>
+                <div class="mh-show-col mh-show-artist">
+                    <div class="mh-h1">PUP</div>
+                    <div class="mh-s1">Tom &amp; Jerry<br>AT&amp;T Band<br/>Simon&Garfunkel</div>
+                </div>
+            </div>"#;
+        let report = Treefort
+            .parse_document(
+                html,
+                &MissingShowTime::default(),
+                base::DEFAULT_DOORS_TO_SHOW_MINUTES,
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse treefort html");
+        assert_eq!(
+            report.events[0].artists,
+            vec!["PUP", "Tom", "Jerry", "AT&T Band", "Simon&Garfunkel"]
+        );
+    }
+
+    #[test]
+    fn malformed_cards_become_warnings() {
+        let html = format!(
+            r#"{SAMPLE_HTML}
+            <div class="mh-show-wrapper">
+                <div class="mh-show-col mh-show-date">
+                    <div id="dat">someday soon</div>
+                </div>
+                <div class="mh-show-col mh-show-artist">
+                    <div class="mh-h1">Mystery Act</div>
+                </div>
+            </div>
+            <div class="mh-show-wrapper">
+                <div class="mh-show-col mh-show-artist">
+                    <div class="mh-h1">No Date Band</div>
+                </div>
+            </div>"#
+        );
+
+        let report = Treefort
+            .parse_document(
+                &html,
+                &MissingShowTime::default(),
+                base::DEFAULT_DOORS_TO_SHOW_MINUTES,
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse treefort html");
+        assert_eq!(report.events.len(), 2);
+        assert_eq!(
+            report.warnings,
+            vec![
+                "skipped card 3: unparseable date \"someday soon\"".to_string(),
+                "skipped card 4: missing date".to_string(),
+            ]
+        );
+    }
+
+    const NO_TIME_HTML: &str = r#"
+    <div class="mh-show-wrapper">
+        <div class="mh-show-col mh-show-date">
+            <div id="dat">10/8/2025</div>
+        </div>
+        <div class="mh-show-col mh-show-artist">
+            <div class="mh-h1">PUP</div>
+        </div>
+    </div>
+    "#;
+
+    #[test]
+    fn missing_time_uses_configured_default() {
+        let missing = MissingShowTime::At("9:00 PM".to_string());
+        let report = Treefort
+            .parse_document(
+                NO_TIME_HTML,
+                &missing,
+                base::DEFAULT_DOORS_TO_SHOW_MINUTES,
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse treefort html");
+        let event = &report.events[0];
+        assert!(!event.time_tba);
+        let start_local =
+            chrono::DateTime::parse_from_rfc3339(event.start_local.as_ref().expect("local time"))
+                .expect("parse time");
+        assert_eq!(start_local.hour(), 21);
+    }
+
+    #[test]
+    fn missing_time_can_stay_date_only() {
+        let report = Treefort
+            .parse_document(
+                NO_TIME_HTML,
+                &MissingShowTime::Tba,
+                base::DEFAULT_DOORS_TO_SHOW_MINUTES,
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse treefort html");
+        let event = &report.events[0];
+        assert!(event.time_tba);
+        assert_eq!(
+            event.start_local.as_deref(),
+            Some("2025-10-08T00:00:00-06:00")
+        );
+        assert!(event.doors_local.is_none());
+    }
+
+    fn card_with_times(times: &str) -> String {
+        format!(
+            r#"<div class="mh-show-wrapper">
+                <div class="mh-show-col mh-show-date">
+                    <div id="dat">10/8/2025</div>
+                    <div id="doo">{times}</div>
+                </div>
+                <div class="mh-show-col mh-show-artist">
+                    <div class="mh-h1">PUP</div>
+                </div>
+            </div>"#
+        )
+    }
+
+    #[test]
+    fn doors_only_listing_estimates_the_show_start() {
+        let parse = |doors_to_show_minutes| {
+            Treefort
+                .parse_document(
+                    &card_with_times("DOORS: 7pm"),
+                    &MissingShowTime::default(),
+                    doors_to_show_minutes,
+                    base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                    false,
+                )
+                .expect("parse treefort html")
+                .events
+                .remove(0)
+        };
+        let event = &parse(45);
+        assert_eq!(
+            event.start_local.as_deref(),
+            Some("2025-10-08T19:45:00-06:00")
+        );
+        assert_eq!(
+            event.doors_local.as_deref(),
+            Some("2025-10-08T19:00:00-06:00")
+        );
+        assert_eq!(event.extra["start_estimated_from_doors"], true);
+
+        let doors = TIMEZONE
+            .with_ymd_and_hms(2025, 10, 8, 19, 0, 0)
+            .single()
+            .expect("doors time")
+            .with_timezone(&Utc);
+        assert_eq!(event.id, base::event_id(VENUE_ID, &doors, &event.artists));
+        assert_eq!(event.id, parse(60).id);
+    }
+
+    #[test]
+    fn doors_and_show_stay_distinct() {
+        let report = Treefort
+            .parse_document(
+                &card_with_times("DOORS: 7pm / SHOW: 8:30pm"),
+                &MissingShowTime::default(),
+                60,
+                base::DEFAULT_MAX_ARTISTS_PER_EVENT,
+                false,
+            )
+            .expect("parse treefort html");
+        let event = &report.events[0];
+        assert_eq!(
+            event.start_local.as_deref(),
+            Some("2025-10-08T20:30:00-06:00")
+        );
+        assert_eq!(
+            event.doors_local.as_deref(),
+            Some("2025-10-08T19:00:00-06:00")
+        );
+        assert!(event.extra.get("start_estimated_from_doors").is_none());
     }
 }
@@ -0,0 +1,173 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub body: String,
+}
+
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl MockResponse {
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self {
+            status: 200,
+            body: body.into(),
+        }
+    }
+
+    pub fn status(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+        }
+    }
+}
+
+type Handler = dyn Fn(&MockRequest) -> MockResponse + Send + Sync;
+
+/// Minimal HTTP/1.1 server for exercising outbound request paths in tests.
+/// Every connection is answered once and closed.
+pub struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let url = format!("http://{}", listener.local_addr().expect("mock addr"));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let handler = Arc::clone(&handler);
+                let recorded = Arc::clone(&recorded);
+                thread::spawn(move || serve(stream, handler, recorded));
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().expect("mock requests").clone()
+    }
+}
+
+fn serve(stream: TcpStream, handler: Arc<Handler>, recorded: Arc<Mutex<Vec<MockRequest>>>) {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0usize;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let mut body = vec![0u8; content_length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+
+    let request = MockRequest {
+        method,
+        path,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    };
+    recorded
+        .lock()
+        .expect("mock requests")
+        .push(request.clone());
+    let response = handler(&request);
+
+    let mut stream = reader.into_inner();
+    let payload = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.body.len(),
+        response.body
+    );
+    let _ = stream.write_all(payload.as_bytes());
+    let _ = stream.flush();
+}
+
+/// Wraps `content` in an OpenAI-style chat completion body.
+pub fn chat_completion(content: &str) -> String {
+    serde_json::json!({
+        "choices": [
+            { "message": { "role": "assistant", "content": content } }
+        ]
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_server_records_requests_and_answers_from_the_handler() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/chat" => MockResponse::ok(chat_completion("hello")),
+            _ => MockResponse::status(404, "missing"),
+        });
+        let client = reqwest::blocking::Client::new();
+
+        let ok = client
+            .post(format!("{}/chat", server.url()))
+            .body("ping")
+            .send()
+            .expect("chat request");
+        assert_eq!(ok.status().as_u16(), 200);
+        let body: serde_json::Value = ok.json().expect("json body");
+        assert_eq!(body["choices"][0]["message"]["content"], "hello");
+
+        let missing = client
+            .get(format!("{}/other", server.url()))
+            .send()
+            .expect("other request");
+        assert_eq!(missing.status().as_u16(), 404);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/chat");
+        assert_eq!(requests[0].body, "ping");
+        assert_eq!(requests[1].method, "GET");
+    }
+}
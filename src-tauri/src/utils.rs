@@ -1,33 +1,151 @@
+pub mod cache;
+
 use dirs::data_dir;
 use once_cell::sync::Lazy;
-use std::{fs, path::PathBuf};
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const DATA_DIR_ENV: &str = "SHOW_SCRAPER_DATA_DIR";
+const DB_PATH_ENV: &str = "SHOW_SCRAPER_DB_PATH";
+const CONFIG_PATH_ENV: &str = "SHOW_SCRAPER_CONFIG_PATH";
 
 static DATA_ROOT: Lazy<PathBuf> = Lazy::new(|| {
-    let base = data_dir()
-        .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
-    let root = base.join("show-scrape");
+    let root = resolve_data_root();
     if let Err(err) = fs::create_dir_all(&root) {
         eprintln!("failed to create data root {:?}: {err}", root);
     }
     root
 });
 
+fn resolve_data_root() -> PathBuf {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|value| !value.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    let base = data_dir()
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    base.join("show-scrape")
+}
+
 pub fn data_root() -> PathBuf {
     DATA_ROOT.clone()
 }
 
+/// Verifies the data root can be created and written to, so startup can report a
+/// readable error instead of a later SQLite failure.
+pub fn ensure_writable_data_root() -> Result<PathBuf, String> {
+    let root = data_root();
+    ensure_writable_dir(&root)?;
+    Ok(root)
+}
+
+fn ensure_writable_dir(dir: &Path) -> Result<(), String> {
+    let hint = format!("set {DATA_DIR_ENV} to a writable folder");
+    fs::create_dir_all(dir).map_err(|err| {
+        format!(
+            "data directory {} could not be created: {err}; {hint}",
+            dir.display()
+        )
+    })?;
+    let probe = dir.join(".write-test");
+    fs::write(&probe, b"ok").map_err(|err| {
+        format!(
+            "data directory {} is not writable: {err}; {hint}",
+            dir.display()
+        )
+    })?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
 pub fn database_path() -> PathBuf {
-    data_root().join("show-scrape.sqlite")
+    path_override(
+        std::env::var_os(DB_PATH_ENV),
+        data_root().join("show-scrape.sqlite"),
+    )
 }
 
 pub fn config_path() -> PathBuf {
-    data_root().join("config.json")
+    path_override(
+        std::env::var_os(CONFIG_PATH_ENV),
+        data_root().join("config.json"),
+    )
+}
+
+/// Hand-maintained artist → genres map for acts MusicBrainz doesn't know.
+pub fn genre_overrides_path() -> PathBuf {
+    data_root().join("genre_overrides.json")
 }
 
-pub fn ensure_parent(path: &PathBuf) {
+/// The path an env override names, else `default`. An override's parent
+/// directory is created so callers can open it straight away.
+fn path_override(value: Option<OsString>, default: PathBuf) -> PathBuf {
+    match value.filter(|value| !value.is_empty()) {
+        Some(value) => {
+            let path = PathBuf::from(value);
+            ensure_parent(&path);
+            path
+        }
+        None => default,
+    }
+}
+
+pub fn ensure_parent(path: &Path) {
     if let Some(parent) = path.parent() {
         if let Err(err) = fs::create_dir_all(parent) {
             eprintln!("failed to create parent {:?}: {err}", parent);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("show-scrape-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn writable_dir_is_created_and_accepted() {
+        let dir = scratch_dir("writable").join("nested");
+        ensure_writable_dir(&dir).expect("temp dir should be writable");
+        assert!(dir.is_dir());
+        assert!(!dir.join(".write-test").exists());
+        let _ = fs::remove_dir_all(dir.parent().expect("parent"));
+    }
+
+    #[test]
+    fn path_overrides_win_over_the_default() {
+        let root = scratch_dir("overrides");
+        let db = root.join("profiles").join("ci.sqlite");
+        let default = root.join("default.sqlite");
+
+        assert_eq!(
+            path_override(Some(db.clone().into_os_string()), default.clone()),
+            db
+        );
+        assert!(db.parent().expect("db parent").is_dir());
+        assert_eq!(path_override(None, default.clone()), default);
+        assert_eq!(
+            path_override(Some(OsString::new()), default.clone()),
+            default
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn invalid_dir_reports_actionable_error() {
+        let root = scratch_dir("invalid");
+        fs::create_dir_all(&root).expect("scratch root");
+        let blocker = root.join("not-a-dir");
+        fs::write(&blocker, b"file").expect("blocker file");
+
+        let err = ensure_writable_dir(&blocker.join("data")).expect_err("path under a file");
+        assert!(err.contains("could not be created"), "{err}");
+        assert!(err.contains(DATA_DIR_ENV), "{err}");
+        let _ = fs::remove_dir_all(root);
+    }
+}
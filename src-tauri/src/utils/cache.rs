@@ -0,0 +1,177 @@
+//! Size-capped file cache for snapshots and HTTP responses. Entries are files
+//! named by a hash of their key; reading one refreshes its mtime, and pruning
+//! drops the least recently used files until the directory fits the cap.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
+
+use crate::config::AppConfig;
+
+pub const DEFAULT_MAX_MB: u64 = 100;
+const CACHE_DIR_ENV: &str = "SHOW_SCRAPER_CACHE_DIR";
+
+pub struct Cache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self { dir, max_bytes }
+    }
+
+    /// The app cache: `SHOW_SCRAPER_CACHE_DIR`, else `cache_dir` from config,
+    /// else `cache/` under the data root, capped at `cache_max_mb`.
+    pub fn open_default() -> Self {
+        let config = AppConfig::load().unwrap_or_default();
+        let dir = std::env::var_os(CACHE_DIR_ENV)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .or(config.cache_dir)
+            .unwrap_or_else(|| super::data_root().join("cache"));
+        Self::new(dir, config.cache_max_mb.saturating_mul(1024 * 1024))
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:x}", Sha256::digest(key.as_bytes())))
+    }
+
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let path = self.entry_path(key);
+        let bytes = fs::read(&path).ok()?;
+        if let Err(err) = touch(&path) {
+            eprintln!("failed to refresh cache entry {}: {err}", path.display());
+        }
+        Some(bytes)
+    }
+
+    /// Stores `bytes` under `key`, then evicts old entries if the cache is over
+    /// its cap.
+    pub fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.entry_path(key);
+        fs::write(&path, bytes)?;
+        touch(&path)?;
+        self.prune()?;
+        Ok(())
+    }
+
+    /// Removes least recently used entries until the cache fits its cap,
+    /// returning how many were removed.
+    pub fn prune(&self) -> io::Result<usize> {
+        let mut entries = Vec::new();
+        let listing = match fs::read_dir(&self.dir) {
+            Ok(listing) => listing,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err),
+        };
+        for entry in listing {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            if meta.is_file() {
+                let used = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                entries.push((used, meta.len(), entry.path()));
+            }
+        }
+        let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+        entries.sort_by_key(|(used, _, _)| *used);
+        let mut removed = 0;
+        for (_, len, path) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            fs::remove_file(&path)?;
+            total -= len;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
+
+fn touch(path: &std::path::Path) -> io::Result<()> {
+    fs::File::options()
+        .append(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+pub fn cache_get(key: &str) -> Option<Vec<u8>> {
+    Cache::open_default().get(key)
+}
+
+pub fn cache_put(key: &str, bytes: &[u8]) -> io::Result<()> {
+    Cache::open_default().put(key, bytes)
+}
+
+/// Enforces the configured cap, e.g. after it was lowered.
+pub fn cache_prune() -> io::Result<usize> {
+    Cache::open_default().prune()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn scratch_cache(name: &str, max_bytes: u64) -> Cache {
+        let dir =
+            std::env::temp_dir().join(format!("show-scrape-cache-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Cache::new(dir, max_bytes)
+    }
+
+    fn age(cache: &Cache, key: &str, secs_ago: u64) {
+        fs::File::options()
+            .append(true)
+            .open(cache.entry_path(key))
+            .expect("entry")
+            .set_modified(SystemTime::now() - Duration::from_secs(secs_ago))
+            .expect("set mtime");
+    }
+
+    #[test]
+    fn entries_round_trip() {
+        let cache = scratch_cache("roundtrip", 1024);
+        cache
+            .put("https://example.com/shows", b"<html>")
+            .expect("put");
+        assert_eq!(
+            cache.get("https://example.com/shows").as_deref(),
+            Some(&b"<html>"[..])
+        );
+        assert!(cache.get("missing").is_none());
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn exceeding_the_cap_evicts_least_recently_used() {
+        let cache = scratch_cache("evict", 25);
+        cache.put("old", &[0; 10]).expect("old");
+        cache.put("mid", &[1; 10]).expect("mid");
+        age(&cache, "old", 300);
+        age(&cache, "mid", 200);
+
+        cache.put("new", &[2; 10]).expect("new");
+        assert!(cache.get("old").is_none());
+        assert!(cache.get("mid").is_some());
+        assert!(cache.get("new").is_some());
+
+        // Reading "mid" made it the freshest, so "new" goes next.
+        age(&cache, "new", 100);
+        cache.put("newest", &[3; 10]).expect("newest");
+        assert!(cache.get("new").is_none());
+        assert!(cache.get("mid").is_some());
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn prune_on_a_missing_dir_is_a_no_op() {
+        let cache = scratch_cache("missing", 0);
+        assert_eq!(cache.prune().expect("prune"), 0);
+    }
+}
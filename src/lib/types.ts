@@ -19,7 +19,13 @@ export const EventSchema = z.object({
   start_utc: z.string(),
   doors_local: z.string().nullable().optional(),
   artists: z.array(z.string()),
-  is_all_ages: z.boolean().nullable().optional(),
+  age_restriction: z
+    .union([
+      z.literal("all_ages"),
+      z.literal("unknown"),
+      z.object({ min_age: z.number() }),
+    ])
+    .optional(),
   ticket_url: z.string().nullable().optional(),
   event_url: z.string().nullable().optional(),
   price_min_cents: z.number().nullable().optional(),